@@ -0,0 +1,34 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Feed arbitrary bytes, e.g., quotes received from a hostile peer, to the
+//! quote parsers, which must return an error rather than panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use teaclave_attestation::quote::{SgxEcdsaSignatureData, SgxEnclaveReport, SgxQuote};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = SgxQuote::parse_from(data);
+    let _ = SgxQuote::parse_from_strict(data);
+    let _ = SgxQuote::parse_prefix(data);
+    let _ = SgxEnclaveReport::parse_from(data);
+    let _ = SgxEnclaveReport::parse_from_strict(data);
+    let _ = SgxEnclaveReport::parse_from_kss(data);
+    let _ = SgxEcdsaSignatureData::parse_from(data);
+});
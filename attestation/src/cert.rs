@@ -15,7 +15,9 @@
 // specific language governing permissions and limitations
 // under the License.
 
-//! This module provides a X.509 certificate parser.
+//! This module provides a X.509 certificate parser. The typed codec of the
+//! certificates generated in the enclave is only available with
+//! `mesalock_sgx`.
 
 #![allow(clippy::unused_unit)]
 #![allow(clippy::needless_lifetimes)]
@@ -23,44 +25,55 @@
 use std::prelude::v1::*;
 
 use yasna::ASN1Result;
+#[cfg(feature = "mesalock_sgx")]
 use yasna::{BERReader, BERReaderSeq, BERReaderSet};
+#[cfg(feature = "mesalock_sgx")]
 use yasna::{DERWriter, DERWriterSeq, DERWriterSet};
 
+#[cfg(feature = "mesalock_sgx")]
 pub type Writer<'a> = DERWriter<'a>;
+#[cfg(feature = "mesalock_sgx")]
 pub type Reader<'a, 'b> = BERReader<'a, 'b>;
 
+#[cfg(feature = "mesalock_sgx")]
 pub trait ConsWriter {
     fn next(&mut self) -> Writer<'_>;
 }
 
+#[cfg(feature = "mesalock_sgx")]
 pub trait ConsReader<'a> {
     fn next(&mut self, tags: &[yasna::Tag]) -> ASN1Result<Reader<'a, '_>>;
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl ConsWriter for DERWriterSeq<'_> {
     fn next(&mut self) -> Writer<'_> {
         self.next()
     }
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl ConsWriter for DERWriterSet<'_> {
     fn next(&mut self) -> Writer<'_> {
         self.next()
     }
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl<'a> ConsReader<'a> for BERReaderSeq<'a, '_> {
     fn next(&mut self, _tags: &[yasna::Tag]) -> ASN1Result<Reader<'a, '_>> {
         Ok(self.next())
     }
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl<'a> ConsReader<'a> for BERReaderSet<'a, '_> {
     fn next(&mut self, tags: &[yasna::Tag]) -> ASN1Result<Reader<'a, '_>> {
         self.next(tags)
     }
 }
 
+#[cfg(feature = "mesalock_sgx")]
 pub trait Asn1Ty {
     type ValueTy;
     const TAG: yasna::Tag;
@@ -69,6 +82,7 @@ pub trait Asn1Ty {
     fn load<'a>(reader: Reader<'a, '_>) -> ASN1Result<Self::ValueTy>;
 }
 
+#[cfg(feature = "mesalock_sgx")]
 pub trait Asn1ConsTy
 where
     Self: std::marker::Sized,
@@ -81,6 +95,7 @@ where
         R: ConsReader<'a>;
 }
 
+#[cfg(feature = "mesalock_sgx")]
 pub trait Asn1Tag {
     const TAG: yasna::Tag;
 }
@@ -92,6 +107,7 @@ const fn context_tag(tnum: u64) -> yasna::Tag {
     }
 }
 
+#[cfg(feature = "mesalock_sgx")]
 mod no_instance {
     #![allow(dead_code)]
 
@@ -129,20 +145,25 @@ mod no_instance {
     pub(crate) struct Nil;
 }
 
+#[cfg(feature = "mesalock_sgx")]
 use no_instance::*;
 
+#[cfg(feature = "mesalock_sgx")]
 impl Asn1Tag for CtxT0 {
     const TAG: yasna::Tag = context_tag(0);
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl Asn1Tag for CtxT1 {
     const TAG: yasna::Tag = context_tag(1);
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl Asn1Tag for CtxT3 {
     const TAG: yasna::Tag = context_tag(3);
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl<U: Asn1Ty, V: Asn1ConsTy> Asn1ConsTy for Cons<U, V> {
     type ValueTy = (U::ValueTy, V::ValueTy);
 
@@ -161,6 +182,7 @@ impl<U: Asn1Ty, V: Asn1ConsTy> Asn1ConsTy for Cons<U, V> {
     }
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl Asn1ConsTy for Nil {
     type ValueTy = ();
 
@@ -173,6 +195,7 @@ impl Asn1ConsTy for Nil {
     }
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl<T: Asn1Tag, S: Asn1Ty> Asn1Ty for Tagged<T, S> {
     type ValueTy = S::ValueTy;
     const TAG: yasna::Tag = T::TAG;
@@ -186,6 +209,7 @@ impl<T: Asn1Tag, S: Asn1Ty> Asn1Ty for Tagged<T, S> {
     }
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl<U: Asn1Ty, V: Asn1ConsTy> Asn1Ty for Sequence<U, V> {
     type ValueTy = (U::ValueTy, V::ValueTy);
     const TAG: yasna::Tag = yasna::tags::TAG_SEQUENCE;
@@ -206,6 +230,7 @@ impl<U: Asn1Ty, V: Asn1ConsTy> Asn1Ty for Sequence<U, V> {
     }
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl<U: Asn1Ty, V: Asn1ConsTy> Asn1Ty for Set<U, V> {
     type ValueTy = (U::ValueTy, V::ValueTy);
     const TAG: yasna::Tag = yasna::tags::TAG_SET;
@@ -226,6 +251,7 @@ impl<U: Asn1Ty, V: Asn1ConsTy> Asn1Ty for Set<U, V> {
     }
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl Asn1Ty for U8 {
     type ValueTy = u8;
     const TAG: yasna::Tag = yasna::tags::TAG_INTEGER;
@@ -239,6 +265,7 @@ impl Asn1Ty for U8 {
     }
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl Asn1Ty for I8 {
     type ValueTy = i8;
     const TAG: yasna::Tag = yasna::tags::TAG_INTEGER;
@@ -252,6 +279,7 @@ impl Asn1Ty for I8 {
     }
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl Asn1Ty for BigUint {
     type ValueTy = num_bigint::BigUint;
     const TAG: yasna::Tag = yasna::tags::TAG_INTEGER;
@@ -265,6 +293,7 @@ impl Asn1Ty for BigUint {
     }
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl Asn1Ty for Utf8Str {
     type ValueTy = String;
     const TAG: yasna::Tag = yasna::tags::TAG_UTF8STRING;
@@ -278,6 +307,7 @@ impl Asn1Ty for Utf8Str {
     }
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl Asn1Ty for Oid {
     type ValueTy = yasna::models::ObjectIdentifier;
     const TAG: yasna::Tag = yasna::tags::TAG_OID;
@@ -291,6 +321,7 @@ impl Asn1Ty for Oid {
     }
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl Asn1Ty for UtcTime {
     type ValueTy = yasna::models::UTCTime;
     const TAG: yasna::Tag = yasna::tags::TAG_UTCTIME;
@@ -304,6 +335,7 @@ impl Asn1Ty for UtcTime {
     }
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl Asn1Ty for BitVec {
     type ValueTy = bit_vec::BitVec;
     const TAG: yasna::Tag = yasna::tags::TAG_BITSTRING;
@@ -317,6 +349,7 @@ impl Asn1Ty for BitVec {
     }
 }
 
+#[cfg(feature = "mesalock_sgx")]
 impl Asn1Ty for Bytes {
     type ValueTy = Vec<u8>;
     const TAG: yasna::Tag = yasna::tags::TAG_OCTETSTRING;
@@ -330,6 +363,7 @@ impl Asn1Ty for Bytes {
     }
 }
 
+#[cfg(feature = "mesalock_sgx")]
 macro_rules! cons {
     () => { Nil };
     ($t: ty) => { Cons<$t, Nil> };
@@ -338,6 +372,7 @@ macro_rules! cons {
     };
 }
 
+#[cfg(feature = "mesalock_sgx")]
 macro_rules! asn1_seq_ty {
     ($t: ty) => { Sequence<$t, Nil> };
     ($t: ty, $($tt: ty),* $(,)?) => {
@@ -345,6 +380,7 @@ macro_rules! asn1_seq_ty {
     };
 }
 
+#[cfg(feature = "mesalock_sgx")]
 macro_rules! asn1_set_ty {
     ($t: ty) => { Set<$t, Nil> };
     ($t: ty, $($tt: ty),* $(,)?) => {
@@ -363,15 +399,25 @@ macro_rules! asn1_seq {
     };
 }
 
+#[cfg(feature = "mesalock_sgx")]
 pub(crate) type Version = Tagged<CtxT0, I8>;
+#[cfg(feature = "mesalock_sgx")]
 pub(crate) type Serial = U8;
+#[cfg(feature = "mesalock_sgx")]
 pub(crate) type CertSignAlgo = asn1_seq_ty!(Oid);
+#[cfg(feature = "mesalock_sgx")]
 pub(crate) type ValidRange = asn1_seq_ty!(UtcTime, UtcTime);
+#[cfg(feature = "mesalock_sgx")]
 pub(crate) type Issuer = asn1_seq_ty!(asn1_set_ty!(asn1_seq_ty!(Oid, Utf8Str)));
+#[cfg(feature = "mesalock_sgx")]
 pub(crate) type Subject = Issuer;
+#[cfg(feature = "mesalock_sgx")]
 pub(crate) type PubKeyAlgo = asn1_seq_ty!(Oid, Oid);
+#[cfg(feature = "mesalock_sgx")]
 pub(crate) type PubKey = asn1_seq_ty!(PubKeyAlgo, BitVec);
+#[cfg(feature = "mesalock_sgx")]
 pub(crate) type SgxRaCertExt = Tagged<CtxT3, asn1_seq_ty!(asn1_seq_ty!(Oid, Bytes))>;
+#[cfg(feature = "mesalock_sgx")]
 pub(crate) type TbsCert = asn1_seq_ty!(
     Version,
     Serial,
@@ -382,5 +428,78 @@ pub(crate) type TbsCert = asn1_seq_ty!(
     PubKey,
     SgxRaCertExt,
 );
-pub(crate) type CertSig = BitVec;
-pub(crate) type X509 = asn1_seq_ty!(TbsCert, CertSignAlgo, CertSig);
+
+/// An extension of a X.509 certificate.
+pub(crate) struct Extension {
+    pub(crate) oid: yasna::models::ObjectIdentifier,
+    pub(crate) value: Vec<u8>,
+}
+
+/// Fields of the `TBSCertificate` of an arbitrary X.509 certificate. All but
+/// the extensions are DER encoded.
+pub(crate) struct TbsCertFields {
+    pub(crate) serial: Vec<u8>,
+    pub(crate) issuer: Vec<u8>,
+    pub(crate) subject: Vec<u8>,
+    pub(crate) subject_public_key_info: Vec<u8>,
+    pub(crate) extensions: Vec<Extension>,
+}
+
+/// Parse the extensions of an arbitrary X.509 certificate in DER, following
+/// the `TBSCertificate` structure of RFC 5280.
+pub(crate) fn extensions(cert_der: &[u8]) -> ASN1Result<Vec<Extension>> {
+    tbs_cert_fields(cert_der).map(|fields| fields.extensions)
+}
+
+/// Parse the fields of the `TBSCertificate` of an arbitrary X.509 certificate
+/// in DER.
+pub(crate) fn tbs_cert_fields(cert_der: &[u8]) -> ASN1Result<TbsCertFields> {
+    yasna::parse_der(cert_der, |reader| {
+        reader.read_sequence(|reader| {
+            let fields = reader.next().read_sequence(|reader| {
+                // version [0] EXPLICIT Version DEFAULT v1
+                reader
+                    .read_optional(|reader| reader.read_tagged(context_tag(0), |r| r.read_der()))?;
+                let serial = reader.next().read_der()?;
+                // signature
+                reader.next().read_der()?;
+                let issuer = reader.next().read_der()?;
+                // validity
+                reader.next().read_der()?;
+                let subject = reader.next().read_der()?;
+                let subject_public_key_info = reader.next().read_der()?;
+                // issuerUniqueID [1] and subjectUniqueID [2]
+                for tnum in 1..3 {
+                    reader.read_optional(|reader| {
+                        reader.read_tagged_implicit(context_tag(tnum), |r| r.read_bitvec_bytes())
+                    })?;
+                }
+                // extensions [3] EXPLICIT Extensions OPTIONAL
+                let extensions = reader.read_optional(|reader| {
+                    reader.read_tagged(context_tag(3), |reader| {
+                        reader.collect_sequence_of(|reader| {
+                            reader.read_sequence(|reader| {
+                                let oid = reader.next().read_oid()?;
+                                // critical BOOLEAN DEFAULT FALSE
+                                reader.read_optional(|reader| reader.read_bool())?;
+                                let value = reader.next().read_bytes()?;
+                                Ok(Extension { oid, value })
+                            })
+                        })
+                    })
+                })?;
+                Ok(TbsCertFields {
+                    serial,
+                    issuer,
+                    subject,
+                    subject_public_key_info,
+                    extensions: extensions.unwrap_or_default(),
+                })
+            })?;
+            // signatureAlgorithm, signatureValue
+            reader.next().read_der()?;
+            reader.next().read_der()?;
+            Ok(fields)
+        })
+    })
+}
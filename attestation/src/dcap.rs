@@ -0,0 +1,1467 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! This module provides types used to verify evidences of the Intel SGX Data
+//! Center Attestation Primitives (DCAP), i.e., ECDSA-based attestation.
+
+use std::prelude::v1::*;
+
+use crate::quote::ct_eq;
+use crate::report::{
+    report_age, AttestationReport, ClockSource, CpuSvn, PlatformIdentity, SgxEcdsaQuoteAkType,
+    SgxEnclaveReport, SgxQuote, SgxQuoteStatus, SgxQuoteVersion,
+};
+use crate::AttestationError;
+
+pub use crate::quote::{CertificationDataType, QeCertificationData, SgxEcdsaSignatureData};
+
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+#[cfg(feature = "mesalock_sgx")]
+use std::untrusted::time::SystemTimeEx;
+
+use anyhow::{anyhow, bail, ensure, Error, Result};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use yasna::models::ObjectIdentifier;
+
+/// OID of the SGX extension in PCK certificates.
+const SGX_EXTENSION_OID: &[u64] = &[1, 2, 840, 113_741, 1, 13, 1];
+/// OID of the PPID field in the SGX extension.
+const PPID_OID: &[u64] = &[1, 2, 840, 113_741, 1, 13, 1, 1];
+/// OID of the TCB field in the SGX extension, whose entries are identified by
+/// the OID suffixed with 1 to 16 (SGX TCB components) and 17 (PCESVN).
+const TCB_OID: &[u64] = &[1, 2, 840, 113_741, 1, 13, 1, 2];
+/// OID of the PCE-ID field in the SGX extension.
+const PCE_ID_OID: &[u64] = &[1, 2, 840, 113_741, 1, 13, 1, 3];
+/// OID of the FMSPC field in the SGX extension.
+const FMSPC_OID: &[u64] = &[1, 2, 840, 113_741, 1, 13, 1, 4];
+
+/// Version of TDX quotes supported by `TdxQeReportCertificationData`.
+const TDX_QUOTE_VERSION: u16 = 4;
+/// TEE type of TDX in the quote header.
+pub(crate) const TDX_TEE_TYPE: u32 = 0x81;
+/// Size of the quote header and the TD report body of a TDX quote.
+const TDX_QUOTE_HEADER_SIZE: usize = 48;
+const TDX_TD_REPORT_SIZE: usize = 584;
+/// Type of the certification data containing the QE report.
+const QE_REPORT_CERT_DATA_TYPE: u16 = 6;
+/// Size of an SGX enclave report.
+const SGX_REPORT_SIZE: usize = 384;
+/// Size of the quote header and the enclave report of an SGX quote.
+const SGX_QUOTE_BODY_SIZE: usize = 48 + SGX_REPORT_SIZE;
+/// OID of ECDSA with SHA-256, which signs the CRLs of the PCK CA chain.
+const ECDSA_WITH_SHA256_OID: &[u64] = &[1, 2, 840, 10045, 4, 3, 2];
+/// OID of RSA PKCS #1 v1.5 with SHA-256, which signs the CRLs of the report
+/// CA of the attestation service.
+const RSA_WITH_SHA256_OID: &[u64] = &[1, 2, 840, 113549, 1, 1, 11];
+/// Certificate chains of the quote signing key, ending at the Intel SGX Root
+/// CA, are all signed with ECDSA P-256 SHA-256.
+static PCK_CERT_SIG_ALGS: &[&webpki::SignatureAlgorithm] = &[&webpki::ECDSA_P256_SHA256];
+
+/// Parse the entries of the SGX extension of a PCK certificate in DER.
+fn sgx_extension_entries(cert_der: &[u8]) -> Result<Vec<(ObjectIdentifier, Vec<u8>)>> {
+    let sgx_extension_oid = ObjectIdentifier::from_slice(SGX_EXTENSION_OID);
+    let sgx_extension = crate::cert::extensions(cert_der)?
+        .into_iter()
+        .find(|e| e.oid == sgx_extension_oid)
+        .ok_or_else(|| anyhow!("SGX extension is missing in the PCK certificate."))?;
+
+    let entries = yasna::parse_der(&sgx_extension.value, |reader| {
+        reader.collect_sequence_of(|reader| {
+            reader.read_sequence(|reader| {
+                let oid = reader.next().read_oid()?;
+                let value = reader.next().read_der()?;
+                Ok((oid, value))
+            })
+        })
+    })?;
+
+    Ok(entries)
+}
+
+/// Raw public key in the SubjectPublicKeyInfo of a certificate in DER.
+pub(crate) fn subject_public_key(cert_der: &[u8]) -> Result<Vec<u8>> {
+    // SubjectPublicKeyInfo ::= SEQUENCE {
+    //      algorithm            AlgorithmIdentifier,
+    //      subjectPublicKey     BIT STRING }
+    let spki = crate::cert::tbs_cert_fields(cert_der)?.subject_public_key_info;
+    let public_key = yasna::parse_der(&spki, |reader| {
+        reader.read_sequence(|reader| {
+            reader.next().read_der()?;
+            let (public_key, _) = reader.next().read_bitvec_bytes()?;
+            Ok(public_key)
+        })
+    })?;
+
+    Ok(public_key)
+}
+
+/// Platform identities in the SGX extension of a PCK (Provisioning
+/// Certification Key) certificate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PckCertSgxExtension {
+    /// Platform provisioning ID of the processor package or platform instance
+    pub ppid: Vec<u8>,
+    /// ID of the Provisioning Certification Enclave
+    pub pce_id: [u8; 2],
+    /// Family-Model-Stepping-Platform-CustomSKU of the platform
+    pub fmspc: [u8; 6],
+}
+
+impl PckCertSgxExtension {
+    /// Parse the SGX extension from a PCK certificate in DER.
+    pub fn from_pck_cert(cert_der: &[u8]) -> Result<Self> {
+        let entries = sgx_extension_entries(cert_der)?;
+        let octets = |oid: &[u64], name: &str| -> Result<Vec<u8>> {
+            let oid = ObjectIdentifier::from_slice(oid);
+            let (_, value) = entries
+                .iter()
+                .find(|(o, _)| *o == oid)
+                .ok_or_else(|| anyhow!("{} is missing in the SGX extension.", name))?;
+            Ok(yasna::parse_der(value, |reader| reader.read_bytes())?)
+        };
+
+        Ok(Self {
+            ppid: octets(PPID_OID, "PPID")?,
+            pce_id: <[u8; 2]>::try_from(octets(PCE_ID_OID, "PCE-ID")?.as_slice())?,
+            fmspc: <[u8; 6]>::try_from(octets(FMSPC_OID, "FMSPC")?.as_slice())?,
+        })
+    }
+
+    /// Verify that the FMSPC of the platform is one of the `allowed` FMSPCs.
+    pub fn verify_fmspc(&self, allowed: &[[u8; 6]]) -> Result<()> {
+        ensure!(
+            allowed.contains(&self.fmspc),
+            AttestationError::FmspcNotAllowed
+        );
+        Ok(())
+    }
+}
+
+/// TCB level of the platform in the SGX extension of a PCK certificate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PckCertTcb {
+    /// SVNs of the 16 SGX TCB components
+    pub sgx_tcb_comp_svn: [u8; 16],
+    /// Security version number of the PCE
+    pub pce_svn: u16,
+}
+
+impl PckCertTcb {
+    /// Parse the TCB field of the SGX extension from a PCK certificate in DER.
+    pub fn from_pck_cert(cert_der: &[u8]) -> Result<Self> {
+        let tcb_oid = ObjectIdentifier::from_slice(TCB_OID);
+        let (_, tcb) = sgx_extension_entries(cert_der)?
+            .into_iter()
+            .find(|(oid, _)| *oid == tcb_oid)
+            .ok_or_else(|| anyhow!("TCB is missing in the SGX extension."))?;
+
+        let entries = yasna::parse_der(&tcb, |reader| {
+            reader.collect_sequence_of(|reader| {
+                reader.read_sequence(|reader| {
+                    let oid = reader.next().read_oid()?;
+                    let value = reader.next().read_der()?;
+                    Ok((oid, value))
+                })
+            })
+        })?;
+        let svn = |index: u64| -> Result<u64> {
+            let mut oid = TCB_OID.to_vec();
+            oid.push(index);
+            let oid = ObjectIdentifier::new(oid);
+            let (_, value) = entries
+                .iter()
+                .find(|(o, _)| *o == oid)
+                .ok_or_else(|| anyhow!("TCB component {} is missing.", index))?;
+            Ok(yasna::parse_der(value, |reader| reader.read_u64())?)
+        };
+
+        let mut sgx_tcb_comp_svn = [0u8; 16];
+        for (i, comp_svn) in sgx_tcb_comp_svn.iter_mut().enumerate() {
+            *comp_svn = u8::try_from(svn(i as u64 + 1)?)?;
+        }
+
+        Ok(Self {
+            sgx_tcb_comp_svn,
+            pce_svn: u16::try_from(svn(17)?)?,
+        })
+    }
+}
+
+/// A TCB level of the TCB info collateral, and the status of platforms at
+/// (or above) the level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TcbLevel {
+    /// SVNs of the 16 SGX TCB components
+    pub sgx_tcb_comp_svn: [u8; 16],
+    /// Security version number of the PCE
+    pub pce_svn: u16,
+    /// Status of the TCB level
+    pub tcb_status: SgxQuoteStatus,
+}
+
+impl TcbLevel {
+    /// The TCB of a platform is at (or above) the level if none of its SVNs is
+    /// lower than the level.
+    fn is_met_by(&self, tcb: &PckCertTcb) -> bool {
+        CpuSvn(tcb.sgx_tcb_comp_svn).meets_minimum(&CpuSvn(self.sgx_tcb_comp_svn))
+            && tcb.pce_svn >= self.pce_svn
+    }
+}
+
+/// TCB information of a platform model (FMSPC) from the TCB info collateral
+/// of Intel PCS, i.e., the `tcbInfo` object of:
+///
+/// ```json
+/// {
+///   "tcbInfo": {
+///     "version": 2,
+///     "issueDate": "2021-01-01T00:00:00Z",
+///     "nextUpdate": "2021-02-01T00:00:00Z",
+///     "fmspc": "00906ea10000",
+///     "pceId": "0000",
+///     "tcbLevels": [{
+///       "tcb": { "sgxtcbcomp01svn": 2, ..., "pcesvn": 11 },
+///       "tcbStatus": "UpToDate"
+///     }, ...],
+///     ...
+///   },
+///   "signature": "<hex>"
+/// }
+/// ```
+///
+/// The SVNs of version 3 (`"sgxtcbcomponents": [{ "svn": 2 }, ...]`) are also
+/// accepted. The signature is over the raw JSON text of `tcbInfo`, made by the
+/// TCB signing certificate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TcbInfo {
+    /// Family-Model-Stepping-Platform-CustomSKU of the platform model
+    pub fmspc: [u8; 6],
+    /// ID of the Provisioning Certification Enclave
+    pub pce_id: [u8; 2],
+    /// Time when the TCB info is issued
+    pub issue_date: SystemTime,
+    /// Time before which the next TCB info will be issued
+    pub next_update: SystemTime,
+    /// TCB levels sorted in descending order
+    pub tcb_levels: Vec<TcbLevel>,
+}
+
+/// Quote status of a platform at a TCB level with the given `tcbStatus`.
+pub(crate) fn tcb_status_from_str(status: &str) -> SgxQuoteStatus {
+    match status {
+        "UpToDate" => SgxQuoteStatus::OK,
+        "SWHardeningNeeded" => SgxQuoteStatus::SwHardeningNeeded,
+        "ConfigurationNeeded" => SgxQuoteStatus::ConfigurationNeeded,
+        "ConfigurationAndSWHardeningNeeded" => SgxQuoteStatus::ConfigurationAndSwHardeningNeeded,
+        "OutOfDate" => SgxQuoteStatus::OutOfDate,
+        "OutOfDateConfigurationNeeded" => SgxQuoteStatus::OutOfDateConfigurationNeeded,
+        "Revoked" => SgxQuoteStatus::KeyRevoked,
+        _ => SgxQuoteStatus::UnknownBadStatus(status.to_string()),
+    }
+}
+
+/// Strip the JSON whitespace around `bytes`.
+fn trim_json_whitespace(bytes: &[u8]) -> &[u8] {
+    let is_text = |b: &u8| !matches!(b, b' ' | b'\t' | b'\n' | b'\r');
+    let start = bytes.iter().position(is_text).unwrap_or(bytes.len());
+    let end = bytes.iter().rposition(is_text).map_or(start, |i| i + 1);
+    &bytes[start..end]
+}
+
+/// Raw JSON text of the value of the member `key` of the top-level object in
+/// `json`, i.e., the bytes covered by the signature of a collateral.
+fn raw_json_member<'a>(json: &'a [u8], key: &str) -> Result<&'a [u8]> {
+    let value: Value = serde_json::from_slice(json)?;
+    ensure!(value.is_object(), "Invalid collateral.");
+    let key = serde_json::to_vec(key)?;
+
+    let (mut depth, mut in_string, mut escaped) = (0, false, false);
+    // Start of the current member of the top-level object, and of its value
+    let (mut member_start, mut value_start) = (0, None);
+    for (i, &byte) in json.iter().enumerate() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => (),
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                if depth == 1 {
+                    member_start = i + 1;
+                }
+            }
+            b':' if depth == 1 && value_start.is_none() => value_start = Some(i + 1),
+            b',' | b'}' | b']' if depth == 1 => {
+                if let Some(start) = value_start.take() {
+                    if trim_json_whitespace(&json[member_start..start - 1]) == key.as_slice() {
+                        return Ok(trim_json_whitespace(&json[start..i]));
+                    }
+                }
+                member_start = i + 1;
+            }
+            _ => (),
+        }
+        if matches!(byte, b'}' | b']') {
+            depth -= 1;
+        }
+    }
+
+    bail!("Invalid collateral.")
+}
+
+/// Verify a collateral `{ "<body>": {...}, "signature": "<hex>" }` of Intel
+/// PCS, i.e., that the signature over the raw JSON text of `body` is made by
+/// the `tcb_signing_cert` in DER with ECDSA P-256 SHA-256, and return the
+/// body. The body is parsed from the signed text only, so that other members
+/// (e.g., a duplicated `body`) cannot alter it.
+fn verify_signed_collateral(
+    collateral: &[u8],
+    body: &str,
+    tcb_signing_cert: &[u8],
+) -> Result<Value> {
+    let signed = raw_json_member(collateral, body)?;
+    let signature = raw_json_member(collateral, "signature")?;
+    let signature = hex::decode(serde_json::from_slice::<String>(signature)?)?;
+    let public_key = subject_public_key(tcb_signing_cert)?;
+    ring::signature::UnparsedPublicKey::new(&ring::signature::ECDSA_P256_SHA256_FIXED, &public_key)
+        .verify(signed, &signature)
+        .map_err(|_| AttestationError::SignatureInvalid)?;
+
+    Ok(serde_json::from_slice(signed)?)
+}
+
+impl TcbInfo {
+    /// Parse the TCB info collateral in JSON, without verifying its signature.
+    pub fn from_json(collateral: &[u8]) -> Result<Self> {
+        let collateral: Value = serde_json::from_slice(collateral)?;
+        Self::from_value(&collateral["tcbInfo"])
+    }
+
+    /// Parse the TCB info collateral in JSON, and verify that it is signed by
+    /// the `tcb_signing_cert` in DER. An invalid signature results in
+    /// `AttestationError::SignatureInvalid`.
+    pub fn verify_and_parse(collateral: &[u8], tcb_signing_cert: &[u8]) -> Result<Self> {
+        Self::from_value(&verify_signed_collateral(
+            collateral,
+            "tcbInfo",
+            tcb_signing_cert,
+        )?)
+    }
+
+    fn from_value(tcb_info: &Value) -> Result<Self> {
+        let error = || anyhow!("Invalid TCB info.");
+        let time = |key: &str| -> Result<SystemTime> {
+            let time = tcb_info[key].as_str().ok_or_else(error)?;
+            to_system_time(&DateTime::parse_from_rfc3339(time)?.with_timezone(&Utc))
+        };
+
+        let fmspc = hex::decode(tcb_info["fmspc"].as_str().ok_or_else(error)?)?;
+        let pce_id = hex::decode(tcb_info["pceId"].as_str().ok_or_else(error)?)?;
+        let mut tcb_levels = Vec::new();
+        for level in tcb_info["tcbLevels"].as_array().ok_or_else(error)? {
+            let tcb = &level["tcb"];
+            let mut sgx_tcb_comp_svn = [0u8; 16];
+            for (i, comp_svn) in sgx_tcb_comp_svn.iter_mut().enumerate() {
+                let svn = match tcb["sgxtcbcomponents"].as_array() {
+                    Some(components) => components.get(i).map(|c| &c["svn"]),
+                    None => tcb.get(format!("sgxtcbcomp{:02}svn", i + 1)),
+                };
+                *comp_svn = u8::try_from(svn.and_then(Value::as_u64).ok_or_else(error)?)?;
+            }
+            let pce_svn = tcb["pcesvn"].as_u64().ok_or_else(error)?;
+            let tcb_status = level["tcbStatus"].as_str().ok_or_else(error)?;
+            tcb_levels.push(TcbLevel {
+                sgx_tcb_comp_svn,
+                pce_svn: u16::try_from(pce_svn)?,
+                tcb_status: tcb_status_from_str(tcb_status),
+            });
+        }
+
+        Ok(Self {
+            fmspc: <[u8; 6]>::try_from(fmspc.as_slice())?,
+            pce_id: <[u8; 2]>::try_from(pce_id.as_slice())?,
+            issue_date: time("issueDate")?,
+            next_update: time("nextUpdate")?,
+            tcb_levels,
+        })
+    }
+
+    /// Status of the platform with the `tcb` in its PCK certificate, i.e., the
+    /// status of the highest TCB level met by the platform.
+    pub fn tcb_status(&self, tcb: &PckCertTcb) -> Result<SgxQuoteStatus> {
+        self.tcb_levels
+            .iter()
+            .find(|level| level.is_met_by(tcb))
+            .map(|level| level.tcb_status.clone())
+            .ok_or_else(|| anyhow!("TCB level of the platform is not supported."))
+    }
+}
+
+/// Certification data of the TD Quoting Enclave (QE) embedded in the
+/// signature data of a TDX quote. The QE report is an ordinary SGX enclave
+/// report, which can be used, e.g., to check that the QE is signed by Intel.
+pub struct TdxQeReportCertificationData {
+    qe_report: SgxEnclaveReport,
+    qe_report_signature: [u8; 64],
+    qe_auth_data: Vec<u8>,
+}
+
+impl TdxQeReportCertificationData {
+    /// Parse the QE report certification data from a TDX quote (version 4).
+    pub fn parse_from_tdx_quote(quote: &[u8]) -> Result<Self> {
+        let mut pos: usize = 0;
+        let mut take = |n: usize| -> Result<&[u8]> {
+            let end = pos
+                .checked_add(n)
+                .ok_or_else(|| Error::new(AttestationError::QuoteTruncated))?;
+            if n > 0 && quote.len() >= end {
+                let ret = &quote[pos..end];
+                pos = end;
+                Ok(ret)
+            } else {
+                Err(Error::new(AttestationError::QuoteTruncated))
+            }
+        };
+
+        // off 0, size 48
+        let header = take(TDX_QUOTE_HEADER_SIZE)?;
+        let version = u16::from_le_bytes(<[u8; 2]>::try_from(&header[0..2])?);
+        let tee_type = u32::from_le_bytes(<[u8; 4]>::try_from(&header[4..8])?);
+        ensure!(
+            version == TDX_QUOTE_VERSION && tee_type == TDX_TEE_TYPE,
+            "Unsupported TDX quote version {} or TEE type {:#x}.",
+            version,
+            tee_type
+        );
+
+        // off 48, size 584
+        let _td_report = take(TDX_TD_REPORT_SIZE)?;
+
+        // off 632, size 4
+        let sig_data_len = u32::from_le_bytes(<[u8; 4]>::try_from(take(4)?)?) as usize;
+
+        // off 636, size 64 + 64: ECDSA signature and attestation key
+        let _signature = take(64)?;
+        let _attestation_key = take(64)?;
+
+        // off 764, size 2 + 4
+        let cert_data_type = u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?);
+        ensure!(
+            cert_data_type == QE_REPORT_CERT_DATA_TYPE,
+            "Unexpected certification data type {} in TDX quote.",
+            cert_data_type
+        );
+        let _cert_data_len = take(4)?;
+
+        // off 770, size 384
+        let qe_report = SgxEnclaveReport::parse_from(take(SGX_REPORT_SIZE)?)?;
+
+        // off 1154, size 64
+        let qe_report_signature = <[u8; 64]>::try_from(take(64)?)?;
+
+        // off 1218, size 2 + qe_auth_data_len
+        let qe_auth_data_len = u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?) as usize;
+        let qe_auth_data = if qe_auth_data_len > 0 {
+            take(qe_auth_data_len)?.to_vec()
+        } else {
+            Vec::new()
+        };
+
+        // The parsed fields must be within the signature data.
+        ensure!(
+            pos - (TDX_QUOTE_HEADER_SIZE + TDX_TD_REPORT_SIZE + 4) <= sig_data_len,
+            AttestationError::QuoteMalformed
+        );
+
+        Ok(Self {
+            qe_report,
+            qe_report_signature,
+            qe_auth_data,
+        })
+    }
+
+    /// Report of the TD Quoting Enclave.
+    pub fn qe_report(&self) -> &SgxEnclaveReport {
+        &self.qe_report
+    }
+
+    /// ECDSA signature over the QE report by the PCK.
+    pub fn qe_report_signature(&self) -> &[u8; 64] {
+        &self.qe_report_signature
+    }
+
+    /// Authentication data of the QE, which is bound into the report data of
+    /// the QE report together with the attestation key.
+    pub fn qe_auth_data(&self) -> &[u8] {
+        &self.qe_auth_data
+    }
+}
+
+/// Identity of the Quoting Enclave (QE) from the QE identity collateral of
+/// Intel PCS, i.e., the `enclaveIdentity` object of:
+///
+/// ```json
+/// {
+///   "enclaveIdentity": {
+///     "id": "QE",
+///     "mrsigner": "<hex>",
+///     "isvprodid": 1,
+///     "tcbLevels": [{ "tcb": { "isvsvn": 6 }, "tcbStatus": "UpToDate" }, ...],
+///     ...
+///   },
+///   "signature": "<hex>"
+/// }
+/// ```
+///
+/// As with `TcbInfo`, the signature is over the raw JSON text of
+/// `enclaveIdentity`, made by the TCB signing certificate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QeIdentity {
+    /// MRSIGNER of the QE
+    pub mr_signer: [u8; 32],
+    /// Product ID of the QE
+    pub isv_prod_id: u16,
+    /// Minimum ISVSVN of the QE with up-to-date TCB status
+    pub min_isv_svn: u16,
+}
+
+impl QeIdentity {
+    /// Parse the QE identity collateral in JSON, without verifying its
+    /// signature.
+    pub fn from_json(collateral: &[u8]) -> Result<Self> {
+        let collateral: Value = serde_json::from_slice(collateral)?;
+        Self::from_value(&collateral["enclaveIdentity"])
+    }
+
+    /// Parse the QE identity collateral in JSON, and verify that it is signed
+    /// by the `tcb_signing_cert` in DER. An invalid signature results in
+    /// `AttestationError::SignatureInvalid`.
+    pub fn verify_and_parse(collateral: &[u8], tcb_signing_cert: &[u8]) -> Result<Self> {
+        Self::from_value(&verify_signed_collateral(
+            collateral,
+            "enclaveIdentity",
+            tcb_signing_cert,
+        )?)
+    }
+
+    fn from_value(identity: &Value) -> Result<Self> {
+        let error = || anyhow!("Invalid QE identity.");
+
+        let mr_signer = hex::decode(identity["mrsigner"].as_str().ok_or_else(error)?)?;
+        let isv_prod_id = identity["isvprodid"].as_u64().ok_or_else(error)?;
+        let min_isv_svn = identity["tcbLevels"]
+            .as_array()
+            .ok_or_else(error)?
+            .iter()
+            .filter(|level| level["tcbStatus"] == "UpToDate")
+            .filter_map(|level| level["tcb"]["isvsvn"].as_u64())
+            .min()
+            .ok_or_else(error)?;
+
+        Ok(Self {
+            mr_signer: <[u8; 32]>::try_from(mr_signer.as_slice())?,
+            isv_prod_id: u16::try_from(isv_prod_id)?,
+            min_isv_svn: u16::try_from(min_isv_svn)?,
+        })
+    }
+
+    /// Verify that the QE which generated the quote matches the QE identity,
+    /// i.e., the ISVSVN of the QE in the quote is not lower than the minimum
+    /// and the QE report (in the signature data of the quote) is signed by the
+    /// MRSIGNER of the QE identity.
+    pub fn verify(&self, quote: &SgxQuote, qe_report: &SgxEnclaveReport) -> Result<()> {
+        ensure!(
+            quote.isv_svn_qe >= self.min_isv_svn
+                && qe_report.isv_svn >= self.min_isv_svn
+                && qe_report.isv_prod_id == self.isv_prod_id
+                && ct_eq(&qe_report.mr_signer, &self.mr_signer),
+            AttestationError::QeIdentityMismatch
+        );
+
+        Ok(())
+    }
+}
+
+/// A certificate revocation list (CRL) of the PCK CA chain from the DCAP
+/// collateral, i.e., the root CA CRL revoking PCK CA certificates, or the PCK
+/// CA (platform or processor) CRL revoking PCK certificates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crl {
+    /// DER encoded name of the issuer
+    issuer: Vec<u8>,
+    /// DER encoded serial numbers of the revoked certificates
+    revoked_serials: Vec<Vec<u8>>,
+}
+
+/// Read an optional `Time ::= CHOICE { UTCTime, GeneralizedTime }`.
+fn read_optional_time(
+    reader: &mut yasna::BERReaderSeq<'_, '_>,
+) -> yasna::ASN1Result<Option<DateTime<Utc>>> {
+    if let Some(time) = reader.read_optional(|reader| reader.read_utctime())? {
+        return Ok(Some(*time.datetime()));
+    }
+    let time = reader.read_optional(|reader| reader.read_generalized_time())?;
+    Ok(time.map(|time| *time.datetime()))
+}
+
+fn to_system_time(time: &DateTime<Utc>) -> Result<SystemTime> {
+    Ok(UNIX_EPOCH + Duration::from_secs(u64::try_from(time.timestamp())?))
+}
+
+impl Crl {
+    /// Parse a CRL in DER (RFC 5280), and verify that it is signed by the
+    /// `issuer_cert` in DER with ECDSA P-256 SHA-256 or RSA PKCS #1 v1.5
+    /// SHA-256, and is valid at `now`.
+    pub fn verify_and_parse(crl_der: &[u8], issuer_cert: &[u8], now: SystemTime) -> Result<Self> {
+        // CertificateList ::= SEQUENCE {
+        //      tbsCertList          TBSCertList,
+        //      signatureAlgorithm   AlgorithmIdentifier,
+        //      signatureValue       BIT STRING }
+        let (tbs_cert_list, signature_algorithm, signature) =
+            yasna::parse_der(crl_der, |reader| {
+                reader.read_sequence(|reader| {
+                    let tbs_cert_list = reader.next().read_der()?;
+                    let signature_algorithm = reader.next().read_sequence(|reader| {
+                        let oid = reader.next().read_oid()?;
+                        reader.read_optional(|reader| reader.read_der())?;
+                        Ok(oid)
+                    })?;
+                    let (signature, _) = reader.next().read_bitvec_bytes()?;
+                    Ok((tbs_cert_list, signature_algorithm, signature))
+                })
+            })?;
+        let verification_alg: &'static dyn ring::signature::VerificationAlgorithm =
+            if signature_algorithm == ObjectIdentifier::from_slice(ECDSA_WITH_SHA256_OID) {
+                &ring::signature::ECDSA_P256_SHA256_ASN1
+            } else if signature_algorithm == ObjectIdentifier::from_slice(RSA_WITH_SHA256_OID) {
+                &ring::signature::RSA_PKCS1_2048_8192_SHA256
+            } else {
+                bail!("Unsupported signature algorithm of the CRL.");
+            };
+
+        let issuer_fields = crate::cert::tbs_cert_fields(issuer_cert)?;
+        let public_key = subject_public_key(issuer_cert)?;
+        ring::signature::UnparsedPublicKey::new(verification_alg, &public_key)
+            .verify(&tbs_cert_list, &signature)
+            .map_err(|_| anyhow!("Invalid signature of the CRL."))?;
+
+        // TBSCertList ::= SEQUENCE {
+        //      version              Version OPTIONAL,
+        //      signature            AlgorithmIdentifier,
+        //      issuer               Name,
+        //      thisUpdate           Time,
+        //      nextUpdate           Time OPTIONAL,
+        //      revokedCertificates  SEQUENCE OF SEQUENCE {
+        //           userCertificate      CertificateSerialNumber,
+        //           revocationDate       Time,
+        //           crlEntryExtensions   Extensions OPTIONAL } OPTIONAL,
+        //      crlExtensions        [0] EXPLICIT Extensions OPTIONAL }
+        let (issuer, this_update, next_update, revoked_serials) =
+            yasna::parse_der(&tbs_cert_list, |reader| {
+                reader.read_sequence(|reader| {
+                    reader.read_optional(|reader| reader.read_u8())?;
+                    reader.next().read_der()?;
+                    let issuer = reader.next().read_der()?;
+                    let this_update = read_optional_time(reader)?;
+                    let next_update = read_optional_time(reader)?;
+                    let revoked_serials = reader.read_optional(|reader| {
+                        reader.collect_sequence_of(|reader| {
+                            reader.read_sequence(|reader| {
+                                let serial = reader.next().read_der()?;
+                                reader.next().read_der()?;
+                                reader.read_optional(|reader| reader.read_der())?;
+                                Ok(serial)
+                            })
+                        })
+                    })?;
+                    reader.read_optional(|reader| {
+                        reader.read_tagged(yasna::Tag::context(0), |reader| reader.read_der())
+                    })?;
+                    Ok((
+                        issuer,
+                        this_update,
+                        next_update,
+                        revoked_serials.unwrap_or_default(),
+                    ))
+                })
+            })?;
+        ensure!(
+            issuer == issuer_fields.subject,
+            "The CRL is not issued by the issuer certificate."
+        );
+
+        let this_update = this_update.ok_or_else(|| anyhow!("Invalid CRL."))?;
+        ensure!(
+            to_system_time(&this_update)? <= now,
+            "The CRL is not yet valid."
+        );
+        if let Some(next_update) = next_update {
+            ensure!(now <= to_system_time(&next_update)?, "The CRL is expired.");
+        }
+
+        Ok(Self {
+            issuer,
+            revoked_serials,
+        })
+    }
+
+    /// Check whether the certificate in DER is revoked. The certificate must
+    /// be issued by the issuer of the CRL.
+    pub fn is_revoked(&self, cert_der: &[u8]) -> Result<bool> {
+        let fields = crate::cert::tbs_cert_fields(cert_der)?;
+        ensure!(
+            fields.issuer == self.issuer,
+            "The certificate is not issued by the issuer of the CRL."
+        );
+
+        Ok(self.revoked_serials.contains(&fields.serial))
+    }
+}
+
+/// Verify that neither the PCK certificate nor the PCK CA certificate is
+/// revoked, given the PCK certificate chain in DER (the PCK certificate, the
+/// PCK CA certificate and the root CA certificate) and the root CA CRL and PCK
+/// CA CRL of the collateral. The chain itself is not verified here. A revoked
+/// certificate results in `AttestationError::CertRevoked`.
+pub fn verify_pck_cert_chain_revocation(
+    pck_cert_chain: &[Vec<u8>],
+    root_ca_crl: &[u8],
+    pck_ca_crl: &[u8],
+    now: SystemTime,
+) -> Result<()> {
+    ensure!(
+        pck_cert_chain.len() == 3,
+        "Invalid length of the PCK certificate chain."
+    );
+    let (pck_cert, pck_ca_cert, root_ca_cert) =
+        (&pck_cert_chain[0], &pck_cert_chain[1], &pck_cert_chain[2]);
+
+    let root_ca_crl = Crl::verify_and_parse(root_ca_crl, root_ca_cert, now)?;
+    ensure!(
+        !root_ca_crl.is_revoked(pck_ca_cert)?,
+        AttestationError::CertRevoked
+    );
+    let pck_ca_crl = Crl::verify_and_parse(pck_ca_crl, pck_ca_cert, now)?;
+    ensure!(
+        !pck_ca_crl.is_revoked(pck_cert)?,
+        AttestationError::CertRevoked
+    );
+
+    Ok(())
+}
+
+/// Collateral of Intel PCS to verify DCAP quotes of a platform, in the formats
+/// returned by the PCS (or a caching service).
+#[derive(Debug, Clone, Default)]
+pub struct DcapCollateral {
+    /// Intel SGX Root CA certificate in DER, which is the trust anchor of the
+    /// PCK certificate chain
+    pub root_ca_cert: Vec<u8>,
+    /// CRL of the root CA in DER
+    pub root_ca_crl: Vec<u8>,
+    /// CRL of the PCK CA (platform or processor) in DER
+    pub pck_crl: Vec<u8>,
+    /// Certificate chain in PEM of the TCB signing certificate (the
+    /// `TCB-Info-Issuer-Chain` of the PCS), i.e., the TCB signing certificate
+    /// followed by the root CA certificate, which signs both the TCB info and
+    /// the QE identity
+    pub tcb_signing_chain: Vec<u8>,
+    /// TCB info of the platform in JSON
+    pub tcb_info: Vec<u8>,
+    /// QE identity in JSON
+    pub qe_identity: Vec<u8>,
+}
+
+impl SgxQuote {
+    /// Verify the QE report in the signature data of an ECDSA quote, i.e.,
+    /// that it binds the attestation key (and the QE authentication data)
+    /// in its report data and matches `qe_identity`. The signature of the QE
+    /// report by the PCK certificate is not verified here, see
+    /// `AttestationReport::from_dcap_quote` for the full verification.
+    pub fn verify_qe_identity(&self, qe_identity: &QeIdentity) -> Result<()> {
+        let signature_data = self
+            .ecdsa_sig
+            .as_ref()
+            .ok_or_else(|| anyhow!("Signature data is missing in the quote."))?;
+        let qe_report = &signature_data.qe_report;
+        let mut ctx = ring::digest::Context::new(&ring::digest::SHA256);
+        ctx.update(&signature_data.attestation_key);
+        ctx.update(&signature_data.qe_auth_data);
+        ensure!(
+            qe_report.report_data_matches(ctx.finish().as_ref()),
+            "The attestation key is not bound by the QE report."
+        );
+
+        qe_identity.verify(self, qe_report)
+    }
+}
+
+/// Verify that the `cert` in DER chains up to the `root_ca_cert` of the DCAP
+/// collateral via the `intermediates`.
+fn verify_dcap_cert_chain(
+    cert: &[u8],
+    intermediates: &[&[u8]],
+    root_ca_cert: &[u8],
+    now: SystemTime,
+) -> Result<()> {
+    let trust_anchor = webpki::trust_anchor_util::cert_der_as_trust_anchor(root_ca_cert)?;
+    let time = webpki::Time::try_from(now).map_err(|_| anyhow!("Cannot convert time."))?;
+    webpki::EndEntityCert::from(cert)?
+        .verify_is_valid_tls_server_cert(
+            PCK_CERT_SIG_ALGS,
+            &webpki::TLSServerTrustAnchors(&[trust_anchor]),
+            intermediates,
+            time,
+        )
+        .map_err(|e| anyhow!("{:?}", e).context(AttestationError::CertChainInvalid))?;
+
+    Ok(())
+}
+
+impl AttestationReport {
+    /// Verify an SGX ECDSA quote (version 3) locally with the PCK certificate
+    /// chain in PEM (e.g., from `QeCertificationData::pck_cert_chain`) and the
+    /// DCAP `collateral`, without an attestation service:
+    ///
+    /// - the PCK certificate chain is valid up to the root CA of the collateral
+    ///   and is not revoked,
+    /// - the QE report is signed by the PCK certificate, binds the attestation
+    ///   key and matches the QE identity,
+    /// - the quote body is signed by the attestation key,
+    /// - the TCB info and the QE identity are signed by the TCB signing
+    ///   certificate of the collateral, which is valid up to the root CA.
+    ///
+    /// The quote status is the status of the TCB level of the platform in the
+    /// TCB info, and the freshness is measured from the issue date of the TCB
+    /// info. The certificate fingerprint of the report is the one of the PCK
+    /// certificate.
+    pub fn from_dcap_quote(
+        quote: &[u8],
+        pck_chain: &[u8],
+        collateral: &DcapCollateral,
+    ) -> Result<Self> {
+        let now = SystemTime::now();
+        let sgx_quote_body = SgxQuote::parse_from(quote)?;
+        match sgx_quote_body.version {
+            SgxQuoteVersion::V3(SgxEcdsaQuoteAkType::P256_256) => (),
+            _ => bail!("Unsupported attestation key type of the quote."),
+        }
+        sgx_quote_body.verify_qe_vendor()?;
+        let signature_data = sgx_quote_body
+            .ecdsa_sig
+            .as_ref()
+            .ok_or_else(|| anyhow!("Signature data is missing in the quote."))?;
+
+        // Verify the PCK certificate chain
+        let pck_chain = rustls::internal::pemfile::certs(&mut &pck_chain[..])
+            .map_err(|_| anyhow!("Invalid PCK certificate chain."))?;
+        ensure!(
+            pck_chain.len() >= 2,
+            "Invalid length of the PCK certificate chain."
+        );
+        let (pck_cert, pck_ca_cert) = (&pck_chain[0].0, &pck_chain[1].0);
+        verify_dcap_cert_chain(
+            pck_cert,
+            &[pck_ca_cert, &collateral.root_ca_cert],
+            &collateral.root_ca_cert,
+            now,
+        )?;
+        verify_pck_cert_chain_revocation(
+            &[
+                pck_cert.clone(),
+                pck_ca_cert.clone(),
+                collateral.root_ca_cert.clone(),
+            ],
+            &collateral.root_ca_crl,
+            &collateral.pck_crl,
+            now,
+        )?;
+
+        // Verify the TCB signing certificate of the TCB info
+        let tcb_signing_chain =
+            rustls::internal::pemfile::certs(&mut &collateral.tcb_signing_chain[..])
+                .map_err(|_| anyhow!("Invalid TCB signing certificate chain."))?;
+        let tcb_signing_cert = &tcb_signing_chain
+            .first()
+            .ok_or_else(|| anyhow!("Invalid length of the TCB signing certificate chain."))?
+            .0;
+        let intermediates: Vec<&[u8]> = tcb_signing_chain[1..]
+            .iter()
+            .map(|cert| cert.0.as_slice())
+            .collect();
+        verify_dcap_cert_chain(
+            tcb_signing_cert,
+            &intermediates,
+            &collateral.root_ca_cert,
+            now,
+        )?;
+
+        // Verify the QE report and the attestation key bound by it
+        let pck_public_key = subject_public_key(pck_cert)?;
+        ring::signature::UnparsedPublicKey::new(
+            &ring::signature::ECDSA_P256_SHA256_FIXED,
+            &pck_public_key,
+        )
+        .verify(
+            &signature_data.qe_report_raw,
+            &signature_data.qe_report_signature,
+        )
+        .map_err(|_| AttestationError::SignatureInvalid)?;
+        let qe_identity = QeIdentity::verify_and_parse(&collateral.qe_identity, tcb_signing_cert)?;
+        sgx_quote_body.verify_qe_identity(&qe_identity)?;
+
+        // Verify the quote body with the attestation key
+        let mut attestation_key = vec![0x04];
+        attestation_key.extend_from_slice(&signature_data.attestation_key);
+        ring::signature::UnparsedPublicKey::new(
+            &ring::signature::ECDSA_P256_SHA256_FIXED,
+            &attestation_key,
+        )
+        .verify(&quote[..SGX_QUOTE_BODY_SIZE], &signature_data.signature)
+        .map_err(|_| AttestationError::SignatureInvalid)?;
+
+        // Evaluate the TCB level of the platform
+        let sgx_extension = PckCertSgxExtension::from_pck_cert(pck_cert)?;
+        let tcb_info = TcbInfo::verify_and_parse(&collateral.tcb_info, tcb_signing_cert)?;
+        ensure!(
+            tcb_info.fmspc == sgx_extension.fmspc && tcb_info.pce_id == sgx_extension.pce_id,
+            "The TCB info does not match the platform."
+        );
+        ensure!(now <= tcb_info.next_update, "The TCB info is expired.");
+        let sgx_quote_status = tcb_info.tcb_status(&PckCertTcb::from_pck_cert(pck_cert)?)?;
+        let freshness = report_age(tcb_info.issue_date, now, Duration::from_secs(0))?;
+
+        let mut cert_fingerprint = [0u8; 32];
+        cert_fingerprint
+            .copy_from_slice(ring::digest::digest(&ring::digest::SHA256, pck_cert).as_ref());
+
+        Ok(Self {
+            freshness,
+            timestamp: tcb_info.issue_date,
+            sgx_quote_status,
+            sgx_quote_body,
+            platform_identity: Some(PlatformIdentity::Ppid(sgx_extension.ppid)),
+            advisory_ids: Vec::new(),
+            platform_info_blob: None,
+            public_key: Vec::new(),
+            cert_fingerprint,
+            clock_used: ClockSource::System(now),
+        })
+    }
+}
+
+#[cfg(all(feature = "enclave_unit_test", feature = "mesalock_sgx"))]
+pub mod tests {
+    use super::*;
+    use crate::quote::SGX_ECDSA_QUOTE_VERSION;
+    use bit_vec::BitVec;
+    use yasna::Tag;
+
+    const FMSPC: [u8; 6] = [0x00, 0x90, 0x6e, 0xa1, 0x00, 0x00];
+
+    fn pck_cert_der() -> Vec<u8> {
+        let oid = ObjectIdentifier::from_slice;
+        let ecdsa_with_sha256_oid = oid(&[1, 2, 840, 10045, 4, 3, 2]);
+        let sgx_extension = yasna::construct_der(|writer| {
+            writer.write_sequence(|writer| {
+                writer.next().write_sequence(|writer| {
+                    writer.next().write_oid(&oid(PPID_OID));
+                    writer.next().write_bytes(&[1u8; 16]);
+                });
+                writer.next().write_sequence(|writer| {
+                    writer.next().write_oid(&oid(PCE_ID_OID));
+                    writer.next().write_bytes(&[0u8; 2]);
+                });
+                writer.next().write_sequence(|writer| {
+                    writer.next().write_oid(&oid(FMSPC_OID));
+                    writer.next().write_bytes(&FMSPC);
+                });
+            });
+        });
+
+        yasna::construct_der(|writer| {
+            writer.write_sequence(|writer| {
+                writer.next().write_sequence(|writer| {
+                    writer
+                        .next()
+                        .write_tagged(Tag::context(0), |writer| writer.write_i8(2));
+                    writer.next().write_u8(1);
+                    writer.next().write_sequence(|writer| {
+                        writer.next().write_oid(&ecdsa_with_sha256_oid);
+                    });
+                    // Issuer, validity, subject and public key are not
+                    // relevant to the SGX extension.
+                    for _ in 0..4 {
+                        writer.next().write_sequence(|_| {});
+                    }
+                    writer.next().write_tagged(Tag::context(3), |writer| {
+                        writer.write_sequence(|writer| {
+                            // Basic constraints, marked as critical
+                            writer.next().write_sequence(|writer| {
+                                writer.next().write_oid(&oid(&[2, 5, 29, 19]));
+                                writer.next().write_bool(true);
+                                writer.next().write_bytes(&[0x30, 0x00]);
+                            });
+                            writer.next().write_sequence(|writer| {
+                                writer.next().write_oid(&oid(SGX_EXTENSION_OID));
+                                writer.next().write_bytes(&sgx_extension);
+                            });
+                        });
+                    });
+                });
+                writer.next().write_sequence(|writer| {
+                    writer.next().write_oid(&ecdsa_with_sha256_oid);
+                });
+                writer.next().write_bitvec(&BitVec::from_bytes(&[0u8; 8]));
+            });
+        })
+    }
+
+    pub fn test_pck_cert_chain_revocation() {
+        use std::io::Read;
+        use std::untrusted::fs::File;
+
+        let read_fixture = |name: &str| {
+            let mut bytes = vec![];
+            let mut f = File::open(format!("fixtures/pck_crl/{}", name)).unwrap();
+            f.read_to_end(&mut bytes).unwrap();
+            bytes
+        };
+        let chain = vec![
+            read_fixture("pck_cert.der"),
+            read_fixture("pck_ca_cert.der"),
+            read_fixture("root_ca_cert.der"),
+        ];
+        let root_ca_crl = read_fixture("root_ca_crl.der");
+        let pck_ca_crl = read_fixture("pck_crl.der");
+        // 2022-01-01T00:00:00Z
+        let now = UNIX_EPOCH + Duration::from_secs(1_640_995_200);
+
+        let crl = Crl::verify_and_parse(&root_ca_crl, &chain[2], now).unwrap();
+        assert!(!crl.is_revoked(&chain[1]).unwrap());
+        assert!(crl.is_revoked(&chain[0]).is_err());
+        let crl = Crl::verify_and_parse(&pck_ca_crl, &chain[1], now).unwrap();
+        assert!(crl.is_revoked(&chain[0]).unwrap());
+
+        // The PCK certificate is revoked by the PCK CA CRL.
+        let err =
+            verify_pck_cert_chain_revocation(&chain, &root_ca_crl, &pck_ca_crl, now).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::CertRevoked) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+
+        // Wrong issuer, expired CRL and tampered CRL
+        assert!(Crl::verify_and_parse(&pck_ca_crl, &chain[2], now).is_err());
+        let expired = UNIX_EPOCH + Duration::from_secs(2_556_057_600);
+        assert!(Crl::verify_and_parse(&root_ca_crl, &chain[2], expired).is_err());
+        let mut tampered = root_ca_crl.clone();
+        let len = tampered.len();
+        tampered[len / 2] ^= 1;
+        assert!(Crl::verify_and_parse(&tampered, &chain[2], now).is_err());
+    }
+
+    pub fn test_pck_cert_sgx_extension() {
+        let sgx_extension = PckCertSgxExtension::from_pck_cert(&pck_cert_der()).unwrap();
+        assert_eq!(sgx_extension.ppid, vec![1u8; 16]);
+        assert_eq!(sgx_extension.pce_id, [0u8; 2]);
+        assert_eq!(sgx_extension.fmspc, FMSPC);
+
+        assert!(sgx_extension.verify_fmspc(&[FMSPC]).is_ok());
+        assert!(sgx_extension.verify_fmspc(&[[0u8; 6]]).is_err());
+    }
+
+    pub fn test_qe_identity() {
+        let collateral = br#"{
+            "enclaveIdentity": {
+                "id": "QE",
+                "mrsigner": "8c4f5775d796503e96137f77c68a829a0056ac8ded70140b081b094490c57bff",
+                "isvprodid": 1,
+                "tcbLevels": [
+                    { "tcb": { "isvsvn": 6 }, "tcbStatus": "UpToDate" },
+                    { "tcb": { "isvsvn": 5 }, "tcbStatus": "UpToDate" },
+                    { "tcb": { "isvsvn": 4 }, "tcbStatus": "OutOfDate" }
+                ]
+            },
+            "signature": "00"
+        }"#;
+        let qe_identity = QeIdentity::from_json(collateral).unwrap();
+        assert_eq!(qe_identity.isv_prod_id, 1);
+        assert_eq!(qe_identity.min_isv_svn, 5);
+
+        let quote = |isv_svn_qe: u16| {
+            let mut quote = Vec::new();
+            quote.extend_from_slice(&3u16.to_le_bytes());
+            quote.extend_from_slice(&2u16.to_le_bytes());
+            quote.extend_from_slice(&[0u8; 4]);
+            quote.extend_from_slice(&isv_svn_qe.to_le_bytes());
+            quote.resize(432, 0);
+            SgxQuote::parse_from(&quote).unwrap()
+        };
+        let qe_report = |mr_signer: &[u8; 32], isv_svn: u16| {
+            let mut report = vec![0u8; SGX_REPORT_SIZE];
+            report[128..160].copy_from_slice(mr_signer);
+            report[256..258].copy_from_slice(&1u16.to_le_bytes());
+            report[258..260].copy_from_slice(&isv_svn.to_le_bytes());
+            SgxEnclaveReport::parse_from(&report).unwrap()
+        };
+
+        let mr_signer = qe_identity.mr_signer;
+        assert!(qe_identity
+            .verify(&quote(5), &qe_report(&mr_signer, 5))
+            .is_ok());
+        assert!(qe_identity
+            .verify(&quote(4), &qe_report(&mr_signer, 5))
+            .is_err());
+        assert!(qe_identity
+            .verify(&quote(6), &qe_report(&[0u8; 32], 6))
+            .is_err());
+    }
+
+    pub fn test_tdx_qe_report_certification_data() {
+        let mut qe_report = vec![0u8; SGX_REPORT_SIZE];
+        // MRSIGNER at offset 128 of the report
+        qe_report[128..160].copy_from_slice(&[0xaa; 32]);
+
+        let mut quote = Vec::new();
+        quote.extend_from_slice(&TDX_QUOTE_VERSION.to_le_bytes());
+        quote.extend_from_slice(&2u16.to_le_bytes());
+        quote.extend_from_slice(&TDX_TEE_TYPE.to_le_bytes());
+        quote.resize(TDX_QUOTE_HEADER_SIZE + TDX_TD_REPORT_SIZE, 0);
+        quote.extend_from_slice(&(128u32 + 6 + 384 + 64 + 2 + 3).to_le_bytes());
+        quote.extend_from_slice(&[0u8; 128]);
+        quote.extend_from_slice(&QE_REPORT_CERT_DATA_TYPE.to_le_bytes());
+        quote.extend_from_slice(&(384u32 + 64 + 2 + 3).to_le_bytes());
+        quote.extend_from_slice(&qe_report);
+        quote.extend_from_slice(&[0xbb; 64]);
+        quote.extend_from_slice(&3u16.to_le_bytes());
+        quote.extend_from_slice(&[1, 2, 3]);
+
+        let cert_data = TdxQeReportCertificationData::parse_from_tdx_quote(&quote).unwrap();
+        assert_eq!(cert_data.qe_report().mr_signer, [0xaa; 32]);
+        assert_eq!(cert_data.qe_report_signature(), &[0xbb; 64]);
+        assert_eq!(cert_data.qe_auth_data(), &[1, 2, 3]);
+
+        assert!(TdxQeReportCertificationData::parse_from_tdx_quote(&quote[..1000]).is_err());
+        quote[0] = 3;
+        assert!(TdxQeReportCertificationData::parse_from_tdx_quote(&quote).is_err());
+    }
+
+    pub fn test_quote_parsers_arbitrary_bytes() {
+        use std::io::Read;
+        use std::untrusted::fs::File;
+
+        let mut quote = vec![];
+        let mut f = File::open("fixtures/dcap_quote/quote.dat").unwrap();
+        f.read_to_end(&mut quote).unwrap();
+
+        // Parsers must return an error instead of panicking on any input.
+        let parse_all = |bytes: &[u8]| {
+            let _ = SgxQuote::parse_from(bytes);
+            let _ = SgxQuote::parse_prefix(bytes);
+            let _ = SgxEnclaveReport::parse_from(bytes);
+            let _ = SgxEnclaveReport::parse_from_strict(bytes);
+            let _ = SgxEcdsaSignatureData::parse_from(bytes);
+            let _ = QeCertificationData::parse_from_sgx_quote(bytes);
+            let _ = TdxQeReportCertificationData::parse_from_tdx_quote(bytes);
+        };
+
+        for len in 0..quote.len() {
+            parse_all(&quote[..len]);
+            parse_all(&quote[len..]);
+        }
+
+        // Length fields of the signature data, QE authentication data and
+        // certification data near the maximum values
+        let sig_data = SGX_QUOTE_BODY_SIZE;
+        let qe_auth_data = sig_data + 4 + 64 + 64 + SGX_REPORT_SIZE + 64;
+        for offset in &[sig_data, qe_auth_data, qe_auth_data + 2 + 32 + 2] {
+            let mut malformed = quote.clone();
+            for b in &mut malformed[*offset..*offset + 4] {
+                *b = 0xff;
+            }
+            parse_all(&malformed);
+            assert!(SgxQuote::parse_from(&malformed).is_err());
+        }
+
+        // Deterministic xorshift for reproducible mutations
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..1000 {
+            let mut mutated = quote.clone();
+            for _ in 0..(next() % 8 + 1) {
+                let i = next() as usize % mutated.len();
+                mutated[i] = next() as u8;
+            }
+            let len = next() as usize % (mutated.len() + 1);
+            parse_all(&mutated[..len]);
+        }
+        for _ in 0..1000 {
+            let len = next() as usize % 2048;
+            let mut random: Vec<u8> = (0..len).map(|_| next() as u8).collect();
+            if len >= 2 {
+                random[0] = (next() % 4) as u8;
+                random[1] = 0;
+            }
+            parse_all(&random);
+        }
+    }
+
+    pub fn test_qe_certification_data() {
+        let pck_cert_chain = b"-----BEGIN CERTIFICATE-----";
+        let mut quote = Vec::new();
+        quote.extend_from_slice(&SGX_ECDSA_QUOTE_VERSION.to_le_bytes());
+        quote.extend_from_slice(&2u16.to_le_bytes());
+        quote.resize(SGX_QUOTE_BODY_SIZE, 0);
+        let sig_data_len = 64 + 64 + 384 + 64 + 2 + 3 + 2 + 4 + pck_cert_chain.len();
+        quote.extend_from_slice(&(sig_data_len as u32).to_le_bytes());
+        quote.extend_from_slice(&[0u8; 64 + 64 + 384 + 64]);
+        quote.extend_from_slice(&3u16.to_le_bytes());
+        quote.extend_from_slice(&[1, 2, 3]);
+        quote.extend_from_slice(&5u16.to_le_bytes());
+        quote.extend_from_slice(&(pck_cert_chain.len() as u32).to_le_bytes());
+        quote.extend_from_slice(pck_cert_chain);
+
+        let cert_data = QeCertificationData::parse_from_sgx_quote(&quote).unwrap();
+        assert_eq!(
+            cert_data.cert_data_type(),
+            CertificationDataType::PckCertChain
+        );
+        assert_eq!(cert_data.cert_data(), &pck_cert_chain[..]);
+        assert_eq!(cert_data.pck_cert_chain(), Some(&pck_cert_chain[..]));
+
+        let len = quote.len();
+        assert!(QeCertificationData::parse_from_sgx_quote(&quote[..len - 1]).is_err());
+        let mut unknown_type = quote.clone();
+        unknown_type[len - pck_cert_chain.len() - 6] = 8;
+        assert!(QeCertificationData::parse_from_sgx_quote(&unknown_type).is_err());
+        quote[0] = 2;
+        assert!(QeCertificationData::parse_from_sgx_quote(&quote).is_err());
+    }
+
+    pub fn test_sgx_quote_signature_data_length() {
+        use std::io::Read;
+        use std::untrusted::fs::File;
+
+        let mut quote = vec![];
+        let mut f = File::open("fixtures/dcap_quote/quote.dat").unwrap();
+        f.read_to_end(&mut quote).unwrap();
+
+        // The declared length of the signature data exceeds the bytes after
+        // the length field.
+        let remaining = (quote.len() - SGX_QUOTE_BODY_SIZE - 4) as u32;
+        for sig_data_len in &[remaining + 1, u32::MAX] {
+            let mut malformed = quote.clone();
+            malformed[SGX_QUOTE_BODY_SIZE..SGX_QUOTE_BODY_SIZE + 4]
+                .copy_from_slice(&sig_data_len.to_le_bytes());
+            let errors = vec![
+                SgxQuote::parse_from(&malformed).unwrap_err(),
+                SgxEcdsaSignatureData::parse_from(&malformed[SGX_QUOTE_BODY_SIZE..]).unwrap_err(),
+            ];
+            for err in errors {
+                match err.downcast_ref::<AttestationError>() {
+                    Some(AttestationError::QuoteTruncated) => (),
+                    _ => panic!("unexpected error: {:?}", err),
+                }
+            }
+        }
+    }
+
+    pub fn test_dcap_quote() {
+        use std::io::Read;
+        use std::untrusted::fs::File;
+
+        let read_fixture = |name: &str| {
+            let mut bytes = vec![];
+            let mut f = File::open(format!("fixtures/dcap_quote/{}", name)).unwrap();
+            f.read_to_end(&mut bytes).unwrap();
+            bytes
+        };
+        let quote = read_fixture("quote.dat");
+        let collateral = DcapCollateral {
+            root_ca_cert: read_fixture("root_ca_cert.der"),
+            root_ca_crl: read_fixture("root_ca_crl.der"),
+            pck_crl: read_fixture("pck_crl.der"),
+            tcb_signing_chain: read_fixture("tcb_signing_chain.pem"),
+            tcb_info: read_fixture("tcb_info.json"),
+            qe_identity: read_fixture("qe_identity.json"),
+        };
+        let cert_data = QeCertificationData::parse_from_sgx_quote(&quote).unwrap();
+        let pck_chain = cert_data.pck_cert_chain().unwrap();
+
+        let report = AttestationReport::from_dcap_quote(&quote, pck_chain, &collateral).unwrap();
+        assert_eq!(report.sgx_quote_status, SgxQuoteStatus::SwHardeningNeeded);
+        assert_eq!(
+            report.timestamp,
+            UNIX_EPOCH + Duration::from_secs(1_609_459_200)
+        );
+        assert_eq!(
+            &report.sgx_quote_body.isv_enclave_report.report_data[..8],
+            &[0, 1, 2, 3, 4, 5, 6, 7]
+        );
+        assert_eq!(
+            report.platform_identity,
+            Some(PlatformIdentity::Ppid((0..16).collect()))
+        );
+        let ecdsa_sig = report.sgx_quote_body.ecdsa_sig.as_ref().unwrap();
+        assert_eq!(ecdsa_sig.qe_report.isv_svn, 6);
+        assert_eq!(ecdsa_sig.qe_auth_data, (0..32).collect::<Vec<u8>>());
+        assert_eq!(ecdsa_sig.qe_cert_data, cert_data);
+        let serialized = serde_json::to_string(ecdsa_sig).unwrap();
+        let deserialized: SgxEcdsaSignatureData = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.qe_report_raw, ecdsa_sig.qe_report_raw);
+        assert_eq!(deserialized.qe_cert_data, cert_data);
+        let quote_body = &quote[..SGX_QUOTE_BODY_SIZE];
+        assert!(SgxQuote::parse_from(quote_body)
+            .unwrap()
+            .ecdsa_sig
+            .is_none());
+        assert!(SgxQuote::parse_from(&quote[..quote.len() - 1]).is_err());
+        let mut quote_with_collateral = quote.clone();
+        quote_with_collateral.extend_from_slice(&collateral.tcb_info);
+        let (prefix, len) = SgxQuote::parse_prefix(&quote_with_collateral).unwrap();
+        assert_eq!(len, quote.len());
+        assert_eq!(prefix.ecdsa_sig.unwrap().qe_cert_data, cert_data);
+        match SgxQuote::parse_from(&quote_with_collateral)
+            .unwrap_err()
+            .downcast_ref::<AttestationError>()
+        {
+            Some(AttestationError::QuoteMalformed) => (),
+            e => panic!("unexpected error: {:?}", e),
+        }
+
+        let mut tampered = quote.clone();
+        tampered[SGX_QUOTE_BODY_SIZE - 1] ^= 1;
+        assert!(AttestationReport::from_dcap_quote(&tampered, pck_chain, &collateral).is_err());
+        let mut tampered = quote.clone();
+        tampered[SGX_QUOTE_BODY_SIZE + 4 + 64 + 64] ^= 1;
+        assert!(AttestationReport::from_dcap_quote(&tampered, pck_chain, &collateral).is_err());
+        let mut tampered = quote.clone();
+        tampered[12] ^= 1;
+        let err =
+            AttestationReport::from_dcap_quote(&tampered, pck_chain, &collateral).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::QeVendorIdMismatch) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+
+        let qe_identity = QeIdentity::from_json(&collateral.qe_identity).unwrap();
+        let sgx_quote = SgxQuote::parse_from(&quote).unwrap();
+        assert!(sgx_quote.verify_qe_identity(&qe_identity).is_ok());
+        let mut other_qe = qe_identity.clone();
+        other_qe.isv_prod_id ^= 1;
+        match sgx_quote
+            .verify_qe_identity(&other_qe)
+            .unwrap_err()
+            .downcast_ref::<AttestationError>()
+        {
+            Some(AttestationError::QeIdentityMismatch) => (),
+            e => panic!("unexpected error: {:?}", e),
+        }
+        let mut other_key = quote.clone();
+        other_key[SGX_QUOTE_BODY_SIZE + 4 + 64] ^= 1;
+        let other_key = SgxQuote::parse_from(&other_key).unwrap();
+        assert!(other_key.verify_qe_identity(&qe_identity).is_err());
+        let body_only = SgxQuote::parse_from(quote_body).unwrap();
+        assert!(body_only.verify_qe_identity(&qe_identity).is_err());
+
+        let mut other_root = collateral.clone();
+        other_root.root_ca_cert = read_fixture("../pck_crl/root_ca_cert.der");
+        assert!(AttestationReport::from_dcap_quote(&quote, pck_chain, &other_root).is_err());
+        let mut unknown_qe = collateral.clone();
+        unknown_qe.qe_identity = String::from_utf8(collateral.qe_identity.clone())
+            .unwrap()
+            .replace("\"isvsvn\": 6", "\"isvsvn\": 7")
+            .into_bytes();
+        let err = AttestationReport::from_dcap_quote(&quote, pck_chain, &unknown_qe).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::SignatureInvalid) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+        // Parsed without its signature, the altered QE identity mismatches.
+        assert!(sgx_quote
+            .verify_qe_identity(&QeIdentity::from_json(&unknown_qe.qe_identity).unwrap())
+            .is_err());
+        let mut upgraded = collateral.clone();
+        upgraded.tcb_info = String::from_utf8(collateral.tcb_info.clone())
+            .unwrap()
+            .replace("\"SWHardeningNeeded\"", "\"UpToDate\"")
+            .into_bytes();
+        assert_ne!(upgraded.tcb_info, collateral.tcb_info);
+        let err = AttestationReport::from_dcap_quote(&quote, pck_chain, &upgraded).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::SignatureInvalid) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+        let tcb_signing_cert =
+            &rustls::internal::pemfile::certs(&mut &collateral.tcb_signing_chain[..]).unwrap()[0].0;
+        assert!(TcbInfo::verify_and_parse(&collateral.tcb_info, tcb_signing_cert).is_ok());
+        assert!(TcbInfo::verify_and_parse(&upgraded.tcb_info, tcb_signing_cert).is_err());
+        assert!(TcbInfo::from_json(&upgraded.tcb_info).is_ok());
+        assert_eq!(
+            QeIdentity::verify_and_parse(&collateral.qe_identity, tcb_signing_cert).unwrap(),
+            qe_identity
+        );
+        assert!(QeIdentity::verify_and_parse(&collateral.tcb_info, tcb_signing_cert).is_err());
+        let mut duplicated = collateral.tcb_info.clone();
+        duplicated.truncate(duplicated.len() - 2);
+        duplicated.extend_from_slice(b",\n  \"tcbInfo\": {}\n}\n");
+        assert_eq!(
+            TcbInfo::verify_and_parse(&duplicated, tcb_signing_cert).unwrap(),
+            TcbInfo::from_json(&collateral.tcb_info).unwrap()
+        );
+        // The TCB signing certificate must be issued by the root CA.
+        let mut pck_signed = collateral.clone();
+        pck_signed.tcb_signing_chain = pck_chain.to_vec();
+        let err = AttestationReport::from_dcap_quote(&quote, pck_chain, &pck_signed).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::SignatureInvalid) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+        let mut untrusted_signer = collateral.clone();
+        untrusted_signer.tcb_signing_chain = format!(
+            "-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n",
+            base64::encode(&read_fixture("../pck_crl/root_ca_cert.der"))
+        )
+        .into_bytes();
+        let err =
+            AttestationReport::from_dcap_quote(&quote, pck_chain, &untrusted_signer).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::CertChainInvalid) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+        let mut other_platform = collateral;
+        other_platform.tcb_info = String::from_utf8(other_platform.tcb_info)
+            .unwrap()
+            .replace("00906ea10000", "00906ea20000")
+            .into_bytes();
+        assert!(AttestationReport::from_dcap_quote(&quote, pck_chain, &other_platform).is_err());
+    }
+}
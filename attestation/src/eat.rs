@@ -0,0 +1,231 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! This module exports attestation reports as Entity Attestation Tokens (EAT)
+//! claims in CBOR defined by the IETF RATS working group.
+//! https://datatracker.ietf.org/doc/draft-ietf-rats-eat/
+
+use std::prelude::v1::*;
+
+use crate::report::AttestationReport;
+
+use std::time::UNIX_EPOCH;
+
+/// Key of the "issued at" claim
+const EAT_IAT: u64 = 6;
+/// Key of the debug status claim
+const EAT_DBGSTAT: u64 = 263;
+/// Debug status: debug is enabled
+const DBGSTAT_ENABLED: u64 = 0;
+/// Debug status: debug is disabled
+const DBGSTAT_DISABLED: u64 = 1;
+
+/// CBOR major types used in claims
+const MAJOR_UNSIGNED: u8 = 0;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_MAP: u8 = 5;
+
+enum ClaimKey {
+    Int(u64),
+    Text(&'static str),
+}
+
+enum ClaimValue {
+    Uint(u64),
+    Bytes(Vec<u8>),
+    Text(String),
+}
+
+/// Write the head of a CBOR data item with the shortest argument encoding.
+fn write_head(buf: &mut Vec<u8>, major: u8, arg: u64) {
+    let major = major << 5;
+    if arg < 24 {
+        buf.push(major | arg as u8);
+    } else if arg <= u64::from(u8::MAX) {
+        buf.push(major | 24);
+        buf.push(arg as u8);
+    } else if arg <= u64::from(u16::MAX) {
+        buf.push(major | 25);
+        buf.extend_from_slice(&(arg as u16).to_be_bytes());
+    } else if arg <= u64::from(u32::MAX) {
+        buf.push(major | 26);
+        buf.extend_from_slice(&(arg as u32).to_be_bytes());
+    } else {
+        buf.push(major | 27);
+        buf.extend_from_slice(&arg.to_be_bytes());
+    }
+}
+
+fn encode_claims(claims: &[(ClaimKey, ClaimValue)]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    write_head(&mut buf, MAJOR_MAP, claims.len() as u64);
+    for (key, value) in claims {
+        match key {
+            ClaimKey::Int(k) => write_head(&mut buf, MAJOR_UNSIGNED, *k),
+            ClaimKey::Text(k) => {
+                write_head(&mut buf, MAJOR_TEXT, k.len() as u64);
+                buf.extend_from_slice(k.as_bytes());
+            }
+        }
+        match value {
+            ClaimValue::Uint(v) => write_head(&mut buf, MAJOR_UNSIGNED, *v),
+            ClaimValue::Bytes(v) => {
+                write_head(&mut buf, MAJOR_BYTES, v.len() as u64);
+                buf.extend_from_slice(v);
+            }
+            ClaimValue::Text(v) => {
+                write_head(&mut buf, MAJOR_TEXT, v.len() as u64);
+                buf.extend_from_slice(v.as_bytes());
+            }
+        }
+    }
+
+    buf
+}
+
+impl AttestationReport {
+    /// Export the report as an EAT claims set in CBOR. Besides the standard
+    /// `iat` and `dbgstat` claims, measurements and the quote status are
+    /// exported as the private claims `sgx-mrenclave`, `sgx-mrsigner`,
+    /// `sgx-isvprodid`, `sgx-isvsvn`, `sgx-report-data` and
+    /// `sgx-quote-status`.
+    pub fn to_eat_cbor(&self) -> Vec<u8> {
+        let report = &self.sgx_quote_body.isv_enclave_report;
+        let iat = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let dbgstat = if report.sgx_attributes().is_debug() {
+            DBGSTAT_ENABLED
+        } else {
+            DBGSTAT_DISABLED
+        };
+
+        let claims = vec![
+            (ClaimKey::Int(EAT_IAT), ClaimValue::Uint(iat)),
+            (ClaimKey::Int(EAT_DBGSTAT), ClaimValue::Uint(dbgstat)),
+            (
+                ClaimKey::Text("sgx-mrenclave"),
+                ClaimValue::Bytes(report.mr_enclave.to_vec()),
+            ),
+            (
+                ClaimKey::Text("sgx-mrsigner"),
+                ClaimValue::Bytes(report.mr_signer.to_vec()),
+            ),
+            (
+                ClaimKey::Text("sgx-isvprodid"),
+                ClaimValue::Uint(u64::from(report.isv_prod_id)),
+            ),
+            (
+                ClaimKey::Text("sgx-isvsvn"),
+                ClaimValue::Uint(u64::from(report.isv_svn)),
+            ),
+            (
+                ClaimKey::Text("sgx-report-data"),
+                ClaimValue::Bytes(report.report_data.to_vec()),
+            ),
+            (
+                ClaimKey::Text("sgx-quote-status"),
+                ClaimValue::Text(self.sgx_quote_status.as_str().to_string()),
+            ),
+        ];
+
+        encode_claims(&claims)
+    }
+}
+
+#[cfg(all(feature = "enclave_unit_test", feature = "mesalock_sgx"))]
+pub mod tests {
+    use super::*;
+    use crate::report::SgxQuoteStatus;
+    use std::io::Read;
+    use std::untrusted::fs::File;
+
+    pub fn test_cbor_encode_claims() {
+        let mut buf = Vec::new();
+        for arg in &[23u64, 24, 255, 256, 65_536, 1 << 32] {
+            write_head(&mut buf, MAJOR_UNSIGNED, *arg);
+        }
+        assert_eq!(
+            buf,
+            vec![
+                0x17, 0x18, 0x18, 0x18, 0xff, 0x19, 0x01, 0x00, 0x1a, 0x00, 0x01, 0x00, 0x00, 0x1b,
+                0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00
+            ]
+        );
+
+        let claims = vec![
+            (ClaimKey::Int(EAT_IAT), ClaimValue::Uint(1)),
+            (ClaimKey::Text("a"), ClaimValue::Bytes(vec![0xff])),
+            (
+                ClaimKey::Int(EAT_DBGSTAT),
+                ClaimValue::Text("b".to_string()),
+            ),
+        ];
+        assert_eq!(
+            encode_claims(&claims),
+            vec![0xa3, 0x06, 0x01, 0x61, 0x61, 0x41, 0xff, 0x19, 0x01, 0x07, 0x61, 0x62]
+        );
+    }
+
+    pub fn test_to_eat_cbor() {
+        let mut cert = vec![];
+        let mut f = File::open("fixtures/tls_ra_cert_v4.der").unwrap();
+        f.read_to_end(&mut cert).unwrap();
+        let mut report = AttestationReport::parse_unverified(&cert).unwrap();
+
+        let enclave_report = &report.sgx_quote_body.isv_enclave_report;
+        let expected = [
+            // map of 8 claims, iat: 2022-01-17T22:07:49, dbgstat: enabled
+            "a8061a61e5e8b519010700".to_string(),
+            format!(
+                "6d7367782d6d72656e636c6176655820{}",
+                hex::encode(enclave_report.mr_enclave)
+            ),
+            format!(
+                "6c7367782d6d727369676e65725820{}",
+                hex::encode(enclave_report.mr_signer)
+            ),
+            "6d7367782d69737670726f64696400".to_string(),
+            "6a7367782d69737673766e00".to_string(),
+            format!(
+                "6f7367782d7265706f72742d646174615840{}",
+                hex::encode(&enclave_report.report_data[..])
+            ),
+            "707367782d71756f74652d737461747573".to_string(),
+        ]
+        .concat();
+        assert_eq!(
+            hex::encode(enclave_report.mr_enclave),
+            "82bc195bd181ca8e3cf220f633eea98e7a0a55f3217fa7f731cf0d7a8d83253e"
+        );
+        // text "OK"
+        assert_eq!(
+            hex::encode(report.to_eat_cbor()),
+            format!("{}624f4b", expected)
+        );
+
+        // Statuses are the canonical strings of the attestation service
+        report.sgx_quote_status = SgxQuoteStatus::SwHardeningNeeded;
+        assert_eq!(
+            hex::encode(report.to_eat_cbor()),
+            format!("{}7353575f48415244454e494e475f4e4545444544", expected)
+        );
+    }
+}
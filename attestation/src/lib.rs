@@ -21,35 +21,161 @@
 
 #![allow(clippy::nonstandard_macro_braces)]
 #![allow(clippy::unknown_clippy_lints)]
-#![cfg_attr(feature = "mesalock_sgx", no_std)]
+#![cfg_attr(any(feature = "mesalock_sgx", not(feature = "std")), no_std)]
 #[cfg(feature = "mesalock_sgx")]
 #[macro_use]
 extern crate sgx_tstd as std;
+extern crate alloc;
 
+#[cfg(feature = "std")]
 use std::prelude::v1::*;
+#[cfg(feature = "std")]
 use std::sync::Arc;
 
-use anyhow::{Context, Result};
+use alloc::string::String;
+use core::fmt;
+
+#[cfg(feature = "std")]
+use anyhow::{ensure, Context, Result};
+#[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
 
 /// Errors that can happen during attestation and verification process
-#[derive(thiserror::Error, Debug)]
+#[derive(Debug)]
 pub enum AttestationError {
-    #[error("OCall error")]
     OCallError(sgx_types::sgx_status_t),
-    #[error("Attestation Service error")]
     AttestationServiceError,
-    #[error("Platform error")]
     PlatformError(sgx_types::sgx_status_t),
-    #[error("Report error")]
     ReportError,
-    #[error("Report error")]
     ConnectionError,
-    #[error("Attestation Service API version not compatible")]
     ApiVersionNotCompatible,
+    ReportDataMismatch,
+    QuoteTruncated,
+    QuoteMalformed,
+    QuoteSizeMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    SignatureInvalid,
+    CertChainInvalid,
+    MalformedCertificate,
+    TimestampParse,
+    NonZeroReserved {
+        offset: usize,
+    },
+    MiscSelectMismatch,
+    CpuSvnBelowMinimum {
+        component: usize,
+    },
+    FmspcNotAllowed,
+    TimestampNotMonotonic,
+    IssuanceTimeMismatch,
+    UnsupportedQuoteVersion(u16),
+    SigningCertPurposeMismatch,
+    NotProductionEnclave(&'static str),
+    ChannelBindingMismatch,
+    CertRevoked,
+    QeIdentityMismatch,
+    QeVendorIdMismatch,
+    IsvSvnNotAccepted,
+    ConfigIdMismatch,
+    ReferenceValueMismatch,
+    NonceMismatch,
+    QuoteStatusUntrusted(quote::SgxQuoteStatus),
+    AdvisoryNotAllowed(String),
+    Stale {
+        age: core::time::Duration,
+        max_age: core::time::Duration,
+    },
+    ClockSkew {
+        ahead: core::time::Duration,
+        clock_skew: core::time::Duration,
+    },
 }
 
+impl fmt::Display for AttestationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use AttestationError::*;
+        match self {
+            OCallError(_) => f.write_str("OCall error"),
+            AttestationServiceError => f.write_str("Attestation Service error"),
+            PlatformError(_) => f.write_str("Platform error"),
+            ReportError => f.write_str("Report error"),
+            ConnectionError => f.write_str("Report error"),
+            ApiVersionNotCompatible => {
+                f.write_str("Attestation Service API version not compatible")
+            }
+            ReportDataMismatch => f.write_str("Report data mismatch"),
+            QuoteTruncated => f.write_str("Quote is truncated"),
+            QuoteMalformed => f.write_str("Quote is malformed"),
+            QuoteSizeMismatch { expected, actual } => write!(
+                f,
+                "Quote size is invalid: expected {} bytes, got {}",
+                expected, actual
+            ),
+            SignatureInvalid => f.write_str("Signature is invalid"),
+            CertChainInvalid => f.write_str("Certificate chain is invalid"),
+            MalformedCertificate => f.write_str("Certificate is malformed"),
+            TimestampParse => f.write_str("Timestamp of the report cannot be parsed"),
+            NonZeroReserved { offset } => write!(
+                f,
+                "Reserved field of the report is not zero at offset {}",
+                offset
+            ),
+            MiscSelectMismatch => {
+                f.write_str("MISCSELECT of the enclave does not match the expected value")
+            }
+            CpuSvnBelowMinimum { component } => write!(
+                f,
+                "Component {} of the CPUSVN is lower than the baseline",
+                component
+            ),
+            FmspcNotAllowed => f.write_str("Platform FMSPC is not allowed"),
+            TimestampNotMonotonic => f.write_str("Report is older than the last accepted report"),
+            IssuanceTimeMismatch => {
+                f.write_str("Certificate issuance time is inconsistent with the report timestamp")
+            }
+            UnsupportedQuoteVersion(v) => write!(f, "Quote version {} is not supported", v),
+            SigningCertPurposeMismatch => {
+                f.write_str("Report signing certificate is not issued for the expected purpose")
+            }
+            NotProductionEnclave(v) => write!(f, "Enclave is not a production enclave: {}", v),
+            ChannelBindingMismatch => {
+                f.write_str("TLS peer certificate does not carry the attested public key")
+            }
+            CertRevoked => f.write_str("Certificate is revoked"),
+            QeIdentityMismatch => f.write_str("Quoting enclave does not match the QE identity"),
+            QeVendorIdMismatch => f.write_str("Quoting enclave vendor ID is not expected"),
+            IsvSvnNotAccepted => f.write_str("ISV SVN of the enclave is not accepted"),
+            ConfigIdMismatch => {
+                f.write_str("Enclave configuration does not match the expected CONFIGID")
+            }
+            ReferenceValueMismatch => f.write_str("Report does not match any reference value"),
+            NonceMismatch => f.write_str("Nonce of the report does not match the expected nonce"),
+            QuoteStatusUntrusted(v) => write!(f, "Quote status {} is not trusted", v),
+            AdvisoryNotAllowed(v) => write!(f, "Security advisory {} is not allowed", v),
+            Stale { age, max_age } => write!(
+                f,
+                "Report is stale: {:?} old, maximum age is {:?}",
+                age, max_age
+            ),
+            ClockSkew { ahead, clock_skew } => write!(
+                f,
+                "Report timestamp is {:?} in the future, tolerated clock skew is {:?}",
+                ahead, clock_skew
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AttestationError {}
+
+#[cfg(not(feature = "std"))]
+impl core::error::Error for AttestationError {}
+
 /// Remote attestation configuration
+#[cfg(feature = "std")]
 #[derive(Clone)]
 pub enum AttestationConfig {
     /// Trust enclave without attestation
@@ -59,6 +185,7 @@ pub enum AttestationConfig {
 }
 
 /// Remote attestation algorithm
+#[cfg(feature = "std")]
 #[derive(Clone)]
 pub(crate) enum AttestationAlgorithm {
     /// Use Intel EPID
@@ -67,6 +194,7 @@ pub(crate) enum AttestationAlgorithm {
     SgxEcdsa,
 }
 
+#[cfg(feature = "std")]
 impl AttestationAlgorithm {
     pub(crate) fn from_str(s: &str) -> Option<Self> {
         match s {
@@ -78,6 +206,7 @@ impl AttestationAlgorithm {
 }
 
 /// Attestation Service Configuration
+#[cfg(feature = "std")]
 #[derive(Clone)]
 pub struct AttestationServiceConfig {
     /// Algorithm to use
@@ -90,8 +219,10 @@ pub struct AttestationServiceConfig {
     spid: sgx_types::sgx_spid_t,
 }
 
+#[cfg(feature = "std")]
 pub struct DcapConfig {}
 
+#[cfg(feature = "std")]
 impl AttestationConfig {
     /// Creates `AttestationConfig` for no attestation
     pub fn no_attestation() -> Arc<Self> {
@@ -138,17 +269,42 @@ impl AttestationConfig {
 /// AttestationReport can be endorsed by either the Intel Attestation Service
 /// using EPID or Data Center Attestation
 /// Service (platform dependent) using ECDSA.
+#[cfg(feature = "std")]
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct EndorsedAttestationReport {
     /// Attestation report generated by the hardware
     pub report: Vec<u8>,
     /// Singature of the report
     pub signature: Vec<u8>,
-    /// Certificate matching the signing key of the signature
+    /// Certificate matching the signing key of the signature, followed by the
+    /// intermediate CA certificates (if any) of the signing certificate bundle
     pub certs: Vec<Vec<u8>>,
 }
 
+#[cfg(feature = "std")]
+impl EndorsedAttestationReport {
+    /// Assemble an endorsed report from the pieces of an attestation service
+    /// HTTP response: the report JSON in `body`, the base64-encoded signature
+    /// in `sig_header` (`X-IASReport-Signature`), and the URL-encoded PEM
+    /// certificate chain in `cert_header` (`X-IASReport-Signing-Certificate`).
+    /// Nothing is verified here.
+    pub fn from_ias_response(body: &[u8], sig_header: &str, cert_header: &str) -> Result<Self> {
+        let signature = base64::decode(sig_header).context("Invalid report signature")?;
+        let decoded_certs = percent_encoding::percent_decode_str(cert_header).decode_utf8()?;
+        let certs = rustls::internal::pemfile::certs(&mut decoded_certs.as_bytes())
+            .map_err(|_| AttestationError::CertChainInvalid)?;
+        ensure!(!certs.is_empty(), AttestationError::CertChainInvalid);
+
+        Ok(Self {
+            report: body.to_vec(),
+            signature,
+            certs: certs.into_iter().map(|c| c.0).collect(),
+        })
+    }
+}
+
 /// Configuration for TLS communication in Remote Attestation
+#[cfg(feature = "std")]
 #[derive(Debug)]
 pub struct AttestedTlsConfig {
     pub cert: Vec<u8>,
@@ -157,10 +313,21 @@ pub struct AttestedTlsConfig {
     pub validity: std::time::Duration,
 }
 
-#[macro_use]
-mod cert;
-pub mod report;
-pub mod verifier;
+pub mod quote;
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "std")] {
+        #[macro_use]
+        mod cert;
+        pub mod dcap;
+        mod eat;
+        pub mod maa;
+        pub mod policy;
+        pub mod report;
+        pub mod telemetry;
+        pub mod verifier;
+    }
+}
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "mesalock_sgx")]  {
@@ -182,9 +349,93 @@ pub mod tests {
             platform::tests::test_init_sgx_quote,
             platform::tests::test_create_sgx_isv_enclave_report,
             platform::tests::test_get_sgx_quote,
-            report::tests::test_sgx_quote_parse_from,
+            quote::tests::test_sgx_quote_parse_from,
+            quote::tests::test_sgx_quote_serde,
+            quote::tests::test_sgx_quote_supported_quote_version,
+            quote::tests::test_sgx_quote_parse_at,
+            quote::tests::test_sgx_quote_unsupported_version,
+            quote::tests::test_sgx_quote_raw,
+            quote::tests::test_sgx_quote_debug,
+            quote::tests::test_sgx_quote_size_mismatch,
+            quote::tests::test_sgx_quote_peek_version,
+            quote::tests::test_sgx_quote_parse_prefix,
+            quote::tests::test_sgx_quote_status_from_str,
+            quote::tests::test_quote_builders,
+            quote::tests::test_sgx_quote_validate_consistency,
+            quote::tests::test_sgx_quote_epid_sig_type,
+            quote::tests::test_sgx_quote_epid_user_data,
+            quote::tests::test_sgx_enclave_report_parse_from_strict,
+            quote::tests::test_sgx_enclave_report_data_halves,
+            quote::tests::test_ct_eq,
+            quote::tests::test_cpu_svn_meets_minimum,
+            quote::tests::test_sgx_enclave_report_satisfies,
+            quote::tests::test_sgx_attributes,
+            quote::tests::test_sgx_enclave_report_verify_report_data_split,
+            quote::tests::test_sgx_enclave_report_to_bytes,
+            quote::tests::test_sgx_misc_select,
+            report::tests::test_report_timestamp_second_boundaries,
+            report::tests::test_decompress_p256_point,
+            report::tests::test_report_advisory_ids,
+            report::tests::test_report_platform_info_blob,
+            report::tests::test_verify_report_nonce,
+            report::tests::test_decompress_quote,
+            report::tests::test_verify_issuance_time,
+            report::tests::test_verify_report_signing_cert_revocation,
+            report::tests::test_verified_report_warnings,
+            report::tests::test_same_platform,
+            report::tests::test_attestation_report_verify_freshness,
             report::tests::test_attestation_report_from_cert,
-            report::tests::test_attestation_report_from_cert_api_version_not_compatible
+            report::tests::test_attestation_report_from_cert_stale,
+            report::tests::test_attestation_report_from_cert_with_warnings,
+            report::tests::test_attestation_report_malformed_certificate,
+            report::tests::test_attestation_report_cert_chain_invalid,
+            report::tests::test_attestation_report_from_cert_with_report_cas,
+            report::tests::test_attestation_report_public_key,
+            report::tests::test_attestation_report_parse_unverified,
+            report::tests::test_attestation_report_from_cert_with_max_age,
+            report::tests::test_attestation_report_qe_vendor_id,
+            report::tests::test_attestation_report_quote_versions,
+            report::tests::test_attestation_report_verify_production,
+            report::tests::test_attestation_report_spki_hash_binding,
+            report::tests::test_attestation_report_from_cert_with_report_data,
+            report::tests::test_attestation_report_verify_report_data_transcript,
+            report::tests::test_peek_attestation_type,
+            report::tests::test_attestation_report_from_cert_with_parser,
+            report::tests::test_attestation_report_archived,
+            report::tests::test_attestation_report_clock,
+            report::tests::test_attestation_report_extension_oid,
+            report::tests::test_attestation_report_issuance_time,
+            report::tests::test_attestation_report_clock_skew,
+            report::tests::test_attestation_report_verify_config_id,
+            report::tests::test_attestation_report_build_identity,
+            report::tests::test_attestation_report_from_cert_with_time,
+            report::tests::test_attestation_report_signing_cert_purpose,
+            report::tests::test_attestation_report_signature_backend,
+            report::tests::test_attestation_report_from_cert_api_version_not_compatible,
+            verifier::tests::test_session_verifier,
+            verifier::tests::test_verify_detached,
+            verifier::tests::test_webpki_backend_intermediate_ca,
+            verifier::tests::test_endorsed_report_from_ias_response,
+            verifier::tests::test_attestation_bundle,
+            verifier::tests::test_webpki_backend_ecdsa_signature,
+            verifier::tests::test_attestation_verifier,
+            verifier::tests::test_channel_binding,
+            verifier::tests::test_reference_value_manifest,
+            dcap::tests::test_pck_cert_sgx_extension,
+            dcap::tests::test_pck_cert_chain_revocation,
+            dcap::tests::test_tdx_qe_report_certification_data,
+            dcap::tests::test_qe_certification_data,
+            dcap::tests::test_quote_parsers_arbitrary_bytes,
+            dcap::tests::test_qe_identity,
+            dcap::tests::test_sgx_quote_signature_data_length,
+            dcap::tests::test_dcap_quote,
+            eat::tests::test_cbor_encode_claims,
+            eat::tests::test_to_eat_cbor,
+            maa::tests::test_maa_jwt,
+            policy::tests::test_policy_evaluate,
+            policy::tests::test_compliance_profiles,
+            policy::tests::test_tcb_policy,
+            telemetry::tests::test_span_recorder
         )
     }
 }
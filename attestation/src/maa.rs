@@ -0,0 +1,310 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! This module provides types used to verify attestation tokens of Microsoft
+//! Azure Attestation (MAA), i.e., JWTs carrying the SGX enclave report as
+//! claims instead of a quote.
+
+use std::prelude::v1::*;
+
+use crate::dcap::{subject_public_key, tcb_status_from_str};
+use crate::report::{
+    report_age, AttestationReport, ClockSource, SgxEnclaveReport, SgxQuote, SgxQuoteStatus,
+};
+use crate::AttestationError;
+
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+#[cfg(feature = "mesalock_sgx")]
+use std::untrusted::time::SystemTimeEx;
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Claim of the TCB status of the platform, in the format of `tcbStatus` of
+/// the DCAP TCB info, e.g., `UpToDate`.
+const TCB_STATUS_CLAIM: &str = "x-ms-sgx-tcb-status";
+
+/// DEBUG flag of the enclave attributes.
+const SGX_FLAGS_DEBUG: u8 = 0x02;
+
+/// Signing keys of an MAA instance, as returned by its `/certs` endpoint.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Jwks {
+    pub keys: Vec<Jwk>,
+}
+
+/// RSA signing key of attestation tokens (RFC 7517), given either by the
+/// certificate chain or by the modulus and exponent.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Jwk {
+    /// ID of the key, matched against `kid` of the token header
+    pub kid: String,
+    /// Base64url encoded modulus
+    #[serde(default)]
+    pub n: Option<String>,
+    /// Base64url encoded public exponent
+    #[serde(default)]
+    pub e: Option<String>,
+    /// Base64 encoded DER certificates, of which the first one carries the key
+    #[serde(default)]
+    pub x5c: Vec<String>,
+}
+
+impl Jwks {
+    /// Parse the signing keys in JSON.
+    pub fn from_json(jwks: &[u8]) -> Result<Self> {
+        serde_json::from_slice(jwks).context("Invalid JWKS.")
+    }
+}
+
+impl Jwk {
+    /// Verify an RS256 signature of `message`. The certificate of the key, if
+    /// any, is taken as is, i.e., the JWKS must be obtained from a trusted
+    /// source, e.g., over TLS from the MAA instance.
+    fn verify_rs256(&self, message: &[u8], signature: &[u8]) -> Result<()> {
+        let verified = match (self.x5c.first(), &self.n, &self.e) {
+            (Some(cert), _, _) => {
+                let public_key = subject_public_key(&base64::decode(cert)?)?;
+                ring::signature::UnparsedPublicKey::new(
+                    &ring::signature::RSA_PKCS1_2048_8192_SHA256,
+                    &public_key,
+                )
+                .verify(message, signature)
+            }
+            (None, Some(n), Some(e)) => ring::signature::RsaPublicKeyComponents {
+                n: base64_url_decode(n)?,
+                e: base64_url_decode(e)?,
+            }
+            .verify(
+                &ring::signature::RSA_PKCS1_2048_8192_SHA256,
+                message,
+                signature,
+            ),
+            _ => bail!("Invalid JWK."),
+        };
+
+        verified.map_err(|_| AttestationError::SignatureInvalid.into())
+    }
+}
+
+fn base64_url_decode(encoded: &str) -> Result<Vec<u8>> {
+    base64::decode_config(encoded, base64::URL_SAFE_NO_PAD).context("Invalid base64url encoding.")
+}
+
+/// Verify the RS256 signature of a JWT with the key of `kid` in `jwks`, and
+/// return its claims and the signing key.
+fn verify_jwt<'a>(token: &str, jwks: &'a Jwks) -> Result<(Value, &'a Jwk)> {
+    let parts: Vec<&str> = token.split('.').collect();
+    ensure!(parts.len() == 3, "Invalid JWT.");
+    let header: Value = serde_json::from_slice(&base64_url_decode(parts[0])?)?;
+    ensure!(
+        header["alg"] == "RS256",
+        "Unsupported signature algorithm of the JWT."
+    );
+    let kid = header["kid"]
+        .as_str()
+        .ok_or_else(|| anyhow!("Key ID of the JWT is missing."))?;
+    let key = jwks.keys.iter().find(|key| key.kid == kid).ok_or_else(|| {
+        anyhow!("Unknown key ID of the JWT.").context(AttestationError::SignatureInvalid)
+    })?;
+
+    let signed_len = parts[0].len() + 1 + parts[1].len();
+    key.verify_rs256(
+        &token.as_bytes()[..signed_len],
+        &base64_url_decode(parts[2])?,
+    )?;
+
+    let claims = serde_json::from_slice(&base64_url_decode(parts[1])?)?;
+
+    Ok((claims, key))
+}
+
+/// Decode a hex claim into a byte array of the exact length.
+fn hex_claim<T: for<'a> TryFrom<&'a [u8]>>(claims: &Value, name: &str) -> Result<T> {
+    let bytes = claims[name]
+        .as_str()
+        .and_then(|value| hex::decode(value).ok())
+        .ok_or_else(|| anyhow!("Invalid claim {}.", name))?;
+    T::try_from(&bytes[..]).map_err(|_| anyhow!("Invalid claim {}.", name))
+}
+
+fn u16_claim(claims: &Value, name: &str) -> Result<u16> {
+    claims[name]
+        .as_u64()
+        .and_then(|value| u16::try_from(value).ok())
+        .ok_or_else(|| anyhow!("Invalid claim {}.", name))
+}
+
+fn time_claim(claims: &Value, name: &str) -> Result<Option<SystemTime>> {
+    match &claims[name] {
+        Value::Null => Ok(None),
+        value => {
+            let secs = value
+                .as_u64()
+                .ok_or_else(|| anyhow!("Invalid claim {}.", name))?;
+            Ok(Some(UNIX_EPOCH + Duration::from_secs(secs)))
+        }
+    }
+}
+
+impl AttestationReport {
+    /// Verify an attestation token of Microsoft Azure Attestation, i.e., a JWT
+    /// signed with RS256 by one of the keys in `maa_jwks`, and construct a
+    /// report from its claims:
+    ///
+    /// - the enclave report is assembled from `x-ms-sgx-mrenclave`,
+    ///   `x-ms-sgx-mrsigner`, `x-ms-sgx-product-id`, `x-ms-sgx-svn`,
+    ///   `x-ms-sgx-report-data` and `x-ms-sgx-is-debuggable`, and the other
+    ///   fields, including the quote header, are zero,
+    /// - the quote status is derived from the `x-ms-sgx-tcb-status` claim as
+    ///   for DCAP TCB levels, or is `UnknownBadStatus` with an empty string if
+    ///   it is absent,
+    /// - the timestamp is `iat`, and the token must be valid within `nbf`
+    ///   and `exp`.
+    ///
+    /// The certificate fingerprint of the report is the one of the token
+    /// signing certificate, or zero if the key is not given by a certificate.
+    pub fn from_maa_jwt(token: &str, maa_jwks: &Jwks) -> Result<Self> {
+        let now = SystemTime::now();
+        let (claims, signing_key) = verify_jwt(token, maa_jwks)?;
+        ensure!(
+            claims["x-ms-attestation-type"] == "sgx",
+            "The token is not an SGX attestation token."
+        );
+
+        let timestamp = time_claim(&claims, "iat")?.ok_or_else(|| anyhow!("Invalid claim iat."))?;
+        if let Some(not_before) = time_claim(&claims, "nbf")? {
+            ensure!(not_before <= now, "The token is not yet valid.");
+        }
+        if let Some(expiration) = time_claim(&claims, "exp")? {
+            ensure!(now <= expiration, "The token is expired.");
+        }
+        let freshness = report_age(timestamp, now, Duration::from_secs(0))?;
+
+        let is_debuggable = claims["x-ms-sgx-is-debuggable"]
+            .as_bool()
+            .ok_or_else(|| anyhow!("Invalid claim x-ms-sgx-is-debuggable."))?;
+        let mut attributes = [0u8; 16];
+        if is_debuggable {
+            attributes[0] = SGX_FLAGS_DEBUG;
+        }
+        let isv_enclave_report = SgxEnclaveReport {
+            cpu_svn: [0u8; 16],
+            misc_select: 0,
+            isv_ext_prod_id: [0u8; 16],
+            attributes,
+            mr_enclave: hex_claim(&claims, "x-ms-sgx-mrenclave")?,
+            mr_signer: hex_claim(&claims, "x-ms-sgx-mrsigner")?,
+            config_id: [0u8; 64],
+            isv_prod_id: u16_claim(&claims, "x-ms-sgx-product-id")?,
+            isv_svn: u16_claim(&claims, "x-ms-sgx-svn")?,
+            config_svn: 0,
+            isv_family_id: [0u8; 16],
+            report_data: hex_claim(&claims, "x-ms-sgx-report-data")?,
+            kss_bytes: None,
+        };
+        let sgx_quote_status = claims[TCB_STATUS_CLAIM].as_str().map_or_else(
+            || SgxQuoteStatus::UnknownBadStatus(String::new()),
+            tcb_status_from_str,
+        );
+
+        let mut cert_fingerprint = [0u8; 32];
+        if let Some(cert) = signing_key.x5c.first() {
+            cert_fingerprint.copy_from_slice(
+                ring::digest::digest(&ring::digest::SHA256, &base64::decode(cert)?).as_ref(),
+            );
+        }
+
+        Ok(Self {
+            freshness,
+            timestamp,
+            sgx_quote_status,
+            sgx_quote_body: SgxQuote::from_enclave_report(isv_enclave_report),
+            platform_identity: None,
+            advisory_ids: Vec::new(),
+            platform_info_blob: None,
+            public_key: Vec::new(),
+            cert_fingerprint,
+            clock_used: ClockSource::System(now),
+        })
+    }
+}
+
+#[cfg(all(feature = "enclave_unit_test", feature = "mesalock_sgx"))]
+pub mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::untrusted::fs::File;
+
+    fn read_fixture(name: &str) -> Vec<u8> {
+        let mut bytes = vec![];
+        let mut f = File::open(format!("fixtures/maa/{}", name)).unwrap();
+        f.read_to_end(&mut bytes).unwrap();
+        bytes
+    }
+
+    pub fn test_maa_jwt() {
+        let jwks = Jwks::from_json(&read_fixture("jwks.json")).unwrap();
+        let token = String::from_utf8(read_fixture("token_x5c.jwt")).unwrap();
+
+        let report = AttestationReport::from_maa_jwt(&token, &jwks).unwrap();
+        assert_eq!(report.sgx_quote_status, SgxQuoteStatus::OK);
+        assert_eq!(
+            report.timestamp,
+            UNIX_EPOCH + Duration::from_secs(1_622_505_600)
+        );
+        let enclave_report = &report.sgx_quote_body.isv_enclave_report;
+        assert_eq!(enclave_report.mr_enclave, [0x11; 32]);
+        assert_eq!(enclave_report.mr_signer, [0x22; 32]);
+        assert_eq!(enclave_report.report_data, [0x33; 64]);
+        assert!(enclave_report.satisfies(2, 5));
+        assert!(!enclave_report.sgx_attributes().is_debug());
+        assert_ne!(report.cert_fingerprint(), [0u8; 32]);
+
+        // The key can also be given by the modulus and exponent
+        let token = String::from_utf8(read_fixture("token_ne.jwt")).unwrap();
+        let report = AttestationReport::from_maa_jwt(&token, &jwks).unwrap();
+        assert_eq!(report.cert_fingerprint(), [0u8; 32]);
+
+        // Tampered claims
+        let parts: Vec<&str> = token.split('.').collect();
+        let mut claims: Value =
+            serde_json::from_slice(&base64_url_decode(parts[1]).unwrap()).unwrap();
+        claims["x-ms-sgx-svn"] = 6.into();
+        let payload = base64::encode_config(
+            &serde_json::to_vec(&claims).unwrap(),
+            base64::URL_SAFE_NO_PAD,
+        );
+        let tampered = [parts[0], &payload, parts[2]].join(".");
+        let err = AttestationReport::from_maa_jwt(&tampered, &jwks).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::SignatureInvalid) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+
+        // Unknown signing key
+        let other_jwks = Jwks::default();
+        let err = AttestationReport::from_maa_jwt(&token, &other_jwks).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::SignatureInvalid) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+        assert!(AttestationReport::from_maa_jwt("not-a-jwt", &jwks).is_err());
+    }
+}
@@ -0,0 +1,608 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! This module implements attestation policies written as small expressions
+//! over the claims of a report (see `AttestationReport::as_claims`), so that
+//! policies can be managed without recompiling services. For example:
+//!
+//! ```text
+//! mr_signer == "83d719e7..." && isv_svn >= 3 && (status == "OK" || !debug)
+//! ```
+//!
+//! An expression consists of claim names, string literals in double quotes,
+//! unsigned integers, `true`/`false`, comparisons (`==`, `!=`, `<`, `<=`, `>`,
+//! `>=`), `!`, `&&`, `||` and parentheses. Only integers can be ordered, and
+//! the expression must evaluate to a boolean.
+//!
+//! Policies and other checks can be grouped into named `ComplianceProfile`s,
+//! which are registered in `ComplianceProfiles` and referred to by name, e.g.,
+//! `report.satisfies(&profiles, "pci-dss")`.
+//!
+//! The acceptance of quote statuses and outstanding security advisories is
+//! decided by a `TcbPolicy`, e.g., `TcbPolicy::allow_hardening(&ids)`.
+
+use std::prelude::v1::*;
+
+use crate::report::{AttestationReport, QuoteBodyParser, SgxQuoteStatus};
+use crate::verifier::IsvSvnPolicy;
+use crate::AttestationError;
+
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use anyhow::{anyhow, bail, ensure, Result};
+use serde_json::Value;
+use teaclave_types::EnclaveMeasurement;
+
+/// Maximum length of a policy expression in bytes.
+const MAX_POLICY_LEN: usize = 4096;
+/// Maximum nesting depth of a policy expression.
+const MAX_POLICY_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Int(u64),
+    Bool(bool),
+    Op(&'static str),
+    LParen,
+    RParen,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+    Str(String),
+    Int(u64),
+    Bool(bool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Literal(Literal),
+    Claim(String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Cmp(&'static str, Box<Expr>, Box<Expr>),
+}
+
+/// An attestation policy parsed from an expression string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Policy {
+    expr: Expr,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    const OPS: &[&str] = &["==", "!=", "<=", ">=", "&&", "||", "<", ">", "!"];
+
+    let mut tokens = Vec::new();
+    let mut rest = input.trim_start();
+    while let Some(c) = rest.chars().next() {
+        if let Some(op) = OPS.iter().find(|op| rest.starts_with(*op)) {
+            tokens.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        } else if c == '(' || c == ')' {
+            tokens.push(if c == '(' {
+                Token::LParen
+            } else {
+                Token::RParen
+            });
+            rest = &rest[1..];
+        } else if c == '"' {
+            let end = rest[1..]
+                .find('"')
+                .ok_or_else(|| anyhow!("Unterminated string in policy."))?;
+            tokens.push(Token::Str(rest[1..=end].to_string()));
+            rest = &rest[end + 2..];
+        } else if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let value = rest[..end]
+                .parse()
+                .map_err(|_| anyhow!("Invalid integer in policy: {}", &rest[..end]))?;
+            tokens.push(Token::Int(value));
+            rest = &rest[end..];
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            tokens.push(match &rest[..end] {
+                "true" => Token::Bool(true),
+                "false" => Token::Bool(false),
+                ident => Token::Ident(ident.to_string()),
+            });
+            rest = &rest[end..];
+        } else {
+            bail!("Unexpected character in policy: {}", c);
+        }
+        rest = rest.trim_start();
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser of policy expressions.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek_op(&self, op: &str) -> bool {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(o)) => *o == op,
+            _ => false,
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut lhs = self.and()?;
+        while self.peek_op("||") {
+            self.pos += 1;
+            lhs = Expr::Or(Box::new(lhs), Box::new(self.and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut lhs = self.unary()?;
+        while self.peek_op("&&") {
+            self.pos += 1;
+            lhs = Expr::And(Box::new(lhs), Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.peek_op("!") {
+            self.pos += 1;
+            self.nested(|parser| Ok(Expr::Not(Box::new(parser.unary()?))))
+        } else {
+            self.comparison()
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Expr> {
+        let lhs = self.primary()?;
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) if !["&&", "||", "!"].contains(op) => {
+                let op = *op;
+                self.pos += 1;
+                Ok(Expr::Cmp(op, Box::new(lhs), Box::new(self.primary()?)))
+            }
+            _ => Ok(lhs),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some(Token::LParen) => {
+                let expr = self.nested(Parser::or)?;
+                ensure!(
+                    self.next() == Some(Token::RParen),
+                    "Missing closing parenthesis in policy."
+                );
+                Ok(expr)
+            }
+            Some(Token::Ident(name)) => Ok(Expr::Claim(name)),
+            Some(Token::Str(s)) => Ok(Expr::Literal(Literal::Str(s))),
+            Some(Token::Int(i)) => Ok(Expr::Literal(Literal::Int(i))),
+            Some(Token::Bool(b)) => Ok(Expr::Literal(Literal::Bool(b))),
+            Some(token) => bail!("Unexpected token in policy: {:?}", token),
+            None => bail!("Unexpected end of policy."),
+        }
+    }
+
+    fn nested<F>(&mut self, f: F) -> Result<Expr>
+    where
+        F: FnOnce(&mut Self) -> Result<Expr>,
+    {
+        ensure!(
+            self.depth < MAX_POLICY_DEPTH,
+            "Policy is nested too deeply."
+        );
+        self.depth += 1;
+        let expr = f(self);
+        self.depth -= 1;
+        expr
+    }
+}
+
+fn claim_literal(name: &str, value: &Value) -> Result<Literal> {
+    match value {
+        Value::String(s) => Ok(Literal::Str(s.clone())),
+        Value::Bool(b) => Ok(Literal::Bool(*b)),
+        Value::Number(n) => n
+            .as_u64()
+            .map(Literal::Int)
+            .ok_or_else(|| anyhow!("Unsupported value of claim {} in policy.", name)),
+        _ => bail!("Unsupported value of claim {} in policy.", name),
+    }
+}
+
+impl Expr {
+    fn eval(&self, claims: &HashMap<String, Value>) -> Result<Literal> {
+        let boolean = |expr: &Expr| match expr.eval(claims)? {
+            Literal::Bool(b) => Ok(b),
+            other => bail!("Expected a boolean in policy, found {:?}.", other),
+        };
+
+        let value = match self {
+            Expr::Literal(literal) => literal.clone(),
+            Expr::Claim(name) => {
+                let value = claims
+                    .get(name)
+                    .ok_or_else(|| anyhow!("Unknown claim in policy: {}", name))?;
+                claim_literal(name, value)?
+            }
+            Expr::Not(expr) => Literal::Bool(!boolean(expr)?),
+            Expr::And(lhs, rhs) => Literal::Bool(boolean(lhs)? && boolean(rhs)?),
+            Expr::Or(lhs, rhs) => Literal::Bool(boolean(lhs)? || boolean(rhs)?),
+            Expr::Cmp(op, lhs, rhs) => {
+                let result = match (op, lhs.eval(claims)?, rhs.eval(claims)?) {
+                    (&"==", l, r) if same_type(&l, &r) => l == r,
+                    (&"!=", l, r) if same_type(&l, &r) => l != r,
+                    (&"<", Literal::Int(l), Literal::Int(r)) => l < r,
+                    (&"<=", Literal::Int(l), Literal::Int(r)) => l <= r,
+                    (&">", Literal::Int(l), Literal::Int(r)) => l > r,
+                    (&">=", Literal::Int(l), Literal::Int(r)) => l >= r,
+                    (op, l, r) => bail!("Cannot compare {:?} {} {:?} in policy.", l, op, r),
+                };
+                Literal::Bool(result)
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+fn same_type(lhs: &Literal, rhs: &Literal) -> bool {
+    std::mem::discriminant(lhs) == std::mem::discriminant(rhs)
+}
+
+impl Policy {
+    /// Parse a policy from an expression string.
+    pub fn parse(policy: &str) -> Result<Self> {
+        ensure!(policy.len() <= MAX_POLICY_LEN, "Policy is too long.");
+        let mut parser = Parser {
+            tokens: tokenize(policy)?,
+            pos: 0,
+            depth: 0,
+        };
+        let expr = parser.or()?;
+        ensure!(
+            parser.pos == parser.tokens.len(),
+            "Unexpected trailing tokens in policy."
+        );
+
+        Ok(Self { expr })
+    }
+
+    /// Evaluate the policy over the given claims. Returns an error if the
+    /// policy refers to an unknown claim or compares values of different
+    /// types.
+    pub fn evaluate(&self, claims: &HashMap<String, Value>) -> Result<bool> {
+        match self.expr.eval(claims)? {
+            Literal::Bool(b) => Ok(b),
+            other => bail!("Policy evaluates to {:?} instead of a boolean.", other),
+        }
+    }
+
+    /// Check whether the attestation report satisfies the policy.
+    pub fn verify<Q: QuoteBodyParser>(&self, report: &AttestationReport<Q>) -> Result<bool> {
+        self.evaluate(&report.as_claims())
+    }
+}
+
+/// A named, auditable group of checks on attestation reports.
+#[derive(Debug, Default)]
+pub struct ComplianceProfile {
+    /// Accepted enclave measurements, any measurement is accepted if empty
+    pub accepted_measurements: Vec<EnclaveMeasurement>,
+    /// Minimum ISV SVN of enclaves, not checked if not given
+    pub isv_svn_policy: Option<IsvSvnPolicy>,
+    /// Accepted range of the PCE SVN, e.g., up to the latest qualified PCE,
+    /// not checked if not given
+    pub pce_svn_range: Option<RangeInclusive<u16>>,
+    /// Accepted quote statuses
+    pub accepted_statuses: Vec<SgxQuoteStatus>,
+    /// Reject enclaves in debug mode
+    pub reject_debug: bool,
+    /// Additional policy over the claims of the report
+    pub policy: Option<Policy>,
+}
+
+impl ComplianceProfile {
+    /// Check whether the attestation report satisfies all checks of the
+    /// profile. Returns an error if the policy cannot be evaluated.
+    pub fn verify<Q: QuoteBodyParser>(&self, report: &AttestationReport<Q>) -> Result<bool> {
+        let enclave_report = report.sgx_quote_body.isv_enclave_report();
+        let measurement =
+            EnclaveMeasurement::new(enclave_report.mr_enclave, enclave_report.mr_signer);
+        if !self.accepted_measurements.is_empty()
+            && !self.accepted_measurements.contains(&measurement)
+        {
+            return Ok(false);
+        }
+        if let Some(isv_svn_policy) = &self.isv_svn_policy {
+            if isv_svn_policy.verify(report).is_err() {
+                return Ok(false);
+            }
+        }
+        if let Some(pce_svn_range) = &self.pce_svn_range {
+            match report.sgx_quote_body.isv_svn_pce() {
+                Some(isv_svn_pce) if pce_svn_range.contains(&isv_svn_pce) => (),
+                _ => return Ok(false),
+            }
+        }
+        if !self.accepted_statuses.contains(&report.sgx_quote_status) {
+            return Ok(false);
+        }
+        if self.reject_debug && enclave_report.sgx_attributes().is_debug() {
+            return Ok(false);
+        }
+
+        match &self.policy {
+            Some(policy) => policy.verify(report),
+            None => Ok(true),
+        }
+    }
+}
+
+/// Acceptance of the quote status of a report and of its outstanding security
+/// advisories. Reports with the `OK` status are always accepted, and reports
+/// with other accepted statuses only if all of their advisory IDs are allowed.
+#[derive(Debug, Clone)]
+pub struct TcbPolicy {
+    /// Accepted quote statuses other than `OK`
+    pub accepted_statuses: Vec<SgxQuoteStatus>,
+    /// Advisory IDs allowed for the accepted statuses, e.g., advisories
+    /// mitigated by the enclave
+    pub allowed_advisory_ids: Vec<String>,
+}
+
+impl TcbPolicy {
+    /// Accept `OK` reports only.
+    pub fn strict() -> Self {
+        Self {
+            accepted_statuses: Vec::new(),
+            allowed_advisory_ids: Vec::new(),
+        }
+    }
+
+    /// Accept `OK` reports, and `SW_HARDENING_NEEDED` reports whose
+    /// advisories are all in `advisory_ids`.
+    pub fn allow_hardening(advisory_ids: &[&str]) -> Self {
+        Self {
+            accepted_statuses: vec![SgxQuoteStatus::SwHardeningNeeded],
+            allowed_advisory_ids: advisory_ids.iter().map(|id| id.to_string()).collect(),
+        }
+    }
+
+    /// Check whether the report is accepted. Otherwise,
+    /// `AttestationError::QuoteStatusUntrusted` or
+    /// `AttestationError::AdvisoryNotAllowed` with the first advisory ID not
+    /// allowed is returned.
+    pub fn evaluate<Q>(&self, report: &AttestationReport<Q>) -> Result<()> {
+        let status = &report.sgx_quote_status;
+        if *status == SgxQuoteStatus::OK {
+            return Ok(());
+        }
+        ensure!(
+            self.accepted_statuses.contains(status),
+            AttestationError::QuoteStatusUntrusted(status.clone())
+        );
+        if let Some(advisory_id) = report
+            .advisory_ids
+            .iter()
+            .find(|id| !self.allowed_advisory_ids.contains(id))
+        {
+            bail!(AttestationError::AdvisoryNotAllowed(advisory_id.clone()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Registry of compliance profiles by name.
+#[derive(Debug, Default)]
+pub struct ComplianceProfiles {
+    profiles: HashMap<String, ComplianceProfile>,
+}
+
+impl ComplianceProfiles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a profile, replacing the profile of the same name if any.
+    pub fn register(&mut self, name: &str, profile: ComplianceProfile) {
+        self.profiles.insert(name.to_string(), profile);
+    }
+
+    /// Get the profile of the given name.
+    pub fn get(&self, name: &str) -> Option<&ComplianceProfile> {
+        self.profiles.get(name)
+    }
+}
+
+impl<Q: QuoteBodyParser> AttestationReport<Q> {
+    /// Check whether the report satisfies the registered compliance profile of
+    /// the given name. Returns an error if the profile is not registered.
+    pub fn satisfies(&self, profiles: &ComplianceProfiles, name: &str) -> Result<bool> {
+        profiles
+            .get(name)
+            .ok_or_else(|| anyhow!("Unknown compliance profile: {}", name))?
+            .verify(self)
+    }
+}
+
+#[cfg(all(feature = "enclave_unit_test", feature = "mesalock_sgx"))]
+pub mod tests {
+    use super::*;
+    use crate::quote::tests::SgxQuoteBuilder;
+    use crate::report::{ClockSource, SgxEpidQuoteSigType, SgxQuote, SgxQuoteVersion};
+    use serde_json::json;
+    use std::io::Read;
+    use std::time::{Duration, UNIX_EPOCH};
+    use std::untrusted::fs::File;
+
+    pub fn test_policy_evaluate() {
+        let claims: HashMap<String, Value> = vec![
+            ("mr_signer".to_string(), json!("abcd")),
+            ("isv_svn".to_string(), json!(3)),
+            ("status".to_string(), json!("OK")),
+            ("debug".to_string(), json!(false)),
+        ]
+        .into_iter()
+        .collect();
+        let evaluate = |policy: &str| Policy::parse(policy).unwrap().evaluate(&claims);
+
+        assert!(evaluate(r#"mr_signer == "abcd" && isv_svn >= 3 && status == "OK""#).unwrap());
+        assert!(evaluate(r#"(status == "GROUP_OUT_OF_DATE" || !debug) && isv_svn < 4"#).unwrap());
+        assert!(!evaluate("isv_svn > 3").unwrap());
+        assert!(!evaluate(r#"!(mr_signer != "abcd") && debug"#).unwrap());
+
+        assert!(evaluate("isv_prod_id == 0").is_err());
+        assert!(evaluate(r#"isv_svn == "3""#).is_err());
+        assert!(evaluate("isv_svn").is_err());
+
+        assert!(Policy::parse("isv_svn >=").is_err());
+        assert!(Policy::parse(r#"status == "OK"#).is_err());
+        assert!(Policy::parse("(isv_svn == 3").is_err());
+        assert!(Policy::parse("isv_svn == 3 3").is_err());
+        assert!(Policy::parse(&"!".repeat(64)).is_err());
+    }
+
+    pub fn test_compliance_profiles() {
+        let read_fixture = |path: &str| {
+            let mut bytes = vec![];
+            let mut f = File::open(path).unwrap();
+            f.read_to_end(&mut bytes).unwrap();
+            bytes
+        };
+        let certs = vec![rustls::Certificate(read_fixture(
+            "fixtures/tls_ra_cert_v4.der",
+        ))];
+        let dcap_root_ca_cert = read_fixture("fixtures/dcap_root_ca_cert.der");
+        let now = AttestationReport::parse_unverified(&certs[0].0)
+            .unwrap()
+            .timestamp;
+        let report = crate::verifier::AttestationVerifier::new(&dcap_root_ca_cert)
+            .unwrap()
+            .verify_at(&certs[0].0, now)
+            .unwrap();
+        let enclave_report = &report.sgx_quote_body.isv_enclave_report;
+
+        let mut profiles = ComplianceProfiles::new();
+        profiles.register(
+            "internal",
+            ComplianceProfile {
+                accepted_measurements: vec![EnclaveMeasurement::new(
+                    enclave_report.mr_enclave,
+                    enclave_report.mr_signer,
+                )],
+                accepted_statuses: vec![SgxQuoteStatus::OK],
+                policy: Some(Policy::parse("isv_svn >= 0").unwrap()),
+                ..ComplianceProfile::default()
+            },
+        );
+        profiles.register(
+            "internal-high",
+            ComplianceProfile {
+                accepted_statuses: vec![SgxQuoteStatus::OK],
+                reject_debug: true,
+                ..ComplianceProfile::default()
+            },
+        );
+        profiles.register("no-status", ComplianceProfile::default());
+        for (name, range) in vec![("pce-in-range", 15..=15), ("pce-above-ceiling", 0..=14)] {
+            profiles.register(
+                name,
+                ComplianceProfile {
+                    pce_svn_range: Some(range),
+                    accepted_statuses: vec![SgxQuoteStatus::OK],
+                    ..ComplianceProfile::default()
+                },
+            );
+        }
+
+        assert!(report.satisfies(&profiles, "internal").unwrap());
+        // The fixture enclave is in debug mode
+        assert!(!report.satisfies(&profiles, "internal-high").unwrap());
+        assert!(!report.satisfies(&profiles, "no-status").unwrap());
+        assert!(report.satisfies(&profiles, "pci-dss").is_err());
+
+        // A report of a quote with the PCE SVN 15
+        let quote = SgxQuoteBuilder::new(SgxQuoteVersion::V2(SgxEpidQuoteSigType::Linkable))
+            .isv_svn_pce(15)
+            .to_bytes();
+        let report = AttestationReport {
+            sgx_quote_body: SgxQuote::parse_from(&quote).unwrap(),
+            ..report
+        };
+        assert!(report.satisfies(&profiles, "pce-in-range").unwrap());
+        assert!(!report.satisfies(&profiles, "pce-above-ceiling").unwrap());
+    }
+
+    pub fn test_tcb_policy() {
+        let report = |sgx_quote_status, advisory_ids: &[&str]| AttestationReport {
+            freshness: Duration::from_secs(0),
+            timestamp: UNIX_EPOCH,
+            sgx_quote_status,
+            sgx_quote_body: (),
+            platform_identity: None,
+            advisory_ids: advisory_ids.iter().map(|id| id.to_string()).collect(),
+            platform_info_blob: None,
+            public_key: Vec::new(),
+            cert_fingerprint: [0u8; 32],
+            clock_used: ClockSource::Injected(UNIX_EPOCH),
+        };
+        let ok = report(SgxQuoteStatus::OK, &[]);
+        let hardening = report(SgxQuoteStatus::SwHardeningNeeded, &["INTEL-SA-00334"]);
+        let out_of_date = report(SgxQuoteStatus::GroupOutOfDate, &["INTEL-SA-00334"]);
+
+        let strict = TcbPolicy::strict();
+        assert!(strict.evaluate(&ok).is_ok());
+        let error = strict.evaluate(&hardening).unwrap_err();
+        match error.downcast_ref::<AttestationError>() {
+            Some(AttestationError::QuoteStatusUntrusted(SgxQuoteStatus::SwHardeningNeeded)) => (),
+            _ => panic!("unexpected error: {:?}", error),
+        }
+
+        let allow_hardening = TcbPolicy::allow_hardening(&["INTEL-SA-00334", "INTEL-SA-00615"]);
+        assert!(allow_hardening.evaluate(&ok).is_ok());
+        assert!(allow_hardening.evaluate(&hardening).is_ok());
+        assert!(allow_hardening.evaluate(&out_of_date).is_err());
+        let error = TcbPolicy::allow_hardening(&["INTEL-SA-00615"])
+            .evaluate(&hardening)
+            .unwrap_err();
+        match error.downcast_ref::<AttestationError>() {
+            Some(AttestationError::AdvisoryNotAllowed(id)) if id == "INTEL-SA-00334" => (),
+            _ => panic!("unexpected error: {:?}", error),
+        }
+    }
+}
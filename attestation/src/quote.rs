@@ -0,0 +1,2248 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! This module provides types and parsers of SGX quotes and enclave reports.
+//! It only depends on `core` and `alloc`, so that quotes can also be parsed
+//! without the `std` feature, e.g., in a bootloader.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::AttestationError;
+
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::Range;
+
+use anyhow::{anyhow, bail, ensure, Error, Result};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+/// (De)serialize byte arrays as hex strings in human-readable formats, e.g.,
+/// `#[serde(with = "hex_bytes")]`.
+pub(crate) mod hex_bytes {
+    use alloc::string::String;
+    use core::convert::TryFrom;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S, T>(bytes: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: AsRef<[u8]>,
+    {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub(crate) fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: for<'a> TryFrom<&'a [u8]>,
+    {
+        let string = String::deserialize(deserializer)?;
+        let bytes = hex::decode(&string).map_err(Error::custom)?;
+        T::try_from(&bytes).map_err(|_| Error::invalid_length(bytes.len(), &"bytes of the field"))
+    }
+}
+
+/// A report generated by an enclave that contains measurement, identity and
+/// other data related to enclave.
+///
+/// # Note
+///
+/// Do not confuse `SgxEnclaveReport` with `AttestationReport`.
+/// `SgxEnclaveReport` is generated by SGX hardware and endorsed by Quoting
+/// Enclave through local attestation. The endorsed `SgxEnclaveReport` is an
+/// `SgxQuote`. The quote is then sent to some attestation service (IAS or
+/// DCAP-based AS). The endorsed `SgxQuote` is an attestation report signed by
+/// attestation service's private key, a.k.a., `EndorsedAttestationReport`.
+#[derive(Serialize, Deserialize)]
+pub struct SgxEnclaveReport {
+    /// Security version number of host system's CPU
+    #[serde(with = "hex_bytes")]
+    pub cpu_svn: [u8; 16],
+    /// Misc select bits for the target enclave. Reserved for future function
+    /// extension.
+    pub misc_select: u32,
+    /// Extended product ID of the enclave (KSS)
+    #[serde(with = "hex_bytes")]
+    pub isv_ext_prod_id: [u8; 16],
+    /// Attributes of the enclave, for example, whether the enclave is running
+    /// in debug mode.
+    #[serde(with = "hex_bytes")]
+    pub attributes: [u8; 16],
+    /// Measurement value of the enclave. See
+    /// [`EnclaveMeasurement`](../types/struct.EnclaveMeasurement.html)
+    #[serde(with = "hex_bytes")]
+    pub mr_enclave: [u8; 32],
+    /// Measurement value of the public key that verified the enclave. See
+    /// [`EnclaveMeasurement`](../types/struct.EnclaveMeasurement.html)
+    #[serde(with = "hex_bytes")]
+    pub mr_signer: [u8; 32],
+    /// Configuration ID of the enclave given at enclave creation (KSS), e.g.,
+    /// to distinguish tenants of the same enclave
+    #[serde(with = "hex_bytes")]
+    pub config_id: [u8; 64],
+    /// Product ID of the enclave
+    pub isv_prod_id: u16,
+    /// Security version number of the enclave
+    pub isv_svn: u16,
+    /// Security version number of the enclave configuration (KSS)
+    pub config_svn: u16,
+    /// Family ID of the enclave (KSS)
+    #[serde(with = "hex_bytes")]
+    pub isv_family_id: [u8; 16],
+    /// Set of data used for communication between enclave and target enclave
+    #[serde(with = "hex_bytes")]
+    pub report_data: [u8; 64],
+    /// KSS fields in the bytes of a report parsed by `parse_from` or
+    /// `parse_from_strict`, which leave the public KSS fields zero. Kept so
+    /// that `to_bytes` re-creates the report as it was sent.
+    #[serde(skip)]
+    pub(crate) kss_bytes: Option<KssFields>,
+}
+
+/// Raw values of ISVEXTPRODID, CONFIGID, CONFIGSVN and ISVFAMILYID of a report.
+#[derive(Clone, Copy)]
+pub(crate) struct KssFields {
+    pub(crate) isv_ext_prod_id: [u8; 16],
+    pub(crate) config_id: [u8; 64],
+    pub(crate) config_svn: u16,
+    pub(crate) isv_family_id: [u8; 16],
+}
+
+impl fmt::Debug for SgxEnclaveReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "cpu_svn: {}", hex::encode(self.cpu_svn))?;
+        writeln!(f, "misc_select: {:?}", self.misc_select)?;
+        writeln!(f, "isv_ext_prod_id: {}", hex::encode(self.isv_ext_prod_id))?;
+        writeln!(f, "attributes: {}", hex::encode(self.attributes))?;
+        writeln!(f, "mr_enclave: {}", hex::encode(self.mr_enclave))?;
+        writeln!(f, "mr_signer: {}", hex::encode(self.mr_signer))?;
+        writeln!(f, "config_id: {}", hex::encode(&self.config_id[..]))?;
+        writeln!(f, "isv_prod_id: {}", self.isv_prod_id)?;
+        writeln!(f, "isv_svn: {}", self.isv_svn)?;
+        writeln!(f, "config_svn: {}", self.config_svn)?;
+        writeln!(f, "isv_family_id: {}", hex::encode(self.isv_family_id))?;
+        writeln!(f, "report_data: {}", hex::encode(&self.report_data[..]))
+    }
+}
+
+impl fmt::Display for SgxEnclaveReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "CPU version (hex): {}", hex::encode(self.cpu_svn))?;
+        writeln!(f, "SSA Frame extended feature set: {}", self.misc_select)?;
+        writeln!(
+            f,
+            "Attributes of the enclave (hex): {}",
+            hex::encode(self.attributes)
+        )?;
+        writeln!(
+            f,
+            "Enclave measurement (hex): {}",
+            hex::encode(self.mr_enclave)
+        )?;
+        writeln!(
+            f,
+            "Hash of the enclave singing key (hex): {}",
+            hex::encode(self.mr_signer)
+        )?;
+        writeln!(f, "Enclave product ID: {}", self.isv_prod_id)?;
+        writeln!(f, "Security version of the enclave: {}", self.isv_svn)?;
+        writeln!(
+            f,
+            "The value of REPORT (hex): {}",
+            hex::encode(&self.report_data.to_vec())
+        )
+    }
+}
+
+impl SgxEnclaveReport {
+    /// Parse bytes of report into `SgxEnclaveReport`.
+    pub fn parse_from(bytes: &[u8]) -> Result<Self> {
+        Self::parse(bytes, false, false)
+    }
+
+    /// Parse bytes of report of a KSS enclave into `SgxEnclaveReport`, i.e.,
+    /// `isv_ext_prod_id`, `config_id`, `config_svn` and `isv_family_id` are
+    /// parsed as well. These fields are left zero by `parse_from`, though
+    /// `to_bytes` still serializes them as parsed.
+    pub fn parse_from_kss(bytes: &[u8]) -> Result<Self> {
+        Self::parse(bytes, false, true)
+    }
+
+    /// Parse a report of `len` bytes at `offset` of `buf` without copying it.
+    pub fn parse_at(buf: &[u8], offset: usize, len: usize) -> Result<Self> {
+        Self::parse_from(subslice(buf, offset, len)?)
+    }
+
+    /// Parse bytes of report into `SgxEnclaveReport` in strict mode, i.e.,
+    /// all reserved regions of the report must be zero. Otherwise,
+    /// `AttestationError::NonZeroReserved` is returned with the offset of the
+    /// first non-zero byte (relative to the start of the report).
+    pub fn parse_from_strict(bytes: &[u8]) -> Result<Self> {
+        Self::parse(bytes, true, false)
+    }
+
+    /// Iterate over the reserved regions of report `bytes` as `(offset,
+    /// region)`, where offsets are relative to the start of the report.
+    /// Regions beyond the end of `bytes` are skipped.
+    pub fn reserved_regions(bytes: &[u8]) -> impl Iterator<Item = (usize, &[u8])> {
+        SGX_REPORT_RESERVED_REGIONS
+            .iter()
+            .filter_map(move |&(offset, len)| Some((offset, subslice(bytes, offset, len).ok()?)))
+    }
+
+    fn parse<'a>(bytes: &'a [u8], strict: bool, kss: bool) -> Result<Self> {
+        let mut pos: usize = 0;
+        let mut take = |n: usize| -> Result<&'a [u8]> {
+            let end = pos
+                .checked_add(n)
+                .ok_or_else(|| Error::new(AttestationError::QuoteTruncated))?;
+            if n > 0 && bytes.len() >= end {
+                let ret = &bytes[pos..end];
+                pos = end;
+                Ok(ret)
+            } else {
+                bail!(AttestationError::QuoteTruncated)
+            }
+        };
+
+        // Start parsing report by bytes following specifications. Don't
+        // transmute directly, since there may cause endianness issue.
+        // off 48, size 16
+        let cpu_svn = <[u8; 16]>::try_from(take(16)?)?;
+
+        // off 64, size 4
+        let misc_select = u32::from_le_bytes(<[u8; 4]>::try_from(take(4)?)?);
+
+        // off 68, size 12
+        take(12)?;
+
+        // off 80, size 16
+        let isv_ext_prod_id = <[u8; 16]>::try_from(take(16)?)?;
+
+        // off 96, size 16
+        let attributes = <[u8; 16]>::try_from(take(16)?)?;
+
+        // off 112, size 32
+        let mr_enclave = <[u8; 32]>::try_from(take(32)?)?;
+
+        // off 144, size 32
+        take(32)?;
+
+        // off 176, size 32
+        let mr_signer = <[u8; 32]>::try_from(take(32)?)?;
+
+        // off 208, size 32
+        take(32)?;
+
+        // off 240, size 64
+        let config_id = <[u8; 64]>::try_from(take(64)?)?;
+
+        // off 304, size 2
+        let isv_prod_id = u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?);
+
+        // off 306, size 2
+        let isv_svn = u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?);
+
+        // off 308, size 2
+        let config_svn = u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?);
+
+        // off 310, size 42
+        take(42)?;
+
+        // off 352, size 16
+        let isv_family_id = <[u8; 16]>::try_from(take(16)?)?;
+
+        // off 368, size 64
+        let mut report_data = [0u8; 64];
+        let _report_data = take(64)?;
+        let mut _it = _report_data.iter();
+        for i in report_data.iter_mut() {
+            *i = *_it.next().ok_or(AttestationError::QuoteTruncated)?;
+        }
+
+        ensure!(pos == bytes.len(), AttestationError::QuoteMalformed);
+
+        if strict {
+            for (offset, region) in Self::reserved_regions(bytes) {
+                ensure_zeroed(offset, region)?;
+            }
+        }
+
+        let kss_fields = KssFields {
+            isv_ext_prod_id,
+            config_id,
+            config_svn,
+            isv_family_id,
+        };
+        let (isv_ext_prod_id, config_id, config_svn, isv_family_id, kss_bytes) = if kss {
+            (isv_ext_prod_id, config_id, config_svn, isv_family_id, None)
+        } else {
+            ([0u8; 16], [0u8; 64], 0, [0u8; 16], Some(kss_fields))
+        };
+
+        Ok(SgxEnclaveReport {
+            cpu_svn,
+            misc_select,
+            isv_ext_prod_id,
+            attributes,
+            mr_enclave,
+            mr_signer,
+            config_id,
+            isv_prod_id,
+            isv_svn,
+            config_svn,
+            isv_family_id,
+            report_data,
+            kss_bytes,
+        })
+    }
+
+    /// KSS fields of the report, i.e., the parsed bytes of a report parsed by
+    /// `parse_from` or `parse_from_strict`, and the public fields otherwise.
+    pub(crate) fn kss_fields(&self) -> KssFields {
+        self.kss_bytes.unwrap_or(KssFields {
+            isv_ext_prod_id: self.isv_ext_prod_id,
+            config_id: self.config_id,
+            config_svn: self.config_svn,
+            isv_family_id: self.isv_family_id,
+        })
+    }
+
+    /// Serialize the report into its layout in quotes, with the reserved
+    /// regions zeroed, e.g., to verify a detached signature over the report.
+    /// The KSS fields of a report parsed by `parse_from` or
+    /// `parse_from_strict` are serialized as parsed, even though the public
+    /// fields are left zero.
+    pub fn to_bytes(&self) -> [u8; SGX_QUOTE_REPORT_BODY_SIZE] {
+        let kss = self.kss_fields();
+        let mut bytes = [0u8; SGX_QUOTE_REPORT_BODY_SIZE];
+        // Offsets are relative to the start of the report, i.e., quote offset
+        // minus 48.
+        let fields: [(usize, &[u8]); 12] = [
+            (0, &self.cpu_svn),
+            (16, &self.misc_select.to_le_bytes()),
+            (32, &kss.isv_ext_prod_id),
+            (48, &self.attributes),
+            (64, &self.mr_enclave),
+            (128, &self.mr_signer),
+            (192, &kss.config_id),
+            (256, &self.isv_prod_id.to_le_bytes()),
+            (258, &self.isv_svn.to_le_bytes()),
+            (260, &kss.config_svn.to_le_bytes()),
+            (304, &kss.isv_family_id),
+            (320, &self.report_data),
+        ];
+        for (offset, field) in fields.iter() {
+            bytes[*offset..*offset + field.len()].copy_from_slice(field);
+        }
+
+        bytes
+    }
+
+    /// SHA-256 digest of the report serialized by `to_bytes`.
+    #[cfg(feature = "std")]
+    pub fn sha256(&self) -> [u8; 32] {
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(
+            ring::digest::digest(&ring::digest::SHA256, &self.to_bytes()).as_ref(),
+        );
+
+        digest
+    }
+
+    /// Convert the report into JSON for generic inspection tools. Byte-array
+    /// fields are hex-encoded and integers are kept as numbers. The KSS fields
+    /// (`isv_ext_prod_id`, `config_id`, `config_svn` and `isv_family_id`) are
+    /// zero unless the report is parsed by `parse_from_kss`:
+    ///
+    /// ```json
+    /// {
+    ///   "cpu_svn": "<hex>",
+    ///   "misc_select": 0,
+    ///   "isv_ext_prod_id": "<hex>",
+    ///   "attributes": "<hex>",
+    ///   "mr_enclave": "<hex>",
+    ///   "mr_signer": "<hex>",
+    ///   "config_id": "<hex>",
+    ///   "isv_prod_id": 0,
+    ///   "isv_svn": 0,
+    ///   "config_svn": 0,
+    ///   "isv_family_id": "<hex>",
+    ///   "report_data": "<hex>"
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn to_json(&self) -> Value {
+        json!({
+            "cpu_svn": hex::encode(self.cpu_svn),
+            "misc_select": self.misc_select,
+            "isv_ext_prod_id": hex::encode(self.isv_ext_prod_id),
+            "attributes": hex::encode(self.attributes),
+            "mr_enclave": hex::encode(self.mr_enclave),
+            "mr_signer": hex::encode(self.mr_signer),
+            "config_id": hex::encode(&self.config_id[..]),
+            "isv_prod_id": self.isv_prod_id,
+            "isv_svn": self.isv_svn,
+            "config_svn": self.config_svn,
+            "isv_family_id": hex::encode(self.isv_family_id),
+            "report_data": hex::encode(&self.report_data[..]),
+        })
+    }
+
+    /// Verify that `report_data` starts with `prefix_expected` (e.g., a public
+    /// key or hash binding) and that the remaining bytes are accepted by
+    /// `suffix_validator` (e.g., a nonce, version or application data check).
+    pub fn verify_report_data_split<F>(
+        &self,
+        prefix_expected: &[u8],
+        suffix_validator: F,
+    ) -> Result<()>
+    where
+        F: Fn(&[u8]) -> bool,
+    {
+        ensure!(
+            prefix_expected.len() <= self.report_data.len(),
+            AttestationError::ReportDataMismatch
+        );
+        let (prefix, suffix) = self.report_data.split_at(prefix_expected.len());
+        ensure!(
+            ct_eq(prefix, prefix_expected) && suffix_validator(suffix),
+            AttestationError::ReportDataMismatch
+        );
+
+        Ok(())
+    }
+
+    /// First 32 bytes of `report_data`, e.g., the user data hash of ECDSA
+    /// quotes.
+    pub fn report_data_first_half(&self) -> &[u8; 32] {
+        <&[u8; 32]>::try_from(&self.report_data[..32]).unwrap()
+    }
+
+    /// Last 32 bytes of `report_data`, e.g., zeros or a second hash of ECDSA
+    /// quotes.
+    pub fn report_data_second_half(&self) -> &[u8; 32] {
+        <&[u8; 32]>::try_from(&self.report_data[32..]).unwrap()
+    }
+
+    /// Check whether `report_data` equals to `expected` left-aligned and
+    /// padded with zeros, e.g., a 32-byte hash followed by 32 zero bytes.
+    pub fn report_data_matches(&self, expected: &[u8]) -> bool {
+        if expected.len() > self.report_data.len() {
+            return false;
+        }
+        let mut padded = [0u8; 64];
+        padded[..expected.len()].copy_from_slice(expected);
+        ct_eq(&self.report_data, &padded)
+    }
+
+    /// CPUSVN of the platform, which can be compared with a baseline.
+    pub fn sgx_cpu_svn(&self) -> CpuSvn {
+        CpuSvn(self.cpu_svn)
+    }
+
+    /// Attributes of the enclave as typed flags and XFRM.
+    pub fn sgx_attributes(&self) -> SgxAttributes {
+        SgxAttributes::from(&self.attributes)
+    }
+
+    /// MISCSELECT of the enclave as typed flags.
+    pub fn sgx_misc_select(&self) -> MiscSelect {
+        MiscSelect(self.misc_select)
+    }
+
+    /// Verify that the MISCSELECT of the enclave is exactly `expected`, e.g.,
+    /// the value produced by a known-good build. Otherwise,
+    /// `AttestationError::MiscSelectMismatch` is returned.
+    pub fn verify_misc_select(&self, expected: MiscSelect) -> Result<()> {
+        ensure!(
+            self.sgx_misc_select() == expected,
+            AttestationError::MiscSelectMismatch
+        );
+
+        Ok(())
+    }
+
+    /// Check the common "this product, at least this version" policy: the
+    /// product ID of the enclave must equal `prod_id`, and its ISV SVN must be
+    /// greater than or equal to `min_svn`.
+    pub fn satisfies(&self, prod_id: u16, min_svn: u16) -> bool {
+        self.isv_prod_id == prod_id && self.isv_svn >= min_svn
+    }
+
+    /// Verify that the enclave is not in debug mode. Otherwise,
+    /// `AttestationError::NotProductionEnclave` is returned.
+    pub fn verify_not_debug(&self) -> Result<()> {
+        ensure!(
+            !self.sgx_attributes().is_debug(),
+            AttestationError::NotProductionEnclave("debug mode")
+        );
+
+        Ok(())
+    }
+}
+
+/// Attributes of an enclave, i.e., the flags (e.g., DEBUG and MODE64BIT) in the
+/// first 8 bytes and the XFRM in the last 8 bytes of
+/// `SgxEnclaveReport.attributes`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SgxAttributes {
+    /// Flags of the enclave
+    pub flags: u64,
+    /// Enabled XSAVE features (XFRM) of the enclave
+    pub xfrm: u64,
+}
+
+impl SgxAttributes {
+    const INIT: u64 = 0x01;
+    const DEBUG: u64 = 0x02;
+    const MODE64BIT: u64 = 0x04;
+    const PROVISION_KEY: u64 = 0x10;
+    const EINITTOKEN_KEY: u64 = 0x20;
+    const KSS: u64 = 0x80;
+
+    /// The enclave is initialized
+    pub fn is_init(&self) -> bool {
+        self.flags & Self::INIT != 0
+    }
+
+    /// The enclave is in debug mode
+    pub fn is_debug(&self) -> bool {
+        self.flags & Self::DEBUG != 0
+    }
+
+    /// The enclave is in 64-bit mode
+    pub fn is_mode64bit(&self) -> bool {
+        self.flags & Self::MODE64BIT != 0
+    }
+
+    /// The enclave has access to the provisioning key
+    pub fn is_provision_key(&self) -> bool {
+        self.flags & Self::PROVISION_KEY != 0
+    }
+
+    /// The enclave has access to the EINITTOKEN key
+    pub fn is_einittoken_key(&self) -> bool {
+        self.flags & Self::EINITTOKEN_KEY != 0
+    }
+
+    /// Key separation and sharing (KSS) is enabled for the enclave
+    pub fn is_kss(&self) -> bool {
+        self.flags & Self::KSS != 0
+    }
+}
+
+impl From<&[u8; 16]> for SgxAttributes {
+    fn from(attributes: &[u8; 16]) -> Self {
+        let mut flags = [0u8; 8];
+        let mut xfrm = [0u8; 8];
+        flags.copy_from_slice(&attributes[..8]);
+        xfrm.copy_from_slice(&attributes[8..]);
+        Self {
+            flags: u64::from_le_bytes(flags),
+            xfrm: u64::from_le_bytes(xfrm),
+        }
+    }
+}
+
+/// MISCSELECT of an enclave, i.e., the extended information saved into the SSA
+/// frame on asynchronous exits, of which only bit 0 (EXINFO) is defined.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MiscSelect(pub u32);
+
+impl MiscSelect {
+    const EXINFO: u32 = 0x01;
+
+    /// Information on page faults and general protection exceptions (EXINFO)
+    /// is reported in the SSA frame
+    pub fn is_exinfo(&self) -> bool {
+        self.0 & Self::EXINFO != 0
+    }
+
+    /// Whether any of the reserved bits, which must be zero, is set
+    pub fn has_reserved_bits(&self) -> bool {
+        self.0 & !Self::EXINFO != 0
+    }
+}
+
+/// CPU security version number (CPUSVN) of a platform, which consists of 16
+/// SVNs of the TCB components. A CPUSVN meets a baseline if each of its
+/// components is not lower than the baseline component, so that CPUSVNs are
+/// only partially ordered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CpuSvn(pub [u8; 16]);
+
+impl CpuSvn {
+    /// Whether each component is not lower than the one of `baseline`.
+    pub fn meets_minimum(&self, baseline: &CpuSvn) -> bool {
+        self.component_below_minimum(baseline).is_none()
+    }
+
+    /// Index of the first component lower than the one of `baseline`, if any.
+    pub fn component_below_minimum(&self, baseline: &CpuSvn) -> Option<usize> {
+        self.0
+            .iter()
+            .zip(baseline.0.iter())
+            .position(|(svn, minimum)| svn < minimum)
+    }
+
+    /// Verify that the CPUSVN meets `baseline`. Otherwise,
+    /// `AttestationError::CpuSvnBelowMinimum` is returned with the index of the
+    /// first component lower than the baseline.
+    pub fn verify_minimum(&self, baseline: &CpuSvn) -> Result<()> {
+        match self.component_below_minimum(baseline) {
+            Some(component) => bail!(AttestationError::CpuSvnBelowMinimum { component }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Compare two byte slices in constant time (with respect to their contents),
+/// e.g., report data and measurements, so that the position of the first
+/// different byte is not leaked through timing.
+#[cfg(feature = "std")]
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    ring::constant_time::verify_slices_are_equal(a, b).is_ok()
+}
+
+/// Same as above without `ring`, which does not build without `std`.
+#[cfg(not(feature = "std"))]
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Bounds-checked `&buf[offset..offset + len]`.
+pub(crate) fn subslice(buf: &[u8], offset: usize, len: usize) -> Result<&[u8]> {
+    offset
+        .checked_add(len)
+        .and_then(|end| buf.get(offset..end))
+        .ok_or_else(|| anyhow!("Out of bounds: {} bytes at offset {}.", len, offset))
+}
+
+/// Ensure a reserved region starting at `offset` only contains zeros.
+fn ensure_zeroed(offset: usize, region: &[u8]) -> Result<()> {
+    match region.iter().position(|b| *b != 0) {
+        Some(i) => bail!(AttestationError::NonZeroReserved { offset: offset + i }),
+        None => Ok(()),
+    }
+}
+
+/// SGX Quote structure version
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "version", content = "type")]
+pub enum SgxQuoteVersion {
+    /// EPID quote version
+    V1(SgxEpidQuoteSigType),
+    /// EPID quote version
+    V2(SgxEpidQuoteSigType),
+    /// ECDSA quote version
+    V3(SgxEcdsaQuoteAkType),
+}
+
+/// Intel EPID attestation signature type
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub enum SgxEpidQuoteSigType {
+    Unlinkable,
+    Linkable,
+}
+
+/// ECDSA attestation key type
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub enum SgxEcdsaQuoteAkType {
+    /// ECDSA-256-with-P-256 curve
+    P256_256,
+    /// ECDSA-384-with-P-384 curve
+    P384_384,
+}
+
+impl fmt::Display for SgxQuoteVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SgxQuoteVersion::V1(key_type) => write!(f, "Version 1, EPID {:?} signature", key_type),
+            SgxQuoteVersion::V2(key_type) => write!(f, "Version 2, EPID {:?} signature", key_type),
+            SgxQuoteVersion::V3(key_type) => {
+                write!(f, "Version 3, ECDSA {:?} attestation key", key_type)
+            }
+        }
+    }
+}
+
+/// SGX Quote status
+#[derive(Clone, PartialEq, Debug)]
+pub enum SgxQuoteStatus {
+    /// EPID signature of the ISV enclave QUOTE was verified correctly and the
+    /// TCB level of the SGX platform is up-to-date.
+    OK,
+    /// EPID signature of the ISV enclave QUOTE was invalid. The content of the
+    /// QUOTE is not trustworthy.
+    ///
+    /// For DCAP, the signature over the application report is invalid.
+    SignatureInvalid,
+    /// The EPID group has been revoked. When this value is returned, the
+    /// revocation Reason field of the Attestation Verification Report will
+    /// contain revocation reason code for this EPID group as reported in the
+    /// EPID Group CRL. The content of the QUOTE is not trustworthy.
+    GroupRevoked,
+    /// The EPID private key used to sign the QUOTE has been revoked by
+    /// signature. The content of the QUOTE is not trustworthy.
+    SignatureRevoked,
+    /// The EPID private key used to sign the QUOTE has been directly revoked
+    /// (not by signature). The content of the QUOTE is not trustworthy.
+    ///
+    /// For DCAP, the attestation key or platform has been revoked.
+    KeyRevoked,
+    /// SigRL version in ISV enclave QUOTE does not match the most recent
+    /// version of the SigRL. In rare situations, after SP retrieved the SigRL
+    /// from IAS and provided it to the platform, a newer version of the SigRL
+    /// is madeavailable. As a result, the Attestation Verification Report will
+    /// indicate SIGRL_VERSION_MISMATCH. SP can retrieve the most recent version
+    /// of SigRL from the IAS and request the platform to perform remote
+    /// attestation again with the most recent version of SigRL. If the platform
+    /// keeps failing to provide a valid QUOTE matching with the most recent
+    /// version of the SigRL, the content of the QUOTE is not trustworthy.
+    SigrlVersionMismatch,
+    /// The EPID signature of the ISV enclave QUOTE has been verified correctly,
+    /// but the TCB level of SGX platform is outdated (for further details see
+    /// Advisory IDs). The platform has not been identified as compromised and
+    /// thus it is not revoked. It is up to the Service Provider to decide
+    /// whether or not to trust the content of the QUOTE, andwhether or not to
+    /// trust the platform performing the attestation to protect specific
+    /// sensitive information.
+    GroupOutOfDate,
+    /// The EPID signature of the ISV enclave QUOTE has been verified correctly,
+    /// but additional configuration of SGX platform may beneeded(for further
+    /// details see Advisory IDs). The platform has not been identified as
+    /// compromised and thus it is not revoked. It is up to the Service Provider
+    /// to decide whether or not to trust the content of the QUOTE, and whether
+    /// or not to trust the platform performing the attestation to protect
+    /// specific sensitive information.
+    ///
+    /// For DCAP, The Quote verification passed and the platform is patched to
+    /// the latest TCB level but additional configuration of the SGX
+    /// platform may be needed.
+    ConfigurationNeeded,
+    /// The EPID signature of the ISV enclave QUOTE has been verified correctly
+    /// but due to certain issues affecting the platform, additional SW
+    /// Hardening in the attesting SGX enclaves may be needed.The relying party
+    /// should evaluate the potential risk of an attack leveraging the relevant
+    /// issues on the attesting enclave, and whether the attesting enclave
+    /// employs adequate software hardening to mitigate the risk.
+    SwHardeningNeeded,
+    /// The EPID signature of the ISV enclave QUOTE has been verified correctly
+    /// but additional configuration for the platform and SW Hardening in the
+    /// attesting SGX enclaves may be needed. The platform has not been
+    /// identified as compromised and thus it is not revoked. It is up to the
+    /// Service Provider to decide whether or not to trust the content of the
+    /// QUOTE. The relying party should also evaluate the potential risk of an
+    /// attack leveraging the relevant issues on the attestation enclave, and
+    /// whether the attesting enclave employs adequate software hardening to
+    /// mitigate the risk.
+    ConfigurationAndSwHardeningNeeded,
+    /// DCAP specific quote status. The Quote is good but TCB level of the
+    /// platform is out of date. The platform needs patching to be at the latest
+    /// TCB level.
+    OutOfDate,
+    /// DCAP specific quote status. The Quote is good but the TCB level of the
+    /// platform is out of date and additional configuration of the SGX Platform
+    /// at its current patching level may be needed. The platform needs patching
+    /// to be at the latest TCB level.
+    OutOfDateConfigurationNeeded,
+    /// DCAP specific quote status. The signature over the application report is
+    /// invalid.
+    InvalidSignature,
+    /// Other unknown bad status, with the status string as reported by the
+    /// attestation service.
+    UnknownBadStatus(String),
+}
+
+impl From<&str> for SgxQuoteStatus {
+    /// Convert from str status from the report to enum.
+    fn from(status: &str) -> Self {
+        match status {
+            "OK" => SgxQuoteStatus::OK,
+            "SIGNATURE_INVALID" => SgxQuoteStatus::SignatureInvalid,
+            "GROUP_REVOKED" => SgxQuoteStatus::GroupRevoked,
+            "SIGNATURE_REVOKED" => SgxQuoteStatus::SignatureRevoked,
+            "KEY_REVOKED" => SgxQuoteStatus::KeyRevoked,
+            "SIGRL_VERSION_MISMATCH" => SgxQuoteStatus::SigrlVersionMismatch,
+            "GROUP_OUT_OF_DATE" => SgxQuoteStatus::GroupOutOfDate,
+            "OUT_OF_DATE" => SgxQuoteStatus::OutOfDate,
+            "OUT_OF_DATE_CONFIGURATION_NEEDED" => SgxQuoteStatus::OutOfDateConfigurationNeeded,
+            "CONFIGURATION_NEEDED" => SgxQuoteStatus::ConfigurationNeeded,
+            "SW_HARDENING_NEEDED" => SgxQuoteStatus::SwHardeningNeeded,
+            "CONFIGURATION_AND_SW_HARDENING_NEEDED" => {
+                SgxQuoteStatus::ConfigurationAndSwHardeningNeeded
+            }
+            _ => SgxQuoteStatus::UnknownBadStatus(status.to_string()),
+        }
+    }
+}
+
+impl SgxQuoteStatus {
+    /// Status string as reported by the attestation service, i.e., the
+    /// reverse of `From<&str>`, including the original string of an unknown
+    /// status.
+    pub fn as_str(&self) -> &str {
+        match self {
+            SgxQuoteStatus::OK => "OK",
+            SgxQuoteStatus::SignatureInvalid => "SIGNATURE_INVALID",
+            SgxQuoteStatus::GroupRevoked => "GROUP_REVOKED",
+            SgxQuoteStatus::SignatureRevoked => "SIGNATURE_REVOKED",
+            SgxQuoteStatus::KeyRevoked => "KEY_REVOKED",
+            SgxQuoteStatus::SigrlVersionMismatch => "SIGRL_VERSION_MISMATCH",
+            SgxQuoteStatus::GroupOutOfDate => "GROUP_OUT_OF_DATE",
+            SgxQuoteStatus::OutOfDate => "OUT_OF_DATE",
+            SgxQuoteStatus::OutOfDateConfigurationNeeded => "OUT_OF_DATE_CONFIGURATION_NEEDED",
+            SgxQuoteStatus::ConfigurationNeeded => "CONFIGURATION_NEEDED",
+            SgxQuoteStatus::SwHardeningNeeded => "SW_HARDENING_NEEDED",
+            SgxQuoteStatus::ConfigurationAndSwHardeningNeeded => {
+                "CONFIGURATION_AND_SW_HARDENING_NEEDED"
+            }
+            SgxQuoteStatus::InvalidSignature => "INVALID_SIGNATURE",
+            SgxQuoteStatus::UnknownBadStatus(status) => status,
+        }
+    }
+}
+
+impl fmt::Display for SgxQuoteStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An application that hosts an enclave can ask the enclave to produce a report
+/// (`SgxEnclaveReport`) and then pass this report to a platform service
+/// (Quoting Enclave) to produce a type of credential that reflects the enclave
+/// and platform state. The quote can be passed to entities off the platform,
+/// and verified using Intel EPID signature verification techniques.
+#[derive(Serialize, Deserialize)]
+pub struct SgxQuote {
+    /// Version of the quote structure
+    pub version: SgxQuoteVersion,
+    /// ID of the Intel EPID group of the platform belongs to
+    pub gid: u32,
+    /// Security version number of Quoting Enclave
+    pub isv_svn_qe: u16,
+    /// Security version number of PCE
+    pub isv_svn_pce: u16,
+    /// Vendor ID of Quoting Enclave
+    pub qe_vendor_id: Uuid,
+    /// User data. For EPID quotes, it is given by the caller when
+    /// initializing the quote, e.g., a tag or the first 20 bytes of a SHA-1
+    /// hash, see `epid_user_data`. For ECDSA quotes, it is set by the Quoting
+    /// Enclave instead, e.g., the QE ID in the first 16 bytes, and carries no
+    /// meaning of the caller.
+    #[serde(with = "hex_bytes")]
+    pub user_data: [u8; 20],
+    /// Report generated by the enclave
+    pub isv_enclave_report: SgxEnclaveReport,
+    /// Signature data of ECDSA (version 3) quotes, if the signature data
+    /// follows the quote body, e.g., quotes generated by the QE of DCAP rather
+    /// than `isvEnclaveQuoteBody` of an attestation report
+    pub ecdsa_sig: Option<SgxEcdsaSignatureData>,
+    /// Bytes the quote is parsed from
+    #[serde(skip)]
+    raw: Option<Vec<u8>>,
+}
+
+impl fmt::Debug for SgxQuote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "version: {:?}", self.version)?;
+        writeln!(f, "gid: {}", self.gid)?;
+        writeln!(f, "isv_svn_qe: {}", self.isv_svn_qe)?;
+        writeln!(f, "isv_svn_pce: {}", self.isv_svn_pce)?;
+        writeln!(f, "qe_vendor_id: {}", self.qe_vendor_id)?;
+        writeln!(f, "user_data: {}", hex::encode(self.user_data))?;
+        writeln!(f, "isv_enclave_report: \n{:?}", self.isv_enclave_report)?;
+        write!(f, "ecdsa_sig: {:?}", self.ecdsa_sig)
+    }
+}
+
+impl fmt::Display for SgxQuote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Version and signature/key type: {}", self.version)?;
+        writeln!(f, "GID or reserved: {}", self.gid)?;
+        writeln!(f, "Security version of the QE: {}", self.isv_svn_qe)?;
+        writeln!(f, "Security version of the PCE: {}", self.isv_svn_pce)?;
+        writeln!(f, "ID of the QE vendor: {}", self.qe_vendor_id)?;
+        writeln!(
+            f,
+            "Custom user-defined data (hex): {}",
+            hex::encode(&self.user_data)
+        )?;
+        write!(f, "{}", self.isv_enclave_report)
+    }
+}
+
+/// User data of an EPID quote, i.e., the 20 bytes given by the caller when
+/// initializing the quote, e.g., to correlate quotes with a tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UserData(pub [u8; 20]);
+
+impl UserData {
+    /// Lowercase hex encoding of the user data.
+    pub fn as_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    /// Whether the user data equals to the `expected` tag, compared in
+    /// constant time.
+    pub fn matches(&self, expected: &[u8; 20]) -> bool {
+        ct_eq(&self.0, expected)
+    }
+}
+
+impl fmt::Display for UserData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.as_hex())
+    }
+}
+
+/// Quote versions which can be parsed by `SgxQuote::parse_from`.
+pub const SUPPORTED_QUOTE_VERSIONS: &[u16] = &[1, 2, 3];
+
+/// Vendor ID of the Intel Quoting Enclave in ECDSA (version 3) quotes, i.e.,
+/// `939a7233-f79c-4ca9-940a-0db3957f0607`.
+pub const INTEL_QE_VENDOR_ID: Uuid = Uuid::from_bytes([
+    0x93, 0x9a, 0x72, 0x33, 0xf7, 0x9c, 0x4c, 0xa9, 0x94, 0x0a, 0x0d, 0xb3, 0x95, 0x7f, 0x06, 0x07,
+]);
+
+/// Offset of the enclave report within a quote.
+pub(crate) const SGX_QUOTE_REPORT_BODY_OFFSET: usize = 48;
+/// Size of the enclave report within a quote.
+pub(crate) const SGX_QUOTE_REPORT_BODY_SIZE: usize = 384;
+/// Reserved regions of the enclave report as `(offset, size)`, where offsets
+/// are relative to the start of the report, i.e., quote offset minus 48.
+pub(crate) const SGX_REPORT_RESERVED_REGIONS: [(usize, usize); 4] =
+    [(20, 12), (96, 32), (160, 32), (262, 42)];
+/// Size of the quote body, i.e., of a whole EPID quote, which is followed by
+/// the signature data in ECDSA quotes.
+pub(crate) const SGX_QUOTE_BODY_SIZE: usize =
+    SGX_QUOTE_REPORT_BODY_OFFSET + SGX_QUOTE_REPORT_BODY_SIZE;
+/// Version of SGX ECDSA quotes supported by `QeCertificationData`.
+pub(crate) const SGX_ECDSA_QUOTE_VERSION: u16 = 3;
+
+impl SgxQuote {
+    /// Peek the version field of a quote of any version, e.g., to dispatch
+    /// version 4 and 5 (TDX) quotes, which are not parsed by `SgxQuote`.
+    pub fn peek_version(bytes: &[u8]) -> Result<u16> {
+        ensure!(bytes.len() >= 2, AttestationError::QuoteTruncated);
+
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// Peek the version of a quote and check that it is supported before any
+    /// further parsing or signature verification, so that unsupported quotes
+    /// are rejected early.
+    pub fn supported_quote_version(bytes: &[u8]) -> Result<u16> {
+        let version = Self::peek_version(bytes)?;
+        ensure!(
+            SUPPORTED_QUOTE_VERSIONS.contains(&version),
+            AttestationError::UnsupportedQuoteVersion(version)
+        );
+
+        Ok(version)
+    }
+
+    /// Quote of an enclave report which is not accompanied by the quote
+    /// header, e.g., assembled from the claims of an attestation token. The
+    /// header fields are zero and the version is an ECDSA quote without
+    /// signature data.
+    #[cfg(feature = "std")]
+    pub(crate) fn from_enclave_report(isv_enclave_report: SgxEnclaveReport) -> Self {
+        Self {
+            version: SgxQuoteVersion::V3(SgxEcdsaQuoteAkType::P256_256),
+            gid: 0,
+            isv_svn_qe: 0,
+            isv_svn_pce: 0,
+            qe_vendor_id: Uuid::nil(),
+            user_data: [0u8; 20],
+            isv_enclave_report,
+            ecdsa_sig: None,
+            raw: None,
+        }
+    }
+
+    /// Parse a quote of `len` bytes at `offset` of `buf`, e.g., from a large
+    /// memory-mapped archive of quotes, without copying it.
+    pub fn parse_at(buf: &[u8], offset: usize, len: usize) -> Result<Self> {
+        Self::parse_from(subslice(buf, offset, len)?)
+    }
+
+    /// Parse from bytes to `SgxQuote`. The size of the bytes is checked
+    /// first, so that a truncated or padded quote, e.g., a corrupted
+    /// `isvEnclaveQuoteBody`, is reported with the expected size.
+    pub fn parse_from(bytes: &[u8]) -> Result<Self> {
+        Self::parse(bytes, false)
+    }
+
+    /// Same as `parse_from`, but also reject quotes which are not consistent,
+    /// see `validate_consistency`.
+    pub fn parse_from_strict(bytes: &[u8]) -> Result<Self> {
+        Self::parse(bytes, true)
+    }
+
+    fn parse(bytes: &[u8], strict: bool) -> Result<Self> {
+        // Quotes of unsupported versions are reported with the version rather
+        // than a size mismatch, whatever their size is.
+        if Self::peek_version(bytes).is_ok() {
+            Self::supported_quote_version(bytes)?;
+        }
+        Self::verify_size(bytes)?;
+        let (quote, len) = Self::parse_prefix(bytes)?;
+        ensure!(len == bytes.len(), AttestationError::QuoteMalformed);
+        if strict {
+            quote.validate_consistency()?;
+        }
+
+        Ok(quote)
+    }
+
+    /// Validate the invariants between the fields of the quote which parsing
+    /// does not check:
+    ///
+    /// - reserved fields of the enclave report (and the QE report of ECDSA
+    ///   quotes) are zero, if the quote is parsed from bytes,
+    /// - EPID quotes consist of the body only,
+    /// - the QE vendor ID of ECDSA quotes is not nil.
+    pub fn validate_consistency(&self) -> Result<()> {
+        match self.version {
+            SgxQuoteVersion::V1(_) | SgxQuoteVersion::V2(_) => {
+                ensure!(self.ecdsa_sig.is_none(), AttestationError::QuoteMalformed);
+            }
+            SgxQuoteVersion::V3(_) => {
+                ensure!(
+                    !self.qe_vendor_id.is_nil(),
+                    AttestationError::QuoteMalformed
+                );
+            }
+        }
+        if let Some(raw) = self.raw() {
+            let report_body = subslice(
+                raw,
+                SGX_QUOTE_REPORT_BODY_OFFSET,
+                SGX_QUOTE_REPORT_BODY_SIZE,
+            )?;
+            SgxEnclaveReport::parse_from_strict(report_body)?;
+        }
+        if let Some(ecdsa_sig) = &self.ecdsa_sig {
+            SgxEnclaveReport::parse_from_strict(&ecdsa_sig.qe_report_raw)?;
+        }
+
+        Ok(())
+    }
+
+    /// Check the size of a quote: EPID quotes consist of the
+    /// `SGX_QUOTE_BODY_SIZE` bytes body only, and ECDSA quotes are at least
+    /// as large as the body.
+    fn verify_size(bytes: &[u8]) -> Result<()> {
+        let actual = bytes.len();
+        let is_epid = actual >= 2 && {
+            let version = u16::from_le_bytes([bytes[0], bytes[1]]);
+            version == 1 || version == 2
+        };
+        ensure!(
+            actual == SGX_QUOTE_BODY_SIZE || (actual > SGX_QUOTE_BODY_SIZE && !is_epid),
+            AttestationError::QuoteSizeMismatch {
+                expected: SGX_QUOTE_BODY_SIZE,
+                actual,
+            }
+        );
+
+        Ok(())
+    }
+
+    /// Parse a quote at the start of `bytes`, e.g., followed by collateral,
+    /// and return it with the number of bytes consumed. Bytes following the
+    /// body of an ECDSA (version 3) quote are parsed as its signature data,
+    /// and EPID quotes consist of the body only.
+    pub fn parse_prefix<'a>(bytes: &'a [u8]) -> Result<(Self, usize)> {
+        let mut pos: usize = 0;
+        let mut take = |n: usize| -> Result<&'a [u8]> {
+            let end = pos
+                .checked_add(n)
+                .ok_or_else(|| Error::new(AttestationError::QuoteTruncated))?;
+            if n > 0 && bytes.len() >= end {
+                let ret = &bytes[pos..end];
+                pos = end;
+                Ok(ret)
+            } else {
+                bail!(AttestationError::QuoteTruncated)
+            }
+        };
+
+        // Parse by bytes according to specifications.
+        // off 0, size 2 + 2
+        let version = match u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?) {
+            1 => {
+                let signature_type = match u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?) {
+                    0 => SgxEpidQuoteSigType::Unlinkable,
+                    1 => SgxEpidQuoteSigType::Linkable,
+                    _ => bail!(AttestationError::QuoteMalformed),
+                };
+                SgxQuoteVersion::V1(signature_type)
+            }
+            2 => {
+                let signature_type = match u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?) {
+                    0 => SgxEpidQuoteSigType::Unlinkable,
+                    1 => SgxEpidQuoteSigType::Linkable,
+                    _ => bail!(AttestationError::QuoteMalformed),
+                };
+                SgxQuoteVersion::V2(signature_type)
+            }
+            3 => {
+                let attestation_key_type = match u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?)
+                {
+                    2 => SgxEcdsaQuoteAkType::P256_256,
+                    3 => SgxEcdsaQuoteAkType::P384_384,
+                    _ => bail!(AttestationError::QuoteMalformed),
+                };
+                SgxQuoteVersion::V3(attestation_key_type)
+            }
+            version => bail!(AttestationError::UnsupportedQuoteVersion(version)),
+        };
+
+        // off 4, size 4
+        let gid = u32::from_le_bytes(<[u8; 4]>::try_from(take(4)?)?);
+
+        // off 8, size 2
+        let isv_svn_qe = u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?);
+
+        // off 10, size 2
+        let isv_svn_pce = u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?);
+
+        // off 12, size 16
+        let qe_vendor_id = Uuid::from_bytes(<[u8; 16]>::try_from(take(16)?)?);
+
+        // off 28, size 20
+        let user_data = <[u8; 20]>::try_from(take(20)?)?;
+
+        // off 48, size 384
+        let isv_enclave_report = SgxEnclaveReport::parse_from(take(SGX_QUOTE_REPORT_BODY_SIZE)?)?;
+
+        // off 432, signature data of ECDSA quotes
+        let ecdsa_sig = match version {
+            SgxQuoteVersion::V3(_) if pos < bytes.len() => {
+                let (ecdsa_sig, len) = SgxEcdsaSignatureData::parse_prefix(&bytes[pos..])?;
+                pos += len;
+                Some(ecdsa_sig)
+            }
+            _ => None,
+        };
+
+        let quote = Self {
+            version,
+            gid,
+            isv_svn_qe,
+            isv_svn_pce,
+            qe_vendor_id,
+            user_data,
+            isv_enclave_report,
+            ecdsa_sig,
+            raw: Some(bytes[..pos].to_vec()),
+        };
+
+        Ok((quote, pos))
+    }
+
+    /// Verify that an ECDSA (version 3) quote is generated by the Intel
+    /// Quoting Enclave, i.e., its vendor ID is `INTEL_QE_VENDOR_ID`.
+    pub fn verify_qe_vendor(&self) -> Result<()> {
+        self.verify_qe_vendor_id(&INTEL_QE_VENDOR_ID)
+    }
+
+    /// Verify that an ECDSA (version 3) quote is generated by the Quoting
+    /// Enclave of `expected`, e.g., a third-party QE. EPID quotes have no QE
+    /// vendor ID (the field is the extended EPID group ID and basename), so
+    /// they are always accepted.
+    pub fn verify_qe_vendor_id(&self, expected: &Uuid) -> Result<()> {
+        if let SgxQuoteVersion::V3(_) = self.version {
+            ensure!(
+                &self.qe_vendor_id == expected,
+                AttestationError::QeVendorIdMismatch
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Signature type of an EPID (version 1 and 2) quote, or `None` for ECDSA
+    /// quotes.
+    pub fn epid_sig_type(&self) -> Option<&SgxEpidQuoteSigType> {
+        match &self.version {
+            SgxQuoteVersion::V1(sig_type) | SgxQuoteVersion::V2(sig_type) => Some(sig_type),
+            SgxQuoteVersion::V3(_) => None,
+        }
+    }
+
+    /// Whether an EPID quote is linkable, e.g., to reject linkable quotes in
+    /// privacy-sensitive deployments, or `None` for ECDSA quotes.
+    pub fn is_linkable(&self) -> Option<bool> {
+        self.epid_sig_type()
+            .map(|sig_type| *sig_type == SgxEpidQuoteSigType::Linkable)
+    }
+
+    /// User data of an EPID quote, or `None` for ECDSA quotes, of which the
+    /// field is not given by the caller.
+    pub fn epid_user_data(&self) -> Option<UserData> {
+        self.epid_sig_type().map(|_| UserData(self.user_data))
+    }
+
+    /// Bytes the quote is parsed from, or `None` if the quote is not parsed
+    /// from bytes, e.g., deserialized.
+    pub fn raw(&self) -> Option<&[u8]> {
+        self.raw.as_deref()
+    }
+
+    /// Byte range of `isv_enclave_report` within the quote, e.g., to hash
+    /// the report body from `raw()` for a custom signature check.
+    pub fn report_body_range(&self) -> Range<usize> {
+        SGX_QUOTE_REPORT_BODY_OFFSET..SGX_QUOTE_REPORT_BODY_OFFSET + SGX_QUOTE_REPORT_BODY_SIZE
+    }
+
+    /// Convert the quote into JSON for generic inspection tools. The layout
+    /// follows `SgxEnclaveReport::to_json`, and the signature type of EPID
+    /// quotes (or attestation key type of ECDSA quotes) is given by its name:
+    ///
+    /// ```json
+    /// {
+    ///   "version": 2,
+    ///   "signature_type": "Linkable",
+    ///   "gid": 2863,
+    ///   "isv_svn_qe": 10,
+    ///   "isv_svn_pce": 9,
+    ///   "qe_vendor_id": "00000000-ad73-4503-88a6-77623f822196",
+    ///   "user_data": "<hex>",
+    ///   "isv_enclave_report": { ... }
+    /// }
+    /// ```
+    ///
+    /// For version 3 quotes, `signature_type` is replaced by
+    /// `attestation_key_type`.
+    #[cfg(feature = "std")]
+    pub fn to_json(&self) -> Value {
+        let mut value = json!({
+            "gid": self.gid,
+            "isv_svn_qe": self.isv_svn_qe,
+            "isv_svn_pce": self.isv_svn_pce,
+            "qe_vendor_id": self.qe_vendor_id.to_string(),
+            "user_data": hex::encode(self.user_data),
+            "isv_enclave_report": self.isv_enclave_report.to_json(),
+        });
+        let (version, type_key, type_name) = match &self.version {
+            SgxQuoteVersion::V1(sig_type) => (1, "signature_type", format!("{:?}", sig_type)),
+            SgxQuoteVersion::V2(sig_type) => (2, "signature_type", format!("{:?}", sig_type)),
+            SgxQuoteVersion::V3(ak_type) => (3, "attestation_key_type", format!("{:?}", ak_type)),
+        };
+        value["version"] = json!(version);
+        value[type_key] = json!(type_name);
+
+        value
+    }
+}
+
+/// Type of the certification data of the Quoting Enclave (QE) in an ECDSA
+/// quote, i.e., the data to certify the PCK signing the QE report.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CertificationDataType {
+    /// PPID in clear text, with CPUSVN, PCESVN and PCE-ID
+    PpidCleartext,
+    /// PPID encrypted with RSA-2048-OAEP, with CPUSVN, PCESVN and PCE-ID
+    PpidRsa2048Oaep,
+    /// PPID encrypted with RSA-3072-OAEP, with CPUSVN, PCESVN and PCE-ID
+    PpidRsa3072Oaep,
+    /// PCK certificate in clear text
+    PckCertCleartext,
+    /// PCK certificate chain in PEM
+    PckCertChain,
+    /// QE report certification data, as in TDX quotes
+    QeReportCertificationData,
+    /// Platform manifest
+    PlatformManifest,
+}
+
+impl CertificationDataType {
+    fn from_u16(cert_data_type: u16) -> Result<Self> {
+        use CertificationDataType::*;
+        let cert_data_type = match cert_data_type {
+            1 => PpidCleartext,
+            2 => PpidRsa2048Oaep,
+            3 => PpidRsa3072Oaep,
+            4 => PckCertCleartext,
+            5 => PckCertChain,
+            6 => QeReportCertificationData,
+            7 => PlatformManifest,
+            _ => bail!("Unknown certification data type {}.", cert_data_type),
+        };
+
+        Ok(cert_data_type)
+    }
+}
+
+/// Certification data of the Quoting Enclave (QE) at the end of the signature
+/// data of an SGX ECDSA quote.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QeCertificationData {
+    cert_data_type: CertificationDataType,
+    #[serde(with = "hex_bytes")]
+    cert_data: Vec<u8>,
+}
+
+/// Signature data of an SGX ECDSA quote (version 3) following the quote body.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SgxEcdsaSignatureData {
+    /// ECDSA P-256 signature (r || s) of the quote body
+    #[serde(with = "hex_bytes")]
+    pub signature: [u8; 64],
+    /// ECDSA P-256 public key (x || y) of the attestation key
+    #[serde(with = "hex_bytes")]
+    pub attestation_key: [u8; 64],
+    /// Report of the QE, which binds the attestation key
+    pub qe_report: SgxEnclaveReport,
+    /// ECDSA P-256 signature (r || s) of the QE report by the PCK
+    #[serde(with = "hex_bytes")]
+    pub qe_report_signature: [u8; 64],
+    /// Authentication data of the QE
+    #[serde(with = "hex_bytes")]
+    pub qe_auth_data: Vec<u8>,
+    /// Certification data of the QE
+    pub qe_cert_data: QeCertificationData,
+    /// Raw bytes of the QE report, signed by `qe_report_signature`
+    #[serde(with = "hex_bytes")]
+    pub(crate) qe_report_raw: Vec<u8>,
+}
+
+impl SgxEcdsaSignatureData {
+    /// Parse the signature data (starting from its length field) following
+    /// the body of an SGX ECDSA quote.
+    pub fn parse_from(bytes: &[u8]) -> Result<Self> {
+        let (signature_data, len) = Self::parse_prefix(bytes)?;
+        ensure!(len == bytes.len(), AttestationError::QuoteMalformed);
+
+        Ok(signature_data)
+    }
+
+    /// Parse the signature data at the start of `bytes`, which may be followed
+    /// by other data, and return it with the number of bytes consumed, i.e.,
+    /// the length field and the signature data of that length.
+    pub fn parse_prefix(bytes: &[u8]) -> Result<(Self, usize)> {
+        ensure!(bytes.len() >= 4, AttestationError::QuoteTruncated);
+        let sig_data_len = u32::from_le_bytes(<[u8; 4]>::try_from(&bytes[..4])?) as usize;
+        ensure!(
+            bytes.len() - 4 >= sig_data_len,
+            AttestationError::QuoteTruncated
+        );
+        let bytes = &bytes[..4 + sig_data_len];
+
+        let mut pos: usize = 0;
+        let mut take = |n: usize| -> Result<&[u8]> {
+            let end = pos
+                .checked_add(n)
+                .ok_or_else(|| Error::new(AttestationError::QuoteTruncated))?;
+            if bytes.len() >= end {
+                let ret = &bytes[pos..end];
+                pos = end;
+                Ok(ret)
+            } else {
+                Err(Error::new(AttestationError::QuoteTruncated))
+            }
+        };
+
+        // off 432, size 4
+        take(4)?;
+
+        // off 436, size 64 + 64 + 384 + 64: ECDSA signature, attestation key,
+        // QE report and its signature
+        let signature = <[u8; 64]>::try_from(take(64)?)?;
+        let attestation_key = <[u8; 64]>::try_from(take(64)?)?;
+        let qe_report_raw = take(SGX_QUOTE_REPORT_BODY_SIZE)?.to_vec();
+        let qe_report_signature = <[u8; 64]>::try_from(take(64)?)?;
+
+        // off 1012, size 2 + qe_auth_data_len
+        let qe_auth_data_len = u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?) as usize;
+        let qe_auth_data = take(qe_auth_data_len)?.to_vec();
+
+        // size 2 + 4 + cert_data_len
+        let cert_data_type = u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?);
+        let cert_data_type = CertificationDataType::from_u16(cert_data_type)?;
+        let cert_data_len = u32::from_le_bytes(<[u8; 4]>::try_from(take(4)?)?) as usize;
+        let cert_data = take(cert_data_len)?.to_vec();
+
+        ensure!(pos == bytes.len(), AttestationError::QuoteMalformed);
+
+        let signature_data = Self {
+            signature,
+            attestation_key,
+            qe_report: SgxEnclaveReport::parse_from(&qe_report_raw)?,
+            qe_report_signature,
+            qe_auth_data,
+            qe_cert_data: QeCertificationData {
+                cert_data_type,
+                cert_data,
+            },
+            qe_report_raw,
+        };
+
+        Ok((signature_data, pos))
+    }
+}
+
+impl QeCertificationData {
+    /// Parse the QE certification data from an SGX ECDSA quote (version 3).
+    pub fn parse_from_sgx_quote(quote: &[u8]) -> Result<Self> {
+        ensure!(
+            quote.len() > SGX_QUOTE_BODY_SIZE,
+            AttestationError::QuoteTruncated
+        );
+        let (body, signature_data) = quote.split_at(SGX_QUOTE_BODY_SIZE);
+        let version = u16::from_le_bytes(<[u8; 2]>::try_from(&body[0..2])?);
+        ensure!(
+            version == SGX_ECDSA_QUOTE_VERSION,
+            AttestationError::UnsupportedQuoteVersion(version)
+        );
+
+        Ok(SgxEcdsaSignatureData::parse_from(signature_data)?.qe_cert_data)
+    }
+
+    /// Type of the certification data.
+    pub fn cert_data_type(&self) -> CertificationDataType {
+        self.cert_data_type
+    }
+
+    /// Raw certification data, whose format depends on the type.
+    pub fn cert_data(&self) -> &[u8] {
+        &self.cert_data
+    }
+
+    /// PCK certificate chain in PEM, if the certification data is of type
+    /// `PckCertChain`.
+    pub fn pck_cert_chain(&self) -> Option<&[u8]> {
+        match self.cert_data_type {
+            CertificationDataType::PckCertChain => Some(&self.cert_data),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(all(feature = "enclave_unit_test", feature = "mesalock_sgx"))]
+pub mod tests {
+    use super::*;
+
+    pub fn attesation_report() -> Value {
+        let report = json!({
+            "version": 3,
+            "timestamp": "2020-02-11T22:25:59.682915",
+            "platformInfoBlob": "1502006504000900000D0D02040180030000000000000000000\
+                                 A00000B000000020000000000000B2FE0AE0F7FD4D552BF7EF4\
+                                 C938D44E349F1BD0E76F041362DC52B43B7B25994978D792137\
+                                 90362F6DAE91797ACF5BD5072E45F9A60795D1FFB10140421D8\
+                                 691FFD",
+            "isvEnclaveQuoteStatus": "GROUP_OUT_OF_DATE",
+            "isvEnclaveQuoteBody": "AgABAC8LAAAKAAkAAAAAAK1zRQOIpndiP4IhlnW2AkwAAAAA\
+                                    AAAAAAAAAAAAAAAABQ4CBf+AAAAAAAAAAAAAAAAAAAAAAAAA\
+                                    AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABwAAAAAAAAAHAAAA\
+                                    AAAAADMKqRCjd2eA4gAmrj2sB68OWpMfhPH4MH27hZAvWGlT\
+                                    AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAACD1xnn\
+                                    ferKFHD2uvYqTXdDA8iZ22kCD5xw7h38CMfOngAAAAAAAAAA\
+                                    AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA\
+                                    AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA\
+                                    AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA\
+                                    AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA\
+                                    AAAAAAAAAADYIY9k0MVmCdIDUuFLf/2bGIHAfPjO9nvC7fgz\
+                                    rQedeA3WW4dFeI6oe+RCLdV3XYD1n6lEZjITOzPPLWDxulGz",
+            "id": "53530608302195762335736519878284384788",
+            "epidPseudonym": "NRksaQej8R/SyyHpZXzQGNBXqfrzPy5KCxcmJrEjupXrq3xrm2y2+J\
+                              p0IBVtcW15MCekYs9K3UH82fPyj6F5ciJoMsgEMEIvRR+csX9uyd54\
+                              p+m+/RVyuGYhWbhUcpJigdI5Q3x04GG/A7EP10j/zypwqhYLQh0qN1\
+                              ykYt1N1P0="
+        });
+
+        report
+    }
+
+    /// Builder of the bytes of an `SgxEnclaveReport` in tests, e.g., to feed
+    /// the parsers without laying out the offsets by hand. All fields are zero
+    /// unless set.
+    #[derive(Clone)]
+    pub struct SgxEnclaveReportBuilder {
+        bytes: Vec<u8>,
+    }
+
+    impl Default for SgxEnclaveReportBuilder {
+        fn default() -> Self {
+            Self {
+                bytes: vec![0u8; SGX_QUOTE_REPORT_BODY_SIZE],
+            }
+        }
+    }
+
+    impl SgxEnclaveReportBuilder {
+        // Offsets are relative to the start of the report, i.e., quote offset
+        // minus 48.
+        fn set(mut self, offset: usize, value: &[u8]) -> Self {
+            self.bytes[offset..offset + value.len()].copy_from_slice(value);
+            self
+        }
+
+        pub fn cpu_svn(self, cpu_svn: [u8; 16]) -> Self {
+            self.set(0, &cpu_svn)
+        }
+
+        pub fn misc_select(self, misc_select: u32) -> Self {
+            self.set(16, &misc_select.to_le_bytes())
+        }
+
+        pub fn isv_ext_prod_id(self, isv_ext_prod_id: [u8; 16]) -> Self {
+            self.set(32, &isv_ext_prod_id)
+        }
+
+        pub fn attributes(self, attributes: [u8; 16]) -> Self {
+            self.set(48, &attributes)
+        }
+
+        pub fn mr_enclave(self, mr_enclave: [u8; 32]) -> Self {
+            self.set(64, &mr_enclave)
+        }
+
+        pub fn mr_signer(self, mr_signer: [u8; 32]) -> Self {
+            self.set(128, &mr_signer)
+        }
+
+        pub fn config_id(self, config_id: [u8; 64]) -> Self {
+            self.set(192, &config_id)
+        }
+
+        pub fn isv_prod_id(self, isv_prod_id: u16) -> Self {
+            self.set(256, &isv_prod_id.to_le_bytes())
+        }
+
+        pub fn isv_svn(self, isv_svn: u16) -> Self {
+            self.set(258, &isv_svn.to_le_bytes())
+        }
+
+        pub fn config_svn(self, config_svn: u16) -> Self {
+            self.set(260, &config_svn.to_le_bytes())
+        }
+
+        pub fn isv_family_id(self, isv_family_id: [u8; 16]) -> Self {
+            self.set(304, &isv_family_id)
+        }
+
+        pub fn report_data(self, report_data: [u8; 64]) -> Self {
+            self.set(320, &report_data)
+        }
+
+        pub fn to_bytes(&self) -> Vec<u8> {
+            self.bytes.clone()
+        }
+    }
+
+    /// Builder of the bytes of an `SgxQuote` in tests. The signature data,
+    /// if any, is appended to the body as is.
+    #[derive(Clone)]
+    pub struct SgxQuoteBuilder {
+        header: Vec<u8>,
+        isv_enclave_report: SgxEnclaveReportBuilder,
+        signature_data: Vec<u8>,
+    }
+
+    impl SgxQuoteBuilder {
+        pub fn new(version: SgxQuoteVersion) -> Self {
+            let (version, key_type) = match version {
+                SgxQuoteVersion::V1(sig_type) => (1u16, sig_type as u16),
+                SgxQuoteVersion::V2(sig_type) => (2, sig_type as u16),
+                SgxQuoteVersion::V3(SgxEcdsaQuoteAkType::P256_256) => (3, 2),
+                SgxQuoteVersion::V3(SgxEcdsaQuoteAkType::P384_384) => (3, 3),
+            };
+            Self {
+                header: vec![0u8; SGX_QUOTE_REPORT_BODY_OFFSET],
+                isv_enclave_report: SgxEnclaveReportBuilder::default(),
+                signature_data: Vec::new(),
+            }
+            .set(0, &version.to_le_bytes())
+            .set(2, &key_type.to_le_bytes())
+        }
+
+        fn set(mut self, offset: usize, value: &[u8]) -> Self {
+            self.header[offset..offset + value.len()].copy_from_slice(value);
+            self
+        }
+
+        pub fn gid(self, gid: u32) -> Self {
+            self.set(4, &gid.to_le_bytes())
+        }
+
+        pub fn isv_svn_qe(self, isv_svn_qe: u16) -> Self {
+            self.set(8, &isv_svn_qe.to_le_bytes())
+        }
+
+        pub fn isv_svn_pce(self, isv_svn_pce: u16) -> Self {
+            self.set(10, &isv_svn_pce.to_le_bytes())
+        }
+
+        pub fn qe_vendor_id(self, qe_vendor_id: Uuid) -> Self {
+            self.set(12, qe_vendor_id.as_bytes())
+        }
+
+        pub fn user_data(self, user_data: [u8; 20]) -> Self {
+            self.set(28, &user_data)
+        }
+
+        pub fn isv_enclave_report(mut self, isv_enclave_report: SgxEnclaveReportBuilder) -> Self {
+            self.isv_enclave_report = isv_enclave_report;
+            self
+        }
+
+        pub fn signature_data(mut self, signature_data: Vec<u8>) -> Self {
+            self.signature_data = signature_data;
+            self
+        }
+
+        pub fn to_bytes(&self) -> Vec<u8> {
+            let mut bytes = self.header.clone();
+            bytes.extend_from_slice(&self.isv_enclave_report.to_bytes());
+            bytes.extend_from_slice(&self.signature_data);
+            bytes
+        }
+    }
+
+    pub fn test_sgx_quote_serde() {
+        let attn_report = attesation_report();
+        let sgx_quote_body_encoded = attn_report["isvEnclaveQuoteBody"].as_str().unwrap();
+        let quote_raw = base64::decode(&sgx_quote_body_encoded.as_bytes()).unwrap();
+        let sgx_quote = SgxQuote::parse_from(quote_raw.as_slice()).unwrap();
+
+        let serialized = serde_json::to_value(&sgx_quote).unwrap();
+        assert_eq!(serialized["version"]["version"], "V2");
+        assert_eq!(serialized["version"]["type"], "Linkable");
+        assert_eq!(
+            serialized["isv_enclave_report"]["mr_enclave"],
+            hex::encode(&sgx_quote.isv_enclave_report.mr_enclave)
+        );
+
+        let deserialized: SgxQuote = serde_json::from_value(serialized.clone()).unwrap();
+        assert!(deserialized.raw().is_none());
+        assert_eq!(deserialized.to_json(), sgx_quote.to_json());
+        assert_eq!(serde_json::to_value(&deserialized).unwrap(), serialized);
+
+        let mut truncated = serialized;
+        truncated["user_data"] = Value::String("00".to_string());
+        assert!(serde_json::from_value::<SgxQuote>(truncated).is_err());
+    }
+
+    pub fn test_sgx_quote_parse_from() {
+        let attn_report = attesation_report();
+        let sgx_quote_body_encoded = attn_report["isvEnclaveQuoteBody"].as_str().unwrap();
+        let quote_raw = base64::decode(&sgx_quote_body_encoded.as_bytes()).unwrap();
+        let sgx_quote = SgxQuote::parse_from(quote_raw.as_slice()).unwrap();
+
+        assert_eq!(
+            sgx_quote.version,
+            SgxQuoteVersion::V2(SgxEpidQuoteSigType::Linkable)
+        );
+        assert_eq!(sgx_quote.gid, 2863);
+        assert_eq!(sgx_quote.isv_svn_qe, 10);
+        assert_eq!(sgx_quote.isv_svn_pce, 9);
+        assert_eq!(
+            sgx_quote.qe_vendor_id,
+            Uuid::parse_str("00000000-ad73-4503-88a6-77623f822196").unwrap()
+        );
+        assert_eq!(
+            sgx_quote.user_data,
+            [117, 182, 2, 76, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+
+        let isv_enclave_report = sgx_quote.isv_enclave_report;
+        assert_eq!(
+            isv_enclave_report.cpu_svn,
+            [5, 14, 2, 5, 255, 128, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(isv_enclave_report.misc_select, 0);
+        assert_eq!(
+            isv_enclave_report.attributes,
+            [7, 0, 0, 0, 0, 0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            isv_enclave_report.mr_enclave,
+            [
+                51, 10, 169, 16, 163, 119, 103, 128, 226, 0, 38, 174, 61, 172, 7, 175, 14, 90, 147,
+                31, 132, 241, 248, 48, 125, 187, 133, 144, 47, 88, 105, 83
+            ]
+        );
+        assert_eq!(
+            isv_enclave_report.mr_signer,
+            [
+                131, 215, 25, 231, 125, 234, 202, 20, 112, 246, 186, 246, 42, 77, 119, 67, 3, 200,
+                153, 219, 105, 2, 15, 156, 112, 238, 29, 252, 8, 199, 206, 158
+            ]
+        );
+        assert_eq!(isv_enclave_report.isv_prod_id, 0);
+        assert_eq!(isv_enclave_report.isv_svn, 0);
+        assert_eq!(
+            isv_enclave_report.report_data.to_vec(),
+            [
+                216, 33, 143, 100, 208, 197, 102, 9, 210, 3, 82, 225, 75, 127, 253, 155, 24, 129,
+                192, 124, 248, 206, 246, 123, 194, 237, 248, 51, 173, 7, 157, 120, 13, 214, 91,
+                135, 69, 120, 142, 168, 123, 228, 66, 45, 213, 119, 93, 128, 245, 159, 169, 68,
+                102, 50, 19, 59, 51, 207, 45, 96, 241, 186, 81, 179
+            ]
+            .to_vec()
+        );
+    }
+
+    pub fn test_sgx_quote_supported_quote_version() {
+        let attn_report = attesation_report();
+        let sgx_quote_body_encoded = attn_report["isvEnclaveQuoteBody"].as_str().unwrap();
+        let quote_raw = base64::decode(sgx_quote_body_encoded).unwrap();
+        assert_eq!(SgxQuote::supported_quote_version(&quote_raw).unwrap(), 2);
+        assert!(SgxQuote::supported_quote_version(&[4, 0]).is_err());
+        assert!(SgxQuote::supported_quote_version(&[2]).is_err());
+    }
+
+    pub fn test_sgx_quote_parse_at() {
+        let attn_report = attesation_report();
+        let sgx_quote_body_encoded = attn_report["isvEnclaveQuoteBody"].as_str().unwrap();
+        let quote_raw = base64::decode(sgx_quote_body_encoded).unwrap();
+        let mut buf = vec![0u8; 16];
+        buf.extend_from_slice(&quote_raw);
+        buf.extend_from_slice(&[0u8; 16]);
+        assert!(SgxQuote::parse_at(&buf, 16, quote_raw.len()).is_ok());
+        assert!(SgxEnclaveReport::parse_at(&buf, 16 + 48, quote_raw.len() - 48).is_ok());
+        assert!(SgxQuote::parse_at(&buf, 33, quote_raw.len()).is_err());
+        assert!(SgxQuote::parse_at(&buf, usize::MAX, quote_raw.len()).is_err());
+    }
+
+    pub fn test_sgx_quote_unsupported_version() {
+        let attn_report = attesation_report();
+        let sgx_quote_body_encoded = attn_report["isvEnclaveQuoteBody"].as_str().unwrap();
+        let mut quote_raw = base64::decode(sgx_quote_body_encoded).unwrap();
+        quote_raw[0] = 9;
+        let err = SgxQuote::parse_from(&quote_raw).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::UnsupportedQuoteVersion(9)) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    pub fn test_sgx_quote_raw() {
+        let attn_report = attesation_report();
+        let sgx_quote_body_encoded = attn_report["isvEnclaveQuoteBody"].as_str().unwrap();
+        let quote_raw = base64::decode(sgx_quote_body_encoded).unwrap();
+        let sgx_quote = SgxQuote::parse_from(&quote_raw).unwrap();
+        assert_eq!(sgx_quote.raw(), Some(quote_raw.as_slice()));
+        let report_body = &sgx_quote.raw().unwrap()[sgx_quote.report_body_range()];
+        assert_eq!(
+            &report_body[320..],
+            &sgx_quote.isv_enclave_report.report_data[..]
+        );
+        assert!(SgxEnclaveReport::parse_from(report_body).is_ok());
+    }
+
+    pub fn test_sgx_quote_debug() {
+        let attn_report = attesation_report();
+        let sgx_quote_body_encoded = attn_report["isvEnclaveQuoteBody"].as_str().unwrap();
+        let quote_raw = base64::decode(sgx_quote_body_encoded).unwrap();
+        let sgx_quote = SgxQuote::parse_from(&quote_raw).unwrap();
+        let debug = format!("{:?}", sgx_quote);
+        let mr_enclave = hex::encode(sgx_quote.isv_enclave_report.mr_enclave);
+        assert!(debug.contains(&format!("mr_enclave: {}\n", mr_enclave)));
+        assert!(debug.contains(&format!(
+            "user_data: {}\n",
+            hex::encode(sgx_quote.user_data)
+        )));
+    }
+
+    pub fn test_sgx_quote_size_mismatch() {
+        let attn_report = attesation_report();
+        let sgx_quote_body_encoded = attn_report["isvEnclaveQuoteBody"].as_str().unwrap();
+        let quote_raw = base64::decode(sgx_quote_body_encoded).unwrap();
+        let parse_error = |bytes: &[u8]| {
+            SgxQuote::parse_from(bytes)
+                .unwrap_err()
+                .downcast::<AttestationError>()
+                .unwrap()
+        };
+        match parse_error(&quote_raw[..quote_raw.len() - 1]) {
+            AttestationError::QuoteSizeMismatch {
+                expected: 432,
+                actual: 431,
+            } => (),
+            e => panic!("unexpected error: {:?}", e),
+        }
+        let mut padded = quote_raw.clone();
+        padded.push(0);
+        assert_eq!(
+            parse_error(&padded).to_string(),
+            "Quote size is invalid: expected 432 bytes, got 433"
+        );
+        match parse_error(&[]) {
+            AttestationError::QuoteSizeMismatch { actual: 0, .. } => (),
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    pub fn test_sgx_quote_peek_version() {
+        let attn_report = attesation_report();
+        let sgx_quote_body_encoded = attn_report["isvEnclaveQuoteBody"].as_str().unwrap();
+        let quote_raw = base64::decode(sgx_quote_body_encoded).unwrap();
+        let parse_error = |bytes: &[u8]| {
+            SgxQuote::parse_from(bytes)
+                .unwrap_err()
+                .downcast::<AttestationError>()
+                .unwrap()
+        };
+        for version in &[4u16, 5] {
+            let mut header = vec![0u8; 48];
+            header[..2].copy_from_slice(&version.to_le_bytes());
+            assert_eq!(SgxQuote::peek_version(&header).unwrap(), *version);
+            match parse_error(&header) {
+                AttestationError::UnsupportedQuoteVersion(v) if v == *version => (),
+                e => panic!("unexpected error: {:?}", e),
+            }
+        }
+        assert_eq!(SgxQuote::peek_version(&quote_raw).unwrap(), 2);
+        assert!(SgxQuote::peek_version(&[4]).is_err());
+    }
+
+    pub fn test_sgx_quote_parse_prefix() {
+        let attn_report = attesation_report();
+        let sgx_quote_body_encoded = attn_report["isvEnclaveQuoteBody"].as_str().unwrap();
+        let quote_raw = base64::decode(sgx_quote_body_encoded).unwrap();
+        let mut buf = quote_raw.clone();
+        buf.extend_from_slice(&[0u8; 16]);
+        let (prefix, len) = SgxQuote::parse_prefix(&buf).unwrap();
+        assert_eq!(len, quote_raw.len());
+        assert_eq!(prefix.raw(), Some(quote_raw.as_slice()));
+        assert!(SgxQuote::parse_prefix(&quote_raw[..len - 1]).is_err());
+    }
+
+    pub fn test_sgx_quote_status_from_str() {
+        assert_eq!(
+            SgxQuoteStatus::from("SW_HARDENING_NEEDED"),
+            SgxQuoteStatus::SwHardeningNeeded
+        );
+        assert_eq!(
+            SgxQuoteStatus::from("CONFIGURATION_AND_SW_HARDENING_NEEDED"),
+            SgxQuoteStatus::ConfigurationAndSwHardeningNeeded
+        );
+        assert_eq!(
+            SgxQuoteStatus::from("SOMETHING_ELSE"),
+            SgxQuoteStatus::UnknownBadStatus("SOMETHING_ELSE".to_string())
+        );
+        for status in &[
+            SgxQuoteStatus::OK,
+            SgxQuoteStatus::GroupOutOfDate,
+            SgxQuoteStatus::ConfigurationNeeded,
+            SgxQuoteStatus::SwHardeningNeeded,
+            SgxQuoteStatus::ConfigurationAndSwHardeningNeeded,
+            SgxQuoteStatus::UnknownBadStatus("SOMETHING_ELSE".to_string()),
+        ] {
+            assert_eq!(SgxQuoteStatus::from(status.as_str()), *status);
+            assert_eq!(status.to_string(), status.as_str());
+        }
+    }
+
+    pub fn test_quote_builders() {
+        let report_builder = SgxEnclaveReportBuilder::default()
+            .cpu_svn([1; 16])
+            .misc_select(2)
+            .isv_ext_prod_id([3; 16])
+            .attributes([4; 16])
+            .mr_enclave([5; 32])
+            .mr_signer([6; 32])
+            .config_id([7; 64])
+            .isv_prod_id(8)
+            .isv_svn(9)
+            .config_svn(10)
+            .isv_family_id([11; 16])
+            .report_data([12; 64]);
+        let report_bytes = report_builder.to_bytes();
+        assert_eq!(report_bytes.len(), 384);
+        let report = SgxEnclaveReport::parse_from_kss(&report_bytes).unwrap();
+        assert_eq!(report.cpu_svn, [1; 16]);
+        assert_eq!(report.misc_select, 2);
+        assert_eq!(report.isv_ext_prod_id, [3; 16]);
+        assert_eq!(report.attributes, [4; 16]);
+        assert_eq!(report.mr_enclave, [5; 32]);
+        assert_eq!(report.mr_signer, [6; 32]);
+        assert_eq!(&report.config_id[..], &[7; 64][..]);
+        assert_eq!(report.isv_prod_id, 8);
+        assert_eq!(report.isv_svn, 9);
+        assert_eq!(report.config_svn, 10);
+        assert_eq!(report.isv_family_id, [11; 16]);
+        assert_eq!(&report.report_data[..], &[12; 64][..]);
+
+        let quote_bytes = SgxQuoteBuilder::new(SgxQuoteVersion::V2(SgxEpidQuoteSigType::Linkable))
+            .gid(13)
+            .isv_svn_qe(14)
+            .isv_svn_pce(15)
+            .qe_vendor_id(INTEL_QE_VENDOR_ID)
+            .user_data([16; 20])
+            .isv_enclave_report(report_builder.clone())
+            .to_bytes();
+        assert_eq!(quote_bytes.len(), 432);
+        let quote = SgxQuote::parse_from(&quote_bytes).unwrap();
+        assert_eq!(
+            quote.version,
+            SgxQuoteVersion::V2(SgxEpidQuoteSigType::Linkable)
+        );
+        assert_eq!(quote.gid, 13);
+        assert_eq!(quote.isv_svn_qe, 14);
+        assert_eq!(quote.isv_svn_pce, 15);
+        assert_eq!(quote.qe_vendor_id, INTEL_QE_VENDOR_ID);
+        assert_eq!(quote.user_data, [16; 20]);
+        assert_eq!(quote.isv_enclave_report.mr_enclave, [5; 32]);
+        assert_eq!(&quote_bytes[quote.report_body_range()], &report_bytes[..]);
+
+        let unlinkable = SgxQuoteBuilder::new(SgxQuoteVersion::V1(SgxEpidQuoteSigType::Unlinkable));
+        assert_eq!(
+            SgxQuote::parse_from(&unlinkable.to_bytes())
+                .unwrap()
+                .version,
+            SgxQuoteVersion::V1(SgxEpidQuoteSigType::Unlinkable)
+        );
+        let ecdsa = SgxQuoteBuilder::new(SgxQuoteVersion::V3(SgxEcdsaQuoteAkType::P384_384));
+        let ecdsa_quote = SgxQuote::parse_from(&ecdsa.to_bytes()).unwrap();
+        assert_eq!(
+            ecdsa_quote.version,
+            SgxQuoteVersion::V3(SgxEcdsaQuoteAkType::P384_384)
+        );
+        assert!(ecdsa_quote.ecdsa_sig.is_none());
+        assert!(SgxQuote::parse_from(&ecdsa.signature_data(vec![0; 4]).to_bytes()).is_err());
+    }
+
+    pub fn test_sgx_quote_validate_consistency() {
+        let epid = SgxQuoteBuilder::new(SgxQuoteVersion::V2(SgxEpidQuoteSigType::Linkable));
+        assert!(SgxQuote::parse_from_strict(&epid.to_bytes()).is_ok());
+        let mut reserved = epid.to_bytes();
+        reserved[48 + 20] = 1;
+        assert!(SgxQuote::parse_from(&reserved).is_ok());
+        let error = SgxQuote::parse_from_strict(&reserved).unwrap_err();
+        match error.downcast_ref::<AttestationError>() {
+            Some(AttestationError::NonZeroReserved { offset: 20 }) => (),
+            _ => panic!("unexpected error: {:?}", error),
+        }
+
+        let ecdsa = SgxQuoteBuilder::new(SgxQuoteVersion::V3(SgxEcdsaQuoteAkType::P256_256));
+        let nil_vendor = SgxQuote::parse_from(&ecdsa.to_bytes()).unwrap();
+        assert!(nil_vendor.validate_consistency().is_err());
+        let intel_vendor = ecdsa.qe_vendor_id(INTEL_QE_VENDOR_ID).to_bytes();
+        assert!(SgxQuote::parse_from_strict(&intel_vendor).is_ok());
+
+        let attn_report = attesation_report();
+        let sgx_quote_body_encoded = attn_report["isvEnclaveQuoteBody"].as_str().unwrap();
+        let quote_raw = base64::decode(sgx_quote_body_encoded).unwrap();
+        assert!(SgxQuote::parse_from_strict(&quote_raw).is_ok());
+    }
+
+    pub fn test_sgx_quote_epid_sig_type() {
+        let quote = |version| SgxQuote::parse_from(&SgxQuoteBuilder::new(version).to_bytes());
+
+        let linkable = quote(SgxQuoteVersion::V2(SgxEpidQuoteSigType::Linkable)).unwrap();
+        assert_eq!(
+            linkable.epid_sig_type(),
+            Some(&SgxEpidQuoteSigType::Linkable)
+        );
+        assert_eq!(linkable.is_linkable(), Some(true));
+        let unlinkable = quote(SgxQuoteVersion::V1(SgxEpidQuoteSigType::Unlinkable)).unwrap();
+        assert_eq!(
+            unlinkable.epid_sig_type(),
+            Some(&SgxEpidQuoteSigType::Unlinkable)
+        );
+        assert_eq!(unlinkable.is_linkable(), Some(false));
+        let ecdsa = quote(SgxQuoteVersion::V3(SgxEcdsaQuoteAkType::P256_256)).unwrap();
+        assert_eq!(ecdsa.epid_sig_type(), None);
+        assert_eq!(ecdsa.is_linkable(), None);
+    }
+
+    pub fn test_sgx_quote_epid_user_data() {
+        let quote = |version| {
+            let bytes = SgxQuoteBuilder::new(version)
+                .user_data([0xab; 20])
+                .to_bytes();
+            SgxQuote::parse_from(&bytes).unwrap()
+        };
+
+        let epid = quote(SgxQuoteVersion::V2(SgxEpidQuoteSigType::Linkable));
+        let user_data = epid.epid_user_data().unwrap();
+        assert_eq!(user_data, UserData([0xab; 20]));
+        assert!(user_data.matches(&[0xab; 20]));
+        assert!(!user_data.matches(&[0xac; 20]));
+        assert_eq!(user_data.as_hex(), "ab".repeat(20));
+        assert_eq!(user_data.to_string(), user_data.as_hex());
+        let ecdsa = quote(SgxQuoteVersion::V3(SgxEcdsaQuoteAkType::P256_256));
+        assert_eq!(ecdsa.epid_user_data(), None);
+        assert_eq!(ecdsa.user_data, [0xab; 20]);
+    }
+
+    pub fn test_sgx_enclave_report_parse_from_strict() {
+        let attn_report = attesation_report();
+        let sgx_quote_body_encoded = attn_report["isvEnclaveQuoteBody"].as_str().unwrap();
+        let mut quote_raw = base64::decode(&sgx_quote_body_encoded.as_bytes()).unwrap();
+        assert!(SgxEnclaveReport::parse_from_strict(&quote_raw[48..]).is_ok());
+
+        // KSS fields are not reserved, but only parsed by `parse_from_kss`
+        let mut kss_quote_raw = quote_raw.clone();
+        kss_quote_raw[48 + 32] = 1;
+        kss_quote_raw[48 + 192] = 2;
+        kss_quote_raw[48 + 260] = 3;
+        kss_quote_raw[48 + 304] = 4;
+        let report = SgxEnclaveReport::parse_from_strict(&kss_quote_raw[48..]).unwrap();
+        assert_eq!(report.isv_ext_prod_id, [0; 16]);
+        assert_eq!(&report.config_id[..], &[0; 64][..]);
+        assert_eq!(report.config_svn, 0);
+        assert_eq!(report.isv_family_id, [0; 16]);
+        assert_eq!(&report.to_bytes()[..], &kss_quote_raw[48..]);
+        let report = SgxEnclaveReport::parse_from_kss(&kss_quote_raw[48..]).unwrap();
+        assert_eq!(report.isv_ext_prod_id[0], 1);
+        assert_eq!(report.config_id[0], 2);
+        assert_eq!(report.config_svn, 3);
+        assert_eq!(report.isv_family_id[0], 4);
+        let json = report.to_json();
+        assert_eq!(json["isv_ext_prod_id"], format!("01{}", "00".repeat(15)));
+        assert_eq!(json["config_id"], format!("02{}", "00".repeat(63)));
+        assert_eq!(json["config_svn"], 3);
+        assert_eq!(json["isv_family_id"], format!("04{}", "00".repeat(15)));
+
+        let regions: Vec<(usize, usize)> =
+            SgxEnclaveReport::reserved_regions(&quote_raw[48..SGX_QUOTE_BODY_SIZE])
+                .map(|(offset, region)| (offset, region.len()))
+                .collect();
+        assert_eq!(regions, vec![(20, 12), (96, 32), (160, 32), (262, 42)]);
+        assert!(
+            SgxEnclaveReport::reserved_regions(&quote_raw[48..SGX_QUOTE_BODY_SIZE])
+                .all(|(_, region)| region.iter().all(|b| *b == 0))
+        );
+        let truncated: Vec<usize> = SgxEnclaveReport::reserved_regions(&quote_raw[48..48 + 200])
+            .map(|(offset, _)| offset)
+            .collect();
+        assert_eq!(truncated, vec![20, 96, 160]);
+
+        // Second byte of the reserved region after misc_select
+        quote_raw[48 + 21] = 1;
+        assert!(SgxEnclaveReport::parse_from(&quote_raw[48..]).is_ok());
+        let err = SgxEnclaveReport::parse_from_strict(&quote_raw[48..]).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::NonZeroReserved { offset }) => assert_eq!(*offset, 21),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    pub fn test_ct_eq() {
+        assert!(ct_eq(b"", b""));
+        assert!(ct_eq(&[1, 2, 3], &[1, 2, 3]));
+        assert!(!ct_eq(&[1, 2, 3], &[1, 2, 4]));
+        assert!(!ct_eq(&[1, 2, 3], &[1, 2]));
+        assert!(!ct_eq(&[0; 32], &[0; 64]));
+    }
+
+    pub fn test_cpu_svn_meets_minimum() {
+        let mut baseline = [0u8; 16];
+        baseline[0] = 2;
+        baseline[5] = 3;
+        let baseline = CpuSvn(baseline);
+        assert!(baseline.meets_minimum(&baseline));
+        assert!(baseline.verify_minimum(&baseline).is_ok());
+
+        let mut cpu_svn = baseline;
+        cpu_svn.0[1] = 9;
+        assert!(cpu_svn.meets_minimum(&baseline));
+        assert!(!baseline.meets_minimum(&cpu_svn));
+
+        // Higher in the first component does not compensate the sixth one
+        let mut cpu_svn = baseline;
+        cpu_svn.0[0] = 255;
+        cpu_svn.0[5] = 2;
+        assert!(!cpu_svn.meets_minimum(&baseline));
+        assert_eq!(cpu_svn.component_below_minimum(&baseline), Some(5));
+        let err = cpu_svn.verify_minimum(&baseline).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::CpuSvnBelowMinimum { component: 5 }) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+
+        let attn_report = attesation_report();
+        let sgx_quote_body_encoded = attn_report["isvEnclaveQuoteBody"].as_str().unwrap();
+        let quote_raw = base64::decode(&sgx_quote_body_encoded.as_bytes()).unwrap();
+        let report = SgxEnclaveReport::parse_from(&quote_raw[48..]).unwrap();
+        assert_eq!(report.sgx_cpu_svn(), CpuSvn(report.cpu_svn));
+        assert!(report.sgx_cpu_svn().meets_minimum(&CpuSvn([0; 16])));
+    }
+
+    pub fn test_sgx_enclave_report_verify_report_data_split() {
+        let mut report_data = [0u8; 64];
+        report_data[..32].copy_from_slice(&[1; 32]);
+        report_data[32..].copy_from_slice(&[2; 32]);
+        let report_bytes = SgxEnclaveReportBuilder::default()
+            .report_data(report_data)
+            .to_bytes();
+        let report = SgxEnclaveReport::parse_from(&report_bytes).unwrap();
+        let assert_mismatch = |result: Result<()>| {
+            let err = result.unwrap_err();
+            match err.downcast_ref::<AttestationError>() {
+                Some(AttestationError::ReportDataMismatch) => (),
+                _ => panic!("unexpected error: {:?}", err),
+            }
+        };
+
+        assert!(report
+            .verify_report_data_split(&[1; 32], |suffix| suffix == &[2; 32][..])
+            .is_ok());
+        assert_mismatch(report.verify_report_data_split(&[1; 32], |_| false));
+        assert_mismatch(report.verify_report_data_split(&[2; 32], |_| true));
+        assert!(report
+            .verify_report_data_split(&[], |suffix| suffix == &report_data[..])
+            .is_ok());
+        assert_mismatch(report.verify_report_data_split(&[1; 65], |_| true));
+    }
+
+    pub fn test_sgx_enclave_report_to_bytes() {
+        let attn_report = attesation_report();
+        let sgx_quote_body_encoded = attn_report["isvEnclaveQuoteBody"].as_str().unwrap();
+        let quote_raw = base64::decode(sgx_quote_body_encoded).unwrap();
+        let report_raw = &quote_raw[48..];
+        let report = SgxEnclaveReport::parse_from(report_raw).unwrap();
+        assert_eq!(&report.to_bytes()[..], report_raw);
+        assert_eq!(
+            &report.sha256()[..],
+            ring::digest::digest(&ring::digest::SHA256, report_raw).as_ref()
+        );
+
+        let report_builder = SgxEnclaveReportBuilder::default()
+            .cpu_svn([1; 16])
+            .misc_select(2)
+            .isv_ext_prod_id([3; 16])
+            .attributes([4; 16])
+            .mr_enclave([5; 32])
+            .mr_signer([6; 32])
+            .config_id([7; 64])
+            .isv_prod_id(8)
+            .isv_svn(9)
+            .config_svn(10)
+            .isv_family_id([11; 16])
+            .report_data([12; 64]);
+        let report = SgxEnclaveReport::parse_from_kss(&report_builder.to_bytes()).unwrap();
+        let round_trip = SgxEnclaveReport::parse_from_kss(&report.to_bytes()).unwrap();
+        assert_eq!(&report.to_bytes()[..], &report_builder.to_bytes()[..]);
+        assert_eq!(format!("{:?}", round_trip), format!("{:?}", report));
+
+        // KSS fields left zero by `parse_from` are still serialized as parsed
+        let report = SgxEnclaveReport::parse_from(&report_builder.to_bytes()).unwrap();
+        assert_eq!(&report.config_id[..], &[0; 64][..]);
+        assert_eq!(&report.to_bytes()[..], &report_builder.to_bytes()[..]);
+        assert_eq!(
+            &report.sha256()[..],
+            ring::digest::digest(&ring::digest::SHA256, &report_builder.to_bytes()).as_ref()
+        );
+        let report = SgxEnclaveReport::parse_from_strict(&report_builder.to_bytes()).unwrap();
+        assert_eq!(&report.to_bytes()[..], &report_builder.to_bytes()[..]);
+
+        let mut reserved = report_builder.to_bytes();
+        reserved[20] = 1;
+        let report = SgxEnclaveReport::parse_from(&reserved).unwrap();
+        assert_eq!(report.to_bytes()[20], 0);
+    }
+
+    pub fn test_sgx_misc_select() {
+        let report_bytes = |misc_select| {
+            SgxEnclaveReportBuilder::default()
+                .misc_select(misc_select)
+                .to_bytes()
+        };
+        let report = SgxEnclaveReport::parse_from(&report_bytes(1)).unwrap();
+        let misc_select = report.sgx_misc_select();
+        assert!(misc_select.is_exinfo());
+        assert!(!misc_select.has_reserved_bits());
+        assert!(report.verify_misc_select(MiscSelect(1)).is_ok());
+        let error = report.verify_misc_select(MiscSelect(0)).unwrap_err();
+        match error.downcast_ref::<AttestationError>() {
+            Some(AttestationError::MiscSelectMismatch) => (),
+            _ => panic!("unexpected error: {:?}", error),
+        }
+
+        let report = SgxEnclaveReport::parse_from(&report_bytes(0x8000_0000)).unwrap();
+        assert!(!report.sgx_misc_select().is_exinfo());
+        assert!(report.sgx_misc_select().has_reserved_bits());
+    }
+
+    pub fn test_sgx_enclave_report_satisfies() {
+        let report_bytes = SgxEnclaveReportBuilder::default()
+            .isv_prod_id(2)
+            .isv_svn(5)
+            .to_bytes();
+        let report = SgxEnclaveReport::parse_from(&report_bytes).unwrap();
+        assert!(report.satisfies(2, 5));
+        assert!(report.satisfies(2, 4));
+        assert!(report.satisfies(2, 0));
+        assert!(!report.satisfies(2, 6));
+        assert!(!report.satisfies(1, 5));
+        assert!(!report.satisfies(5, 2));
+    }
+
+    pub fn test_sgx_attributes() {
+        let mut attributes = [0u8; 16];
+        attributes[0] = 0x07;
+        attributes[8] = 0x03;
+        attributes[15] = 0x80;
+        let sgx_attributes = SgxAttributes::from(&attributes);
+        assert!(sgx_attributes.is_init() && sgx_attributes.is_debug());
+        assert!(sgx_attributes.is_mode64bit());
+        assert!(!sgx_attributes.is_provision_key());
+        assert!(!sgx_attributes.is_einittoken_key());
+        assert!(!sgx_attributes.is_kss());
+        assert_eq!(sgx_attributes.flags, 0x07);
+        assert_eq!(sgx_attributes.xfrm, 0x8000_0000_0000_0003);
+
+        attributes[0] = 0xb5;
+        let sgx_attributes = SgxAttributes::from(&attributes);
+        assert!(!sgx_attributes.is_debug());
+        assert!(sgx_attributes.is_provision_key());
+        assert!(sgx_attributes.is_einittoken_key());
+        assert!(sgx_attributes.is_kss());
+    }
+
+    pub fn test_sgx_enclave_report_data_halves() {
+        let attn_report = attesation_report();
+        let sgx_quote_body_encoded = attn_report["isvEnclaveQuoteBody"].as_str().unwrap();
+        let quote_raw = base64::decode(&sgx_quote_body_encoded.as_bytes()).unwrap();
+        let mut report = SgxEnclaveReport::parse_from(&quote_raw[48..]).unwrap();
+        assert_eq!(
+            &report.report_data_first_half()[..],
+            &report.report_data[..32]
+        );
+        assert_eq!(
+            &report.report_data_second_half()[..],
+            &report.report_data[32..]
+        );
+        let report_data = report.report_data;
+        assert!(report.report_data_matches(&report_data));
+        assert!(!report.report_data_matches(&report_data[..32]));
+        assert!(!report.report_data_matches(&[0u8; 65]));
+
+        let hash = [0xaa; 32];
+        report.report_data = [0; 64];
+        report.report_data[..32].copy_from_slice(&hash);
+        assert!(report.report_data_matches(&hash));
+        assert!(!report.report_data_matches(&hash[..16]));
+        assert!(!report.report_data_matches(&[0xaa; 33]));
+        assert_eq!(report.report_data_second_half(), &[0; 32]);
+    }
+}
@@ -22,9 +22,12 @@
 #[cfg(feature = "mesalock_sgx")]
 use std::prelude::v1::*;
 
+use crate::quote::ct_eq;
+use crate::verifier::{AttestationVerifier, ReportSignatureBackend};
 use crate::AttestationError;
 use crate::EndorsedAttestationReport;
 
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fmt;
 use std::time::*;
@@ -33,530 +36,781 @@ use std::untrusted::time::SystemTimeEx;
 
 use anyhow::{anyhow, bail, ensure, Error, Result};
 use chrono::DateTime;
-use serde_json::Value;
+use serde_json::{json, Value};
 use uuid::Uuid;
 
-type SignatureAlgorithms = &'static [&'static webpki::SignatureAlgorithm];
-static SUPPORTED_SIG_ALGS: SignatureAlgorithms = &[
-    &webpki::ECDSA_P256_SHA256,
-    &webpki::ECDSA_P256_SHA384,
-    &webpki::ECDSA_P384_SHA256,
-    &webpki::ECDSA_P384_SHA384,
-    &webpki::RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
-    &webpki::RSA_PSS_2048_8192_SHA384_LEGACY_KEY,
-    &webpki::RSA_PSS_2048_8192_SHA512_LEGACY_KEY,
-    &webpki::RSA_PKCS1_2048_8192_SHA256,
-    &webpki::RSA_PKCS1_2048_8192_SHA384,
-    &webpki::RSA_PKCS1_2048_8192_SHA512,
-    &webpki::RSA_PKCS1_3072_8192_SHA384,
-];
-
-/// A report generated by an enclave that contains measurement, identity and
-/// other data related to enclave.
-///
-/// # Note
+pub use crate::quote::{
+    CpuSvn, MiscSelect, SgxAttributes, SgxEcdsaQuoteAkType, SgxEnclaveReport, SgxEpidQuoteSigType,
+    SgxQuote, SgxQuoteStatus, SgxQuoteVersion, UserData, INTEL_QE_VENDOR_ID,
+    SUPPORTED_QUOTE_VERSIONS,
+};
+
+/// Intel SGX Attestation Report Signing CA certificate in DER, i.e., the root
+/// CA of the reports signed by Intel Attestation Service (EPID).
+#[cfg(feature = "ias_report_ca")]
+pub const IAS_REPORT_CA: &[u8] = include_bytes!("../../keys/ias_root_ca_cert.der");
+
+/// Maximum age of reports accepted by `AttestationReport::from_cert` and by
+/// `AttestationVerifier` by default.
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Parser of the quote body carried in `isvEnclaveQuoteBody` of an attestation
+/// report.
 ///
-/// Do not confuse `SgxEnclaveReport` with `AttestationReport`.
-/// `SgxEnclaveReport` is generated by SGX hardware and endorsed by Quoting
-/// Enclave through local attestation. The endorsed `SgxEnclaveReport` is an
-/// `SgxQuote`. The quote is then sent to some attestation service (IAS or
-/// DCAP-based AS). The endorsed `SgxQuote` is an attestation report signed by
-/// attestation service's private key, a.k.a., `EndorsedAttestationReport`.
-pub struct SgxEnclaveReport {
-    /// Security version number of host system's CPU
-    pub cpu_svn: [u8; 16],
-    /// Misc select bits for the target enclave. Reserved for future function
-    /// extension.
-    pub misc_select: u32,
-    /// Attributes of the enclave, for example, whether the enclave is running
-    /// in debug mode.
-    pub attributes: [u8; 16],
-    /// Measurement value of the enclave. See
-    /// [`EnclaveMeasurement`](../types/struct.EnclaveMeasurement.html)
-    pub mr_enclave: [u8; 32],
-    /// Measurement value of the public key that verified the enclave. See
-    /// [`EnclaveMeasurement`](../types/struct.EnclaveMeasurement.html)
-    pub mr_signer: [u8; 32],
-    /// Product ID of the enclave
-    pub isv_prod_id: u16,
-    /// Security version number of the enclave
-    pub isv_svn: u16,
-    /// Set of data used for communication between enclave and target enclave
-    pub report_data: [u8; 64],
-}
+/// `SgxQuote` implements this trait for the Intel quote layouts. Integrators
+/// can implement it for a vendor-specific quote format and use
+/// `AttestationVerifier::verify_with_parser` (or
+/// `AttestationReport::from_cert_with_parser`) to reuse the signature and
+/// freshness verification of the report, as well as the measurement and
+/// policy checks on the enclave report carried by the quote.
+pub trait QuoteBodyParser: Sized {
+    /// Parse the decoded quote body.
+    fn parse_quote_body(bytes: &[u8]) -> Result<Self>;
+
+    /// Report data bound into the quote by the enclave, which is expected to
+    /// contain the public key of the attested TLS certificate.
+    fn report_data(&self) -> &[u8];
+
+    /// Report of the attested enclave, e.g., for `verify_measurement` and
+    /// compliance profiles.
+    fn isv_enclave_report(&self) -> &SgxEnclaveReport;
+
+    /// Security version number of the Quoting Enclave, if the quote carries
+    /// one.
+    fn isv_svn_qe(&self) -> Option<u16> {
+        None
+    }
 
-impl std::fmt::Debug for SgxEnclaveReport {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "cpu_svn: {:?}", self.cpu_svn)?;
-        writeln!(f, "misc_select: {:?}", self.misc_select)?;
-        writeln!(f, "attributes: {:?}", self.attributes)?;
-        writeln!(f, "mr_enclave: {:?}", self.mr_enclave)?;
-        writeln!(f, "mr_signer: {:?}", self.mr_signer)?;
-        writeln!(f, "isv_prod_id: {}", self.isv_prod_id)?;
-        writeln!(f, "isv_svn: {}", self.isv_svn)?;
-        writeln!(f, "report_data: {:?}", &self.report_data.to_vec())
+    /// Security version number of the Provisioning Certification Enclave, if
+    /// the quote carries one.
+    fn isv_svn_pce(&self) -> Option<u16> {
+        None
     }
-}
 
-impl fmt::Display for SgxEnclaveReport {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "CPU version (hex): {}", hex::encode(self.cpu_svn))?;
-        writeln!(f, "SSA Frame extended feature set: {}", self.misc_select)?;
-        writeln!(
-            f,
-            "Attributes of the enclave (hex): {}",
-            hex::encode(self.attributes)
-        )?;
-        writeln!(
-            f,
-            "Enclave measurement (hex): {}",
-            hex::encode(self.mr_enclave)
-        )?;
-        writeln!(
-            f,
-            "Hash of the enclave singing key (hex): {}",
-            hex::encode(self.mr_signer)
-        )?;
-        writeln!(f, "Enclave product ID: {}", self.isv_prod_id)?;
-        writeln!(f, "Security version of the enclave: {}", self.isv_svn)?;
-        writeln!(
-            f,
-            "The value of REPORT (hex): {}",
-            hex::encode(&self.report_data.to_vec())
-        )
+    /// Verify that the quote is generated by the Quoting Enclave of
+    /// `expected`, if the quote carries a QE vendor ID.
+    fn verify_qe_vendor_id(&self, _expected: &Uuid) -> Result<()> {
+        Ok(())
     }
 }
 
-impl SgxEnclaveReport {
-    /// Parse bytes of report into `SgxEnclaveReport`.
-    pub fn parse_from<'a>(bytes: &'a [u8]) -> Result<Self> {
-        let mut pos: usize = 0;
-        let mut take = |n: usize| -> Result<&'a [u8]> {
-            if n > 0 && bytes.len() >= pos + n {
-                let ret = &bytes[pos..pos + n];
-                pos += n;
-                Ok(ret)
-            } else {
-                bail!("Quote parsing error.")
-            }
-        };
+impl QuoteBodyParser for SgxQuote {
+    fn parse_quote_body(bytes: &[u8]) -> Result<Self> {
+        SgxQuote::parse_from(bytes)
+    }
 
-        // Start parsing report by bytes following specifications. Don't
-        // transmute directly, since there may cause endianness issue.
-        // off 48, size 16
-        let cpu_svn = <[u8; 16]>::try_from(take(16)?)?;
+    fn report_data(&self) -> &[u8] {
+        &self.isv_enclave_report.report_data
+    }
 
-        // off 64, size 4
-        let misc_select = u32::from_le_bytes(<[u8; 4]>::try_from(take(4)?)?);
+    fn isv_enclave_report(&self) -> &SgxEnclaveReport {
+        &self.isv_enclave_report
+    }
 
-        // off 68, size 28
-        let _reserved = take(28)?;
+    fn isv_svn_qe(&self) -> Option<u16> {
+        Some(self.isv_svn_qe)
+    }
 
-        // off 96, size 16
-        let attributes = <[u8; 16]>::try_from(take(16)?)?;
+    fn isv_svn_pce(&self) -> Option<u16> {
+        Some(self.isv_svn_pce)
+    }
 
-        // off 112, size 32
-        let mr_enclave = <[u8; 32]>::try_from(take(32)?)?;
+    fn verify_qe_vendor_id(&self, expected: &Uuid) -> Result<()> {
+        SgxQuote::verify_qe_vendor_id(self, expected)
+    }
+}
 
-        // off 144, size 32
-        let _reserved = take(32)?;
+/// Identity of the physical platform on which a quote was generated.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlatformIdentity {
+    /// EPID pseudonym of a linkable quote returned by IAS, which is unique
+    /// for each platform and SPID.
+    EpidPseudonym(Vec<u8>),
+    /// Platform provisioning ID in the PCK certificate of the platform (DCAP).
+    Ppid(Vec<u8>),
+}
 
-        // off 176, size 32
-        let mr_signer = <[u8; 32]>::try_from(take(32)?)?;
+/// Fields identifying the build of an enclave, which are stable across
+/// platforms, unlike the TCB related fields (e.g., `cpu_svn`, `attributes` and
+/// the quote status).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BuildIdentity {
+    pub mr_enclave: [u8; 32],
+    pub mr_signer: [u8; 32],
+    pub isv_prod_id: u16,
+    pub isv_svn: u16,
+}
 
-        // off 208, size 96
-        let _reserved = take(96)?;
+/// A report that can be signed by Intel EPID (which generates
+/// `EndorsedAttestationReport`) and then sent off of the platform to be
+/// verified by remote client.
+#[derive(Debug)]
+pub struct AttestationReport<Q = SgxQuote> {
+    /// The freshness of the report, i.e., elapsed time after acquiring the
+    /// report in seconds.
+    pub freshness: Duration,
+    /// Time when the report was generated by the attestation service
+    pub timestamp: SystemTime,
+    /// Quote status
+    pub sgx_quote_status: SgxQuoteStatus,
+    /// Content of the quote
+    pub sgx_quote_body: Q,
+    /// Identity of the platform, if provided by the attestation service
+    pub platform_identity: Option<PlatformIdentity>,
+    /// IDs of the security advisories outstanding on the platform, e.g.,
+    /// `INTEL-SA-00334`, if provided by the attestation service
+    pub advisory_ids: Vec<String>,
+    /// Platform info blob (PIB) returned by IAS for non-OK statuses, e.g.,
+    /// `GROUP_OUT_OF_DATE`, which can be used to trigger a TCB update of the
+    /// platform
+    pub platform_info_blob: Option<Vec<u8>>,
+    /// Public key of the certificate carrying the report, in the uncompressed
+    /// form of SEC 1 (i.e., 0x04 || x || y) for EC keys, e.g., to pin the
+    /// peer; empty if the report is not carried by a certificate
+    pub public_key: Vec<u8>,
+    /// SHA-256 digest of the DER certificate carrying the report
+    pub cert_fingerprint: [u8; 32],
+    /// Source and value of the current time used in verification
+    pub clock_used: ClockSource,
+}
 
-        // off 304, size 2
-        let isv_prod_id = u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?);
+/// Source of the current time used to verify a report, recorded in
+/// `AttestationReport::clock_used` for audits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockSource {
+    /// `SystemTime::now()` of the verifier
+    System(SystemTime),
+    /// A clock injected by the caller, e.g., a trusted monotonic clock
+    Injected(SystemTime),
+    /// A reference time from an external source, e.g., a time server
+    External(SystemTime),
+}
 
-        // off 306, size 2
-        let isv_svn = u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?);
+/// Source of the current time used to verify reports, which can be replaced
+/// by a fixed clock to verify recorded reports deterministically.
+pub trait TimeSource {
+    fn now(&self) -> SystemTime;
+}
 
-        // off 308, size 60
-        let _reserved = take(60)?;
+/// `TimeSource` of `SystemTime::now()`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
 
-        // off 368, size 64
-        let mut report_data = [0u8; 64];
-        let _report_data = take(64)?;
-        let mut _it = _report_data.iter();
-        for i in report_data.iter_mut() {
-            *i = *_it.next().ok_or_else(|| anyhow!("Quote parsing error."))?;
-        }
+impl TimeSource for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
 
-        ensure!(pos == bytes.len(), "Quote parsing error.");
+impl ClockSource {
+    /// The current time given by the clock.
+    pub fn time(&self) -> SystemTime {
+        match self {
+            ClockSource::System(time)
+            | ClockSource::Injected(time)
+            | ClockSource::External(time) => *time,
+        }
+    }
+}
 
-        Ok(SgxEnclaveReport {
-            cpu_svn,
-            misc_select,
-            attributes,
-            mr_enclave,
-            mr_signer,
-            isv_prod_id,
-            isv_svn,
-            report_data,
-        })
+/// Check whether two reports came from the same physical platform. Returns
+/// `None` if it cannot be determined, e.g., the attestation service does not
+/// provide a platform identity (unlinkable EPID quotes) or the reports are
+/// endorsed by different backends.
+pub fn same_platform<Q>(a: &AttestationReport<Q>, b: &AttestationReport<Q>) -> Option<bool> {
+    use PlatformIdentity::*;
+    match (a.platform_identity.as_ref()?, b.platform_identity.as_ref()?) {
+        (EpidPseudonym(a), EpidPseudonym(b)) => Some(a == b),
+        (Ppid(a), Ppid(b)) => Some(a == b),
+        _ => None,
     }
 }
 
-/// SGX Quote structure version
-#[derive(Debug, PartialEq)]
-pub enum SgxQuoteVersion {
-    /// EPID quote version
-    V1(SgxEpidQuoteSigType),
-    /// EPID quote version
-    V2(SgxEpidQuoteSigType),
-    /// ECDSA quote version
-    V3(SgxEcdsaQuoteAkType),
+impl<Q: fmt::Display> fmt::Display for AttestationReport<Q> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Report Freshness: {:?}", self.freshness)?;
+        writeln!(f, "SGX Quote status: {:?}", self.sgx_quote_status)?;
+        write!(f, "{}", self.sgx_quote_body)
+    }
 }
 
-/// Intel EPID attestation signature type
-#[derive(Debug, PartialEq)]
-pub enum SgxEpidQuoteSigType {
-    Unlinkable,
-    Linkable,
+/// How the public key of the attested TLS certificate, or an application
+/// value, is bound into the report data of the quote.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportDataBinding {
+    /// The report data is the uncompressed EC point of the public key
+    /// (without the leading `0x04`), which is used by Teaclave.
+    RawPublicKey,
+    /// The first 32 bytes of the report data are the SHA-256 digest of the
+    /// DER encoded `SubjectPublicKeyInfo` of the certificate, which is used
+    /// by several other RA-TLS libraries.
+    SpkiHash,
+    /// The report data equals the given bytes, e.g., a hash of a manifest or
+    /// of a key exchange transcript, regardless of the certificate key.
+    Expected([u8; 64]),
 }
 
-/// ECDSA attestation key type
-#[derive(Debug, PartialEq)]
-pub enum SgxEcdsaQuoteAkType {
-    /// ECDSA-256-with-P-256 curve
-    P256_256,
-    /// ECDSA-384-with-P-384 curve
-    P384_384,
+/// OID of the extension carrying the endorsed attestation report in RA-TLS
+/// certificates (Netscape comment).
+pub(crate) const SGX_RA_CERT_EXT_OID: &[u64] = &[2, 16, 840, 1, 113_730, 1, 13];
+
+/// Get the value of the extension of the given OID from a certificate in DER.
+/// The extension must appear exactly once (RFC 5280, Section 4.2), wherever
+/// it is in the list of extensions.
+fn cert_ext_payload(cert: &[u8], oid: &[u64]) -> Result<Vec<u8>> {
+    let oid = yasna::models::ObjectIdentifier::from_slice(oid);
+    let mut matched = cert_fields(cert)?
+        .extensions
+        .into_iter()
+        .filter(|e| e.oid == oid);
+    let extension = matched
+        .next()
+        .ok_or_else(|| Error::new(AttestationError::ReportError))?;
+    ensure!(
+        matched.next().is_none(),
+        "Duplicated attestation extension in certificate."
+    );
+
+    Ok(extension.value)
 }
 
-impl std::fmt::Display for SgxQuoteVersion {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            SgxQuoteVersion::V1(key_type) => write!(f, "Version 1, EPID {:?} signature", key_type),
-            SgxQuoteVersion::V2(key_type) => write!(f, "Version 2, EPID {:?} signature", key_type),
-            SgxQuoteVersion::V3(key_type) => {
-                write!(f, "Version 3, ECDSA {:?} attestation key", key_type)
-            }
-        }
+/// Parse the `TBSCertificate` fields of a certificate in DER. Empty or
+/// malformed certificates, e.g., attacker-controlled bytes which are not DER,
+/// are rejected with `AttestationError::MalformedCertificate`.
+fn cert_fields(cert: &[u8]) -> Result<crate::cert::TbsCertFields> {
+    if cert.is_empty() {
+        bail!(anyhow!("Certificate is empty.").context(AttestationError::MalformedCertificate));
     }
+
+    crate::cert::tbs_cert_fields(cert)
+        .map_err(|e| anyhow!("{:?}", e).context(AttestationError::MalformedCertificate))
 }
 
-/// SGX Quote status
-#[derive(PartialEq, Debug)]
-pub enum SgxQuoteStatus {
-    /// EPID signature of the ISV enclave QUOTE was verified correctly and the
-    /// TCB level of the SGX platform is up-to-date.
-    OK,
-    /// EPID signature of the ISV enclave QUOTE was invalid. The content of the
-    /// QUOTE is not trustworthy.
-    ///
-    /// For DCAP, the signature over the application report is invalid.
-    SignatureInvalid,
-    /// The EPID group has been revoked. When this value is returned, the
-    /// revocation Reason field of the Attestation Verification Report will
-    /// contain revocation reason code for this EPID group as reported in the
-    /// EPID Group CRL. The content of the QUOTE is not trustworthy.
-    GroupRevoked,
-    /// The EPID private key used to sign the QUOTE has been revoked by
-    /// signature. The content of the QUOTE is not trustworthy.
-    SignatureRevoked,
-    /// The EPID private key used to sign the QUOTE has been directly revoked
-    /// (not by signature). The content of the QUOTE is not trustworthy.
+/// Type of the quote carried by an RA-TLS certificate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttestationType {
+    /// SGX quote signed with EPID (version 1 and 2)
+    Epid,
+    /// SGX quote signed with ECDSA (version 3, and version 4 of SGX)
+    Ecdsa,
+    /// TDX quote (version 4 of TDX)
+    Tdx,
+}
+
+/// Peek the type of the quote carried by an RA-TLS certificate in DER from
+/// its version, e.g., to pick the right verifier. Nothing is verified, so the
+/// result must not be trusted before the report is verified.
+pub fn peek_attestation_type(cert: &[u8]) -> Result<AttestationType> {
+    let payload = cert_ext_payload(cert, SGX_RA_CERT_EXT_OID)?;
+    let report: EndorsedAttestationReport = serde_json::from_slice(&payload)?;
+    let attn_report: Value = serde_json::from_slice(&report.report)?;
+    let quote = report_quote_body(&attn_report)?;
+
+    // off 0, size 2: version; off 4, size 4: TEE type of version 4
+    ensure!(quote.len() >= 8, AttestationError::QuoteTruncated);
+    let version = u16::from_le_bytes([quote[0], quote[1]]);
+    let attestation_type = match version {
+        1 | 2 => AttestationType::Epid,
+        3 => AttestationType::Ecdsa,
+        4 => match u32::from_le_bytes(<[u8; 4]>::try_from(&quote[4..8])?) {
+            0 => AttestationType::Ecdsa,
+            crate::dcap::TDX_TEE_TYPE => AttestationType::Tdx,
+            tee_type => bail!("Unsupported TEE type {:#x} in quote.", tee_type),
+        },
+        _ => bail!(AttestationError::UnsupportedQuoteVersion(version)),
+    };
+
+    Ok(attestation_type)
+}
+
+/// Verify that the report signing certificate, i.e., the first certificate of
+/// `certs`, is not revoked by `crl`, the CRL in DER of its issuer, which is
+/// either the next certificate of the chain or the report CA. A revoked
+/// certificate results in `AttestationError::CertRevoked`.
+#[cfg(feature = "report_revocation")]
+fn verify_report_signing_cert_revocation(
+    certs: &[Vec<u8>],
+    report_ca_cert: &[u8],
+    crl: &[u8],
+    now: SystemTime,
+) -> Result<()> {
+    let signing_cert = certs
+        .first()
+        .ok_or_else(|| anyhow!("Report signing certificate is missing."))?;
+    let issuer_cert = certs.get(1).map_or(report_ca_cert, |cert| cert.as_slice());
+    let crl = crate::dcap::Crl::verify_and_parse(crl, issuer_cert, now)?;
+    ensure!(
+        !crl.is_revoked(signing_cert)?,
+        AttestationError::CertRevoked
+    );
+
+    Ok(())
+}
+
+/// Certificate extension carrying the time when the RA-TLS certificate is
+/// issued, as a DER encoded `GeneralizedTime`.
+#[derive(Clone, Debug)]
+pub struct IssuanceTimeExtension {
+    /// OID of the extension
+    pub oid: Vec<u64>,
+    /// Maximum deviation of the issuance time from the report timestamp
+    pub max_deviation: Duration,
+}
+
+/// Parse the issuance time of a certificate from the value of its extension,
+/// and check that it deviates from the report timestamp by at most
+/// `max_deviation`.
+fn verify_issuance_time(
+    ext: &[u8],
+    timestamp: SystemTime,
+    max_deviation: Duration,
+) -> Result<SystemTime> {
+    let time = yasna::parse_der(ext, |reader| reader.read_generalized_time())?;
+    let issued_at = UNIX_EPOCH + Duration::from_secs(u64::try_from(time.datetime().timestamp())?);
+    let deviation = match issued_at.duration_since(timestamp) {
+        Ok(d) => d,
+        Err(e) => e.duration(),
+    };
+    ensure!(
+        deviation <= max_deviation,
+        AttestationError::IssuanceTimeMismatch
+    );
+
+    Ok(issued_at)
+}
+
+impl AttestationReport {
+    /// Construct a AttestationReport from a X509 certificate and verify
+    /// attestation report with the report_ca_cert which is from the attestation
+    /// service provider. Reports older than `DEFAULT_MAX_AGE` are rejected
+    /// with `AttestationError::Stale`, use `from_cert_with_max_age` for
+    /// another bound, and `AttestationVerifier` for other options of the
+    /// verification.
+    pub fn from_cert(certs: &[rustls::Certificate], report_ca_cert: &[u8]) -> Result<Self> {
+        Self::from_cert_with_max_age(certs, report_ca_cert, DEFAULT_MAX_AGE)
+    }
+
+    /// Same as `from_cert`, but return the report with the warnings of its
+    /// quote status, see `VerifiedReport`.
+    pub fn from_cert_with_warnings(
+        certs: &[rustls::Certificate],
+        report_ca_cert: &[u8],
+    ) -> Result<VerifiedReport> {
+        VerifiedReport::new(Self::from_cert(certs, report_ca_cert)?)
+    }
+
+    /// Same as `from_cert`, but verify the report with the embedded
+    /// `IAS_REPORT_CA`, i.e., for reports of Intel Attestation Service.
+    #[cfg(feature = "ias_report_ca")]
+    pub fn from_cert_default_ca(certs: &[rustls::Certificate]) -> Result<Self> {
+        Self::from_cert(certs, IAS_REPORT_CA)
+    }
+
+    /// Extract the attestation report from an RA-TLS certificate in DER
+    /// **without verifying it**, e.g., to inspect the quote status and
+    /// measurements of a peer during development.
     ///
-    /// For DCAP, the attestation key or platform has been revoked.
-    KeyRevoked,
-    /// SigRL version in ISV enclave QUOTE does not match the most recent
-    /// version of the SigRL. In rare situations, after SP retrieved the SigRL
-    /// from IAS and provided it to the platform, a newer version of the SigRL
-    /// is madeavailable. As a result, the Attestation Verification Report will
-    /// indicate SIGRL_VERSION_MISMATCH. SP can retrieve the most recent version
-    /// of SigRL from the IAS and request the platform to perform remote
-    /// attestation again with the most recent version of SigRL. If the platform
-    /// keeps failing to provide a valid QUOTE matching with the most recent
-    /// version of the SigRL, the content of the QUOTE is not trustworthy.
-    SigrlVersionMismatch,
-    /// The EPID signature of the ISV enclave QUOTE has been verified correctly,
-    /// but the TCB level of SGX platform is outdated (for further details see
-    /// Advisory IDs). The platform has not been identified as compromised and
-    /// thus it is not revoked. It is up to the Service Provider to decide
-    /// whether or not to trust the content of the QUOTE, andwhether or not to
-    /// trust the platform performing the attestation to protect specific
-    /// sensitive information.
-    GroupOutOfDate,
-    /// The EPID signature of the ISV enclave QUOTE has been verified correctly,
-    /// but additional configuration of SGX platform may beneeded(for further
-    /// details see Advisory IDs). The platform has not been identified as
-    /// compromised and thus it is not revoked. It is up to the Service Provider
-    /// to decide whether or not to trust the content of the QUOTE, and whether
-    /// or not to trust the platform performing the attestation to protect
-    /// specific sensitive information.
+    /// # Security
     ///
-    /// For DCAP, The Quote verification passed and the platform is patched to
-    /// the latest TCB level but additional configuration of the SGX
-    /// platform may be needed.
-    ConfigurationNeeded,
-    /// The EPID signature of the ISV enclave QUOTE has been verified correctly
-    /// but due to certain issues affecting the platform, additional SW
-    /// Hardening in the attesting SGX enclaves may be needed.The relying party
-    /// should evaluate the potential risk of an attack leveraging the relevant
-    /// issues on the attesting enclave, and whether the attesting enclave
-    /// employs adequate software hardening to mitigate the risk.
-    SwHardeningNeeded,
-    /// The EPID signature of the ISV enclave QUOTE has been verified correctly
-    /// but additional configuration for the platform and SW Hardening in the
-    /// attesting SGX enclaves may be needed. The platform has not been
-    /// identified as compromised and thus it is not revoked. It is up to the
-    /// Service Provider to decide whether or not to trust the content of the
-    /// QUOTE. The relying party should also evaluate the potential risk of an
-    /// attack leveraging the relevant issues on the attestation enclave, and
-    /// whether the attesting enclave employs adequate software hardening to
-    /// mitigate the risk.
-    ConfigurationAndSwHardeningNeeded,
-    /// DCAP specific quote status. The Quote is good but TCB level of the
-    /// platform is out of date. The platform needs patching to be at the latest
-    /// TCB level.
-    OutOfDate,
-    /// DCAP specific quote status. The Quote is good but the TCB level of the
-    /// platform is out of date and additional configuration of the SGX Platform
-    /// at its current patching level may be needed. The platform needs patching
-    /// to be at the latest TCB level.
-    OutOfDateConfigurationNeeded,
-    /// DCAP specific quote status. The signature over the application report is
-    /// invalid.
-    InvalidSignature,
-    /// Other unknown bad status.
-    UnknownBadStatus,
-}
-
-impl From<&str> for SgxQuoteStatus {
-    /// Convert from str status from the report to enum.
-    fn from(status: &str) -> Self {
-        match status {
-            "OK" => SgxQuoteStatus::OK,
-            "SIGNATURE_INVALID" => SgxQuoteStatus::SignatureInvalid,
-            "GROUP_REVOKED" => SgxQuoteStatus::GroupRevoked,
-            "SIGNATURE_REVOKED" => SgxQuoteStatus::SignatureRevoked,
-            "KEY_REVOKED" => SgxQuoteStatus::KeyRevoked,
-            "SIGRL_VERSION_MISMATCH" => SgxQuoteStatus::SigrlVersionMismatch,
-            "GROUP_OUT_OF_DATE" => SgxQuoteStatus::GroupOutOfDate,
-            "OUT_OF_DATE" => SgxQuoteStatus::OutOfDate,
-            "OUT_OF_DATE_CONFIGURATION_NEEDED" => SgxQuoteStatus::OutOfDateConfigurationNeeded,
-            "CONFIGURATION_NEEDED" => SgxQuoteStatus::ConfigurationNeeded,
-            "SW_HARDENING_NEEDED" => SgxQuoteStatus::SwHardeningNeeded,
-            "CONFIGURATION_AND_SW_HARDENING_NEEDED" => {
-                SgxQuoteStatus::ConfigurationAndSwHardeningNeeded
-            }
-            _ => SgxQuoteStatus::UnknownBadStatus,
-        }
-    }
-}
-
-/// An application that hosts an enclave can ask the enclave to produce a report
-/// (`SgxEnclaveReport`) and then pass this report to a platform service
-/// (Quoting Enclave) to produce a type of credential that reflects the enclave
-/// and platform state. The quote can be passed to entities off the platform,
-/// and verified using Intel EPID signature verification techniques.
-pub struct SgxQuote {
-    /// Version of the quote structure
-    pub version: SgxQuoteVersion,
-    /// ID of the Intel EPID group of the platform belongs to
-    pub gid: u32,
-    /// Security version number of Quoting Enclave
-    pub isv_svn_qe: u16,
-    /// Security version number of PCE
-    pub isv_svn_pce: u16,
-    /// Vendor ID of Quoting Enclave
-    pub qe_vendor_id: Uuid,
-    /// User data
-    pub user_data: [u8; 20],
-    /// Report generated by the enclave
-    pub isv_enclave_report: SgxEnclaveReport,
-}
-
-impl std::fmt::Debug for SgxQuote {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "version: {:?}", self.version)?;
-        writeln!(f, "gid: {}", self.gid)?;
-        writeln!(f, "isv_svn_qe: {}", self.isv_svn_qe)?;
-        writeln!(f, "isv_svn_pce: {}", self.isv_svn_pce)?;
-        writeln!(f, "qe_vendor_id: {}", self.qe_vendor_id)?;
-        writeln!(f, "user_data: {:?}", &self.user_data)?;
-        write!(f, "isv_enclave_report: \n{:?}", self.isv_enclave_report)
-    }
-}
-
-impl fmt::Display for SgxQuote {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Version and signature/key type: {}", self.version)?;
-        writeln!(f, "GID or reserved: {}", self.gid)?;
-        writeln!(f, "Security version of the QE: {}", self.isv_svn_qe)?;
-        writeln!(f, "Security version of the PCE: {}", self.isv_svn_pce)?;
-        writeln!(f, "ID of the QE vendor: {}", self.qe_vendor_id)?;
-        writeln!(
-            f,
-            "Custom user-defined data (hex): {}",
-            hex::encode(&self.user_data)
-        )?;
-        write!(f, "{}", self.isv_enclave_report)
-    }
-}
-
-impl SgxQuote {
-    /// Parse from bytes to `SgxQuote`.
-    pub fn parse_from<'a>(bytes: &'a [u8]) -> Result<Self> {
-        let mut pos: usize = 0;
-        let mut take = |n: usize| -> Result<&'a [u8]> {
-            if n > 0 && bytes.len() >= pos + n {
-                let ret = &bytes[pos..pos + n];
-                pos += n;
-                Ok(ret)
-            } else {
-                bail!("Quote parsing error.")
-            }
-        };
+    /// Neither the signature and signing certificate chain of the report, nor
+    /// the binding of the report to the certificate, nor the API version and
+    /// QE vendor are checked. Never trust the returned report; use
+    /// `from_cert` for verification.
+    pub fn parse_unverified(cert: &[u8]) -> Result<Self> {
+        let cert_ext_payload = cert_ext_payload(cert, SGX_RA_CERT_EXT_OID)?;
+        let report: EndorsedAttestationReport = serde_json::from_slice(&cert_ext_payload)?;
+        let attn_report: Value = serde_json::from_slice(&report.report)?;
+        let fields = report_fields(&attn_report, cert)?;
 
-        // Parse by bytes according to specifications.
-        // off 0, size 2 + 2
-        let version = match u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?) {
-            1 => {
-                let signature_type = match u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?) {
-                    0 => SgxEpidQuoteSigType::Unlinkable,
-                    1 => SgxEpidQuoteSigType::Linkable,
-                    _ => bail!("Quote parsing error."),
-                };
-                SgxQuoteVersion::V1(signature_type)
-            }
-            2 => {
-                let signature_type = match u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?) {
-                    0 => SgxEpidQuoteSigType::Unlinkable,
-                    1 => SgxEpidQuoteSigType::Linkable,
-                    _ => bail!("Quote parsing error."),
-                };
-                SgxQuoteVersion::V2(signature_type)
-            }
-            3 => {
-                let attestation_key_type = match u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?)
-                {
-                    2 => SgxEcdsaQuoteAkType::P256_256,
-                    3 => SgxEcdsaQuoteAkType::P384_384,
-                    _ => bail!("Quote parsing error."),
-                };
-                SgxQuoteVersion::V3(attestation_key_type)
-            }
-            _ => bail!("Quote parsing error."),
-        };
+        let now = SystemClock.now();
+        let freshness = now
+            .duration_since(fields.timestamp)
+            .map(|age| Duration::from_secs(age.as_secs()))
+            .unwrap_or_default();
 
-        // off 4, size 4
-        let gid = u32::from_le_bytes(<[u8; 4]>::try_from(take(4)?)?);
+        Ok(fields.into_report(freshness, ClockSource::System(now)))
+    }
 
-        // off 8, size 2
-        let isv_svn_qe = u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?);
+    /// Same as `from_cert`, but take the RA-TLS certificate and the root CA
+    /// certificate of the attestation service as `rustls::Certificate`, e.g.,
+    /// as held by rustls-based servers.
+    pub fn from_rustls_cert(
+        cert: &rustls::Certificate,
+        report_ca_cert: &rustls::Certificate,
+    ) -> Result<Self> {
+        Self::from_cert(std::slice::from_ref(cert), &report_ca_cert.0)
+    }
 
-        // off 10, size 2
-        let isv_svn_pce = u16::from_le_bytes(<[u8; 2]>::try_from(take(2)?)?);
+    /// Same as `from_cert`, but accept a report signing certificate chaining
+    /// to any of `report_ca_certs`, e.g., both the old and the new CA during a
+    /// rotation of the report CA.
+    pub fn from_cert_with_report_cas(
+        certs: &[rustls::Certificate],
+        report_ca_certs: &[&[u8]],
+    ) -> Result<Self> {
+        AttestationVerifier::with_report_cas(report_ca_certs)?.verify_certs(certs, None)
+    }
 
-        // off 12, size 16
-        let qe_vendor_id_raw = <[u8; 16]>::try_from(take(16)?)?;
-        let qe_vendor_id = Uuid::from_slice(&qe_vendor_id_raw)?;
+    /// Same as `from_cert`, but verify that the report data equals `expected`
+    /// instead of binding the public key of the certificate.
+    pub fn from_cert_with_report_data(
+        certs: &[rustls::Certificate],
+        report_ca_cert: &[u8],
+        expected: &[u8; 64],
+    ) -> Result<Self> {
+        let mut verifier = AttestationVerifier::new(report_ca_cert)?;
+        verifier.report_data_binding = ReportDataBinding::Expected(*expected);
+        verifier.verify_certs(certs, None)
+    }
 
-        // off 28, size 20
-        let user_data = <[u8; 20]>::try_from(take(20)?)?;
+    /// Same as `from_cert`, but require the report to echo `expected_nonce`
+    /// sent in the quote request to the attestation service, so that a
+    /// captured report cannot be replayed. A missing or different nonce
+    /// results in `AttestationError::NonceMismatch`.
+    pub fn from_cert_with_nonce(
+        certs: &[rustls::Certificate],
+        report_ca_cert: &[u8],
+        expected_nonce: &str,
+    ) -> Result<Self> {
+        let mut verifier = AttestationVerifier::new(report_ca_cert)?;
+        verifier.nonce = Some(expected_nonce.to_string());
+        verifier.verify_certs(certs, None)
+    }
 
-        // off 48, size 384
-        let isv_enclave_report = SgxEnclaveReport::parse_from(take(384)?)?;
+    /// Same as `from_cert`, but reject reports older than `max_age` with
+    /// `AttestationError::Stale`, e.g., 24 hours.
+    pub fn from_cert_with_max_age(
+        certs: &[rustls::Certificate],
+        report_ca_cert: &[u8],
+        max_age: Duration,
+    ) -> Result<Self> {
+        let mut verifier = AttestationVerifier::new(report_ca_cert)?;
+        verifier.max_age = Some(max_age);
+        verifier.verify_certs(certs, None)
+    }
 
-        ensure!(pos == bytes.len(), "Quote parsing error.");
+    /// Same as `from_cert`, but verify the report at the time read once from
+    /// `time_source` instead of `SystemTime::now()`, which is recorded as an
+    /// injected clock.
+    pub fn from_cert_with_time(
+        certs: &[rustls::Certificate],
+        report_ca_cert: &[u8],
+        time_source: &dyn TimeSource,
+    ) -> Result<Self> {
+        let clock = ClockSource::Injected(time_source.now());
+        AttestationVerifier::new(report_ca_cert)?.verify_certs(certs, Some(clock))
+    }
 
-        Ok(Self {
-            version,
-            gid,
-            isv_svn_qe,
-            isv_svn_pce,
-            qe_vendor_id,
-            user_data,
-            isv_enclave_report,
-        })
+    /// Verify the common requirements of production enclaves, i.e., the enclave
+    /// is signed by `expected_mr_signer`, not in debug mode and in 64-bit mode.
+    pub fn verify_production(&self, expected_mr_signer: &[u8; 32]) -> Result<()> {
+        let report = &self.sgx_quote_body.isv_enclave_report;
+        ensure!(
+            ct_eq(&report.mr_signer, expected_mr_signer),
+            AttestationError::NotProductionEnclave("unexpected MRSIGNER")
+        );
+        report.verify_not_debug()?;
+        ensure!(
+            report.sgx_attributes().is_mode64bit(),
+            AttestationError::NotProductionEnclave("not in 64-bit mode")
+        );
+
+        Ok(())
+    }
+
+    /// Verify the configuration of a KSS enclave, i.e., the CONFIGID equals to
+    /// `expected_config_id` and the CONFIGSVN is not lower than
+    /// `min_config_svn`, e.g., to attest the tenant of a multi-tenant
+    /// enclave. The KSS fields are checked as parsed from the quote.
+    pub fn verify_config_id(
+        &self,
+        expected_config_id: &[u8; 64],
+        min_config_svn: u16,
+    ) -> Result<()> {
+        let kss = self.sgx_quote_body.isv_enclave_report.kss_fields();
+        ensure!(
+            ct_eq(&kss.config_id, expected_config_id) && kss.config_svn >= min_config_svn,
+            AttestationError::ConfigIdMismatch
+        );
+
+        Ok(())
+    }
+
+    /// Report data bound into the quote by the enclave, e.g., to carry a hash
+    /// or nonce of the application.
+    pub fn report_data(&self) -> &[u8; 64] {
+        &self.sgx_quote_body.isv_enclave_report.report_data
+    }
+
+    /// Verify that the first 32 bytes of the report data equal the SHA-256
+    /// hash of the concatenation of the `transcript` messages, as bound by
+    /// challenge-response attestation protocols.
+    pub fn verify_report_data_transcript(&self, transcript: &[&[u8]]) -> Result<()> {
+        let mut ctx = ring::digest::Context::new(&ring::digest::SHA256);
+        for message in transcript {
+            ctx.update(message);
+        }
+        let digest = ctx.finish();
+        ensure!(
+            ct_eq(&self.report_data()[..32], digest.as_ref()),
+            AttestationError::ReportDataMismatch
+        );
+
+        Ok(())
+    }
+
+    /// Identity of the enclave build attested by the report.
+    pub fn build_identity(&self) -> BuildIdentity {
+        let report = &self.sgx_quote_body.isv_enclave_report;
+        BuildIdentity {
+            mr_enclave: report.mr_enclave,
+            mr_signer: report.mr_signer,
+            isv_prod_id: report.isv_prod_id,
+            isv_svn: report.isv_svn,
+        }
     }
 }
 
-/// A report that can be signed by Intel EPID (which generates
-/// `EndorsedAttestationReport`) and then sent off of the platform to be
-/// verified by remote client.
+impl<Q: QuoteBodyParser> AttestationReport<Q> {
+    /// Flatten the report into named claims, e.g., for evaluating a
+    /// `policy::Policy`. Byte-array values are lower-case hex strings and
+    /// the status is given as reported by the attestation service:
+    ///
+    /// `mr_enclave`, `mr_signer`, `cpu_svn`, `attributes`, `report_data`,
+    /// `isv_prod_id`, `isv_svn`, `isv_svn_qe`, `isv_svn_pce` (if carried by
+    /// the quote), `debug`, `status` and `freshness` (in seconds).
+    pub fn as_claims(&self) -> HashMap<String, Value> {
+        let quote = &self.sgx_quote_body;
+        let report = quote.isv_enclave_report();
+        let mut claims = vec![
+            ("mr_enclave", json!(hex::encode(report.mr_enclave))),
+            ("mr_signer", json!(hex::encode(report.mr_signer))),
+            ("cpu_svn", json!(hex::encode(report.cpu_svn))),
+            ("attributes", json!(hex::encode(report.attributes))),
+            ("report_data", json!(hex::encode(&report.report_data[..]))),
+            ("isv_prod_id", json!(report.isv_prod_id)),
+            ("isv_svn", json!(report.isv_svn)),
+            ("debug", json!(report.sgx_attributes().is_debug())),
+            ("status", json!(self.sgx_quote_status.as_str())),
+            ("freshness", json!(self.freshness.as_secs())),
+        ];
+        if let Some(isv_svn_qe) = quote.isv_svn_qe() {
+            claims.push(("isv_svn_qe", json!(isv_svn_qe)));
+        }
+        if let Some(isv_svn_pce) = quote.isv_svn_pce() {
+            claims.push(("isv_svn_pce", json!(isv_svn_pce)));
+        }
+
+        claims
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect()
+    }
+}
+
+impl<Q> AttestationReport<Q> {
+    /// Verify that the report is not older than `max_age` at `now`, and
+    /// return the age of the report in whole seconds. A report older than
+    /// `max_age` results in `AttestationError::Stale`.
+    pub fn verify_freshness(&self, max_age: Duration, now: SystemTime) -> Result<Duration> {
+        self.verify_freshness_with_clock_skew(max_age, now, Duration::from_secs(0))
+    }
+
+    /// Same as `verify_freshness`, but tolerate a skew of `clock_skew` between
+    /// the clocks of the attestation service and the verifier in both
+    /// directions, i.e., the report timestamp can be up to `clock_skew` ahead
+    /// of `now`, and the report is stale only if older than
+    /// `max_age + clock_skew`.
+    pub fn verify_freshness_with_clock_skew(
+        &self,
+        max_age: Duration,
+        now: SystemTime,
+        clock_skew: Duration,
+    ) -> Result<Duration> {
+        let age = report_age(self.timestamp, now, clock_skew)?;
+        ensure!(
+            age <= max_age + clock_skew,
+            AttestationError::Stale { age, max_age }
+        );
+
+        Ok(age)
+    }
+
+    /// Time for which the report is still fresh under `max_age`, measured
+    /// from `freshness` at verification, e.g., to schedule re-attestation of
+    /// a long-lived connection. Returns `None` if the report is older than
+    /// `max_age`.
+    pub fn remaining_validity(&self, max_age: Duration) -> Option<Duration> {
+        max_age.checked_sub(self.freshness)
+    }
+
+    /// SHA-256 fingerprint of the RA-TLS certificate carrying the report,
+    /// which can be pinned for subsequent connections to the same enclave.
+    pub fn cert_fingerprint(&self) -> [u8; 32] {
+        self.cert_fingerprint
+    }
+}
+
+/// Warning of a verified report, i.e., a quote status of a trusted quote which
+/// the policy of the relying party may still reject, with the outstanding
+/// advisory IDs of the report.
+#[derive(Clone, Debug, PartialEq)]
+pub enum VerificationWarning {
+    /// TCB level of the platform is out of date, e.g., `GROUP_OUT_OF_DATE`
+    TcbOutOfDate(Vec<String>),
+    /// Additional configuration of the platform may be needed
+    ConfigurationNeeded(Vec<String>),
+    /// Additional software hardening of the enclave may be needed
+    SwHardeningNeeded(Vec<String>),
+}
+
+/// Attestation report with the warnings of its quote status, leaving the
+/// decision on graduated TCB statuses to the policy of the relying party.
 #[derive(Debug)]
-pub struct AttestationReport {
-    /// The freshness of the report, i.e., elapsed time after acquiring the
-    /// report in seconds.
-    pub freshness: Duration,
-    /// Quote status
-    pub sgx_quote_status: SgxQuoteStatus,
-    /// Content of the quote
-    pub sgx_quote_body: SgxQuote,
+pub struct VerifiedReport<Q = SgxQuote> {
+    pub report: AttestationReport<Q>,
+    pub warnings: Vec<VerificationWarning>,
 }
 
-impl fmt::Display for AttestationReport {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Report Freshness: {:?}", self.freshness)?;
-        writeln!(f, "SGX Quote status: {:?}", self.sgx_quote_status)?;
-        write!(f, "{}", self.sgx_quote_body)
+impl<Q> VerifiedReport<Q> {
+    /// Collect the warnings of a verified report. Reports whose quote is not
+    /// trustworthy, e.g., `GROUP_REVOKED`, are rejected with
+    /// `AttestationError::QuoteStatusUntrusted`.
+    pub fn new(report: AttestationReport<Q>) -> Result<Self> {
+        let advisory_ids = || report.advisory_ids.clone();
+        let warnings = match report.sgx_quote_status {
+            SgxQuoteStatus::OK => vec![],
+            SgxQuoteStatus::GroupOutOfDate | SgxQuoteStatus::OutOfDate => {
+                vec![VerificationWarning::TcbOutOfDate(advisory_ids())]
+            }
+            SgxQuoteStatus::OutOfDateConfigurationNeeded => vec![
+                VerificationWarning::TcbOutOfDate(advisory_ids()),
+                VerificationWarning::ConfigurationNeeded(advisory_ids()),
+            ],
+            SgxQuoteStatus::ConfigurationNeeded => {
+                vec![VerificationWarning::ConfigurationNeeded(advisory_ids())]
+            }
+            SgxQuoteStatus::SwHardeningNeeded => {
+                vec![VerificationWarning::SwHardeningNeeded(advisory_ids())]
+            }
+            SgxQuoteStatus::ConfigurationAndSwHardeningNeeded => vec![
+                VerificationWarning::ConfigurationNeeded(advisory_ids()),
+                VerificationWarning::SwHardeningNeeded(advisory_ids()),
+            ],
+            status => bail!(AttestationError::QuoteStatusUntrusted(status)),
+        };
+
+        Ok(Self { report, warnings })
     }
 }
 
-impl AttestationReport {
-    /// Construct a AttestationReport from a X509 certificate and verify
-    /// attestation report with the report_ca_cert which is from the attestation
-    /// service provider.
-    pub fn from_cert(certs: &[rustls::Certificate], report_ca_cert: &[u8]) -> Result<Self> {
+impl<Q: QuoteBodyParser> AttestationReport<Q> {
+    /// Same as `from_cert`, but parse the quote body with the given
+    /// `QuoteBodyParser` instead of the Intel SGX quote layout.
+    pub fn from_cert_with_parser(
+        certs: &[rustls::Certificate],
+        report_ca_cert: &[u8],
+    ) -> Result<Self> {
+        AttestationVerifier::new(report_ca_cert)?.verify_certs(certs, None)
+    }
+
+    /// Verify the attestation report in the first of `certs` with the options
+    /// of `verifier`, and its signature with `backend` and `report_ca_cert`.
+    pub(crate) fn verify_from_cert(
+        certs: &[rustls::Certificate],
+        report_ca_cert: &[u8],
+        backend: &dyn ReportSignatureBackend,
+        verifier: &AttestationVerifier,
+        clock: Option<ClockSource>,
+    ) -> Result<Self> {
         // Before we reach here, Webpki already verifed the cert is properly signed.
-        use crate::cert::*;
 
         // Extract information for attestation from TLS certification.
-        let x509 = yasna::parse_der(&certs[0].0, X509::load)?;
-        let tbs_cert: <TbsCert as Asn1Ty>::ValueTy = x509.0;
-        let pub_key: <PubKey as Asn1Ty>::ValueTy = ((((((tbs_cert.1).1).1).1).1).1).0;
-        let pub_k = (pub_key.1).0;
-        let cert_ext: <SgxRaCertExt as Asn1Ty>::ValueTy = (((((((tbs_cert.1).1).1).1).1).1).1).0;
-        let cert_ext_payload: Vec<u8> = ((cert_ext.0).1).0;
+        let cert = certs.first().ok_or_else(|| {
+            anyhow!("Certificate is missing.").context(AttestationError::MalformedCertificate)
+        })?;
+        let spki = cert_fields(&cert.0)?.subject_public_key_info;
+        let issuance_time = match &verifier.issuance_time {
+            Some(issuance_time) => Some((
+                cert_ext_payload(&cert.0, &issuance_time.oid)?,
+                issuance_time,
+            )),
+            None => None,
+        };
+        let cert_ext_payload = cert_ext_payload(&cert.0, &verifier.cert_ext_oid)?;
         log::debug!("cert_ext_payload: {:?}", &cert_ext_payload);
 
         // Convert to endorsed report
         let report: EndorsedAttestationReport = serde_json::from_slice(&cert_ext_payload)?;
         log::debug!("endorsed attestation report: {:?}", &report);
 
-        // Verify report's signature
-        let signing_cert = webpki::EndEntityCert::from(&report.certs[0])?;
-        let root_store = {
-            let mut root_store = rustls::RootCertStore::empty();
-            root_store.add(&rustls::Certificate(report_ca_cert.to_vec()))?;
-            root_store
-        };
-        let trust_anchors: Vec<webpki::TrustAnchor> = root_store
-            .roots
-            .iter()
-            .map(|cert| cert.to_trust_anchor())
-            .collect();
-
-        let chain: Vec<&[u8]> = if report.certs.len() > 1 {
-            let mut c = report.certs[1..]
-                .iter()
-                .map(|c| &c[..])
-                .collect::<Vec<&[u8]>>();
-            c.push(report_ca_cert);
-            c
+        // Read the clock once, so that the certificate validity and the
+        // freshness of the report are checked against the same time.
+        let clock_used = clock.unwrap_or_else(|| ClockSource::System(SystemClock.now()));
+        let now = clock_used.time();
+        let verification_time = if verifier.at_report_timestamp {
+            // The timestamp is authenticated by the signature verified below.
+            report_timestamp(&serde_json::from_slice(&report.report)?)?
         } else {
-            vec![report_ca_cert]
+            now
         };
-        let time = webpki::Time::try_from(SystemTime::now())
-            .map_err(|_| anyhow!("Cannot convert time."))?;
-        signing_cert.verify_is_valid_tls_server_cert(
-            SUPPORTED_SIG_ALGS,
-            &webpki::TLSServerTrustAnchors(&trust_anchors),
-            &chain,
-            time,
-        )?;
-
-        // Verify the signature against the signing cert
-        signing_cert.verify_signature(
-            &webpki::RSA_PKCS1_2048_8192_SHA256,
-            &report.report,
-            &report.signature,
-        )?;
+
+        // Reject quotes of other versions before verifying the signature. The
+        // version is not authenticated yet, but it is only used to reject.
+        if let Some(quote_versions) = &verifier.quote_versions {
+            let quote = report_quote_body(&serde_json::from_slice(&report.report)?)?;
+            let version = SgxQuote::peek_version(&quote)?;
+            ensure!(
+                quote_versions.contains(&version),
+                AttestationError::UnsupportedQuoteVersion(version)
+            );
+        }
+
+        // Verify report's signature. With a clock skew, the signing
+        // certificate is also accepted if valid at the skewed times.
+        let verify_signature = |time: SystemTime| {
+            backend.verify(
+                &report.certs,
+                report_ca_cert,
+                &report.report,
+                &report.signature,
+                time,
+            )
+        };
+        let skew = verifier.clock_skew;
+        verify_signature(verification_time).or_else(|e| {
+            if skew == Duration::from_secs(0) {
+                return Err(e);
+            }
+            let earlier = verification_time.checked_sub(skew);
+            let later = verification_time.checked_add(skew);
+            earlier
+                .into_iter()
+                .chain(later)
+                .find(|time| verify_signature(*time).is_ok())
+                .map(|_| ())
+                .ok_or(e)
+        })?;
+
+        #[cfg(feature = "report_revocation")]
+        {
+            if let Some(crl) = &verifier.report_signing_crl {
+                verify_report_signing_cert_revocation(
+                    &report.certs,
+                    report_ca_cert,
+                    crl,
+                    verification_time,
+                )?;
+            }
+        }
+
+        if let Some(purpose) = &verifier.signing_cert_purpose {
+            let signing_cert = report
+                .certs
+                .first()
+                .ok_or_else(|| anyhow!("Report signing certificate is missing."))?;
+            purpose.verify(signing_cert)?;
+        }
 
         // Verify and extract information from attestation report
         let attn_report: Value = serde_json::from_slice(&report.report)?;
@@ -568,64 +822,437 @@ impl AttestationReport {
             .ok_or_else(|| Error::new(AttestationError::ReportError))?;
         ensure!(version == 4, AttestationError::ApiVersionNotCompatible);
 
+        if let Some(expected_nonce) = &verifier.nonce {
+            verify_report_nonce(&attn_report, expected_nonce)?;
+        }
+
+        let fields = report_fields::<Q>(&attn_report, &cert.0)?;
+
         // Get quote freshness
-        let freshness = {
-            let time = attn_report["timestamp"]
-                .as_str()
-                .ok_or_else(|| Error::new(AttestationError::ReportError))?;
-            let time_fixed = String::from(time) + "+0000";
-            let date_time = DateTime::parse_from_str(&time_fixed, "%Y-%m-%dT%H:%M:%S%.f%z")?;
-            let ts = date_time.naive_utc();
-            let now = DateTime::<chrono::offset::Utc>::from(SystemTime::now()).naive_utc();
-            let quote_freshness = u64::try_from((now - ts).num_seconds())?;
-            std::time::Duration::from_secs(quote_freshness)
+        let issued_at = match issuance_time {
+            Some((ext, issuance_time)) => {
+                verify_issuance_time(&ext, fields.timestamp, issuance_time.max_deviation)?
+            }
+            None => fields.timestamp,
         };
+        let freshness = report_age(issued_at, now, verifier.clock_skew)?;
+        // Archived reports are expected to be old, so their age is not bounded.
+        if let Some(max_age) = verifier.max_age.filter(|_| !verifier.at_report_timestamp) {
+            let age = freshness
+                .checked_sub(verifier.clock_skew)
+                .unwrap_or_default();
+            ensure!(
+                age <= max_age,
+                AttestationError::Stale {
+                    age: freshness,
+                    max_age
+                }
+            );
+        }
 
-        // Get quote status
-        let sgx_quote_status = {
-            let status_string = attn_report["isvEnclaveQuoteStatus"]
-                .as_str()
-                .ok_or_else(|| Error::new(AttestationError::ReportError))?;
-            SgxQuoteStatus::from(status_string)
-        };
+        let sgx_quote_body = &fields.sgx_quote_body;
+        if let Some(qe_vendor_id) = &verifier.qe_vendor_id {
+            sgx_quote_body.verify_qe_vendor_id(qe_vendor_id)?;
+        }
 
-        // Get quote body
-        let sgx_quote_body = {
-            let quote_encoded = attn_report["isvEnclaveQuoteBody"]
-                .as_str()
-                .ok_or_else(|| Error::new(AttestationError::ReportError))?;
-            let quote_raw = base64::decode(&quote_encoded.as_bytes())?;
-            SgxQuote::parse_from(quote_raw.as_slice())?
-        };
+        let public_key = &fields.public_key;
+        match verifier.report_data_binding {
+            ReportDataBinding::RawPublicKey => {
+                // According to RFC 5480 `Elliptic Curve Cryptography Subject
+                // Public Key Information', SEC 2.2: ``The first octet of the
+                // OCTET STRING indicates whether the key is compressed or
+                // uncompressed. The uncompressed form is indicated by 0x04 and
+                // the compressed form is indicated by either 0x02 or 0x03 (see
+                // 2.3.3 in [SEC1]). The public key MUST be rejected if any
+                // other value is included in the first octet.''
+                //
+                // The report data is the uncompressed key without the first
+                // octet, so compressed keys are decompressed first.
+                if public_key.first() != Some(&4) {
+                    bail!(AttestationError::ReportError);
+                }
+                if !ct_eq(&public_key[1..], sgx_quote_body.report_data()) {
+                    bail!(AttestationError::ReportDataMismatch);
+                }
+            }
+            ReportDataBinding::SpkiHash => {
+                let spki_hash = ring::digest::digest(&ring::digest::SHA256, &spki);
+                let report_data = sgx_quote_body.report_data();
+                if report_data.len() < 32 || !ct_eq(&report_data[..32], spki_hash.as_ref()) {
+                    bail!(AttestationError::ReportDataMismatch);
+                }
+            }
+            ReportDataBinding::Expected(expected) => {
+                if !ct_eq(sgx_quote_body.report_data(), &expected) {
+                    bail!(AttestationError::ReportDataMismatch);
+                }
+            }
+        }
 
-        // According to RFC 5480 `Elliptic Curve Cryptography Subject Public Key
-        // Information', SEC 2.2: ``The first octet of the OCTET STRING
-        // indicates whether the key is compressed or uncompressed. The
-        // uncompressed form is indicated by 0x04 and the compressed form is
-        // indicated by either 0x02 or 0x03 (see 2.3.3 in [SEC1]). The public
-        // key MUST be rejected if any other value is included in the first
-        // octet.''
-        //
-        // We only accept the uncompressed form here.
-        let raw_pub_k = pub_k.to_bytes();
-        let is_uncompressed = raw_pub_k[0] == 4;
-        let pub_k = &raw_pub_k.as_slice()[1..];
-        if !is_uncompressed || pub_k != &sgx_quote_body.isv_enclave_report.report_data[..] {
-            bail!(AttestationError::ReportError);
-        }
-
-        Ok(Self {
+        Ok(fields.into_report(freshness, clock_used))
+    }
+}
+
+/// Fields of an `AttestationReport` extracted from the JSON of the attestation
+/// report and the certificate carrying it, i.e., all but the freshness and the
+/// clock used in verification.
+struct ReportFields<Q> {
+    timestamp: SystemTime,
+    sgx_quote_status: SgxQuoteStatus,
+    sgx_quote_body: Q,
+    platform_identity: Option<PlatformIdentity>,
+    advisory_ids: Vec<String>,
+    platform_info_blob: Option<Vec<u8>>,
+    public_key: Vec<u8>,
+    cert_fingerprint: [u8; 32],
+}
+
+impl<Q> ReportFields<Q> {
+    fn into_report(self, freshness: Duration, clock_used: ClockSource) -> AttestationReport<Q> {
+        AttestationReport {
             freshness,
-            sgx_quote_status,
-            sgx_quote_body,
+            timestamp: self.timestamp,
+            sgx_quote_status: self.sgx_quote_status,
+            sgx_quote_body: self.sgx_quote_body,
+            platform_identity: self.platform_identity,
+            advisory_ids: self.advisory_ids,
+            platform_info_blob: self.platform_info_blob,
+            public_key: self.public_key,
+            cert_fingerprint: self.cert_fingerprint,
+            clock_used,
+        }
+    }
+}
+
+/// Extract the fields of an attestation report from its JSON and the RA-TLS
+/// certificate in DER carrying it. Nothing is verified, which is left to the
+/// caller.
+fn report_fields<Q: QuoteBodyParser>(attn_report: &Value, cert: &[u8]) -> Result<ReportFields<Q>> {
+    // SubjectPublicKeyInfo ::= SEQUENCE {
+    //      algorithm            AlgorithmIdentifier,
+    //      subjectPublicKey     BIT STRING }
+    let spki = cert_fields(cert)?.subject_public_key_info;
+    let raw_pub_k = yasna::parse_der(&spki, |reader| {
+        reader.read_sequence(|reader| {
+            reader.next().read_der()?;
+            let (pub_k, _) = reader.next().read_bitvec_bytes()?;
+            Ok(pub_k)
         })
+    })?;
+    let public_key = uncompressed_public_key(&raw_pub_k)?;
+
+    let mut cert_fingerprint = [0u8; 32];
+    cert_fingerprint.copy_from_slice(ring::digest::digest(&ring::digest::SHA256, cert).as_ref());
+
+    // Get report timestamp
+    let timestamp = report_timestamp(attn_report)?;
+
+    // Get quote status
+    let sgx_quote_status = {
+        let status_string = attn_report["isvEnclaveQuoteStatus"]
+            .as_str()
+            .ok_or_else(|| Error::new(AttestationError::ReportError))?;
+        SgxQuoteStatus::from(status_string)
+    };
+
+    // Get quote body
+    let sgx_quote_body = Q::parse_quote_body(&report_quote_body(attn_report)?)?;
+
+    // Get EPID pseudonym of the platform, which is only available for
+    // linkable quotes
+    let platform_identity = match attn_report["epidPseudonym"].as_str() {
+        Some(pseudonym) => Some(PlatformIdentity::EpidPseudonym(base64::decode(pseudonym)?)),
+        None => None,
+    };
+
+    // Get IDs of the outstanding security advisories, which are only
+    // available if the TCB level of the platform is not up-to-date
+    let advisory_ids = report_advisory_ids(attn_report)?;
+    let platform_info_blob = report_platform_info_blob(attn_report, &sgx_quote_status)?;
+
+    Ok(ReportFields {
+        timestamp,
+        sgx_quote_status,
+        sgx_quote_body,
+        platform_identity,
+        advisory_ids,
+        platform_info_blob,
+        public_key,
+        cert_fingerprint,
+    })
+}
+
+/// Convert a compressed P-256 public key of SEC 1 (2.3.3) into the
+/// uncompressed form, i.e., 0x04 || x || y. Other keys are returned as is.
+fn uncompressed_public_key(public_key: &[u8]) -> Result<Vec<u8>> {
+    match public_key.first() {
+        Some(2) | Some(3) if public_key.len() == 33 => {
+            let mut uncompressed = vec![4];
+            uncompressed.extend_from_slice(&decompress_p256_point(public_key)?);
+            Ok(uncompressed)
+        }
+        _ => Ok(public_key.to_vec()),
+    }
+}
+
+/// Decompress a P-256 point in the compressed form of SEC 1 (2.3.3), i.e.,
+/// 0x02 or 0x03 (the parity of y) followed by x, into x || y.
+fn decompress_p256_point(compressed: &[u8]) -> Result<[u8; 64]> {
+    use num_bigint::BigUint;
+
+    const P: &str = "ffffffff00000001000000000000000000000000ffffffffffffffffffffffff";
+    const B: &str = "5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b";
+
+    ensure!(
+        compressed.len() == 33 && (compressed[0] == 2 || compressed[0] == 3),
+        AttestationError::ReportError
+    );
+    let p = BigUint::parse_bytes(P.as_bytes(), 16).unwrap();
+    let b = BigUint::parse_bytes(B.as_bytes(), 16).unwrap();
+    let x = BigUint::from_bytes_be(&compressed[1..]);
+    ensure!(x < p, AttestationError::ReportError);
+
+    // y^2 = x^3 - 3x + b, and y = (y^2)^((p + 1) / 4) as p = 3 (mod 4)
+    let three = BigUint::from(3u32);
+    let y2 = (&x * &x * &x + (&p - &three) * &x + &b) % &p;
+    let mut y = y2.modpow(&((&p + 1u32) >> 2), &p);
+    ensure!(&y * &y % &p == y2, AttestationError::ReportError);
+    if (y.clone() % 2u32 == BigUint::from(1u32)) != (compressed[0] == 3) {
+        y = &p - &y;
     }
+
+    let mut point = [0u8; 64];
+    point[..32].copy_from_slice(&compressed[1..]);
+    let y = y.to_bytes_be();
+    point[64 - y.len()..].copy_from_slice(&y);
+
+    Ok(point)
+}
+
+/// Get the advisory IDs of an attestation report, which may be absent.
+fn report_advisory_ids(attn_report: &Value) -> Result<Vec<String>> {
+    match attn_report.get("advisoryIDs") {
+        Some(ids) => ids
+            .as_array()
+            .ok_or_else(|| Error::new(AttestationError::ReportError))?
+            .iter()
+            .map(|id| {
+                id.as_str()
+                    .map(|id| id.to_string())
+                    .ok_or_else(|| Error::new(AttestationError::ReportError))
+            })
+            .collect(),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Get the platform info blob of an attestation report, which is hex-encoded
+/// and only available for non-OK statuses.
+fn report_platform_info_blob(
+    attn_report: &Value,
+    sgx_quote_status: &SgxQuoteStatus,
+) -> Result<Option<Vec<u8>>> {
+    if *sgx_quote_status == SgxQuoteStatus::OK {
+        return Ok(None);
+    }
+    match attn_report.get("platformInfoBlob") {
+        Some(blob) => {
+            let blob = blob
+                .as_str()
+                .ok_or_else(|| Error::new(AttestationError::ReportError))?;
+            Ok(Some(hex::decode(blob)?))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Verify that an attestation report echoes the nonce of the quote request.
+fn verify_report_nonce(attn_report: &Value, expected_nonce: &str) -> Result<()> {
+    ensure!(
+        attn_report["nonce"].as_str() == Some(expected_nonce),
+        AttestationError::NonceMismatch
+    );
+
+    Ok(())
+}
+
+/// Get the decoded quote body of an attestation report.
+fn report_quote_body(attn_report: &Value) -> Result<Vec<u8>> {
+    let quote_encoded = attn_report["isvEnclaveQuoteBody"]
+        .as_str()
+        .ok_or_else(|| Error::new(AttestationError::ReportError))?;
+    decompress_quote(base64::decode(quote_encoded)?)
+}
+
+/// Get the timestamp of an attestation report.
+fn report_timestamp(attn_report: &Value) -> Result<SystemTime> {
+    let time = attn_report["timestamp"]
+        .as_str()
+        .ok_or_else(|| Error::new(AttestationError::ReportError))?;
+    parse_timestamp(time)
+}
+
+/// Parse the timestamp of an attestation report, which is in UTC and ISO 8601
+/// format without time zone (e.g., `2020-02-11T22:25:59.682915`). Timestamps
+/// without fractional seconds (e.g., `2020-02-11T22:25:59`) are accepted too,
+/// as they have been emitted by IAS as well.
+///
+/// Like Unix time (and thus `SystemTime` and `webpki::Time`), leap seconds are
+/// not counted, i.e., a timestamp within a leap second (`23:59:60.5`) is folded
+/// into the following second.
+fn parse_timestamp(time: &str) -> Result<SystemTime> {
+    let time_fixed = String::from(time) + "+0000";
+    let date_time = DateTime::parse_from_str(&time_fixed, "%Y-%m-%dT%H:%M:%S%.f%z")
+        .or_else(|_| DateTime::parse_from_str(&time_fixed, "%Y-%m-%dT%H:%M:%S%z"))
+        .map_err(|_| AttestationError::TimestampParse)?;
+    // Nanoseconds of a leap second are larger than 1_000_000_000 and carried
+    // into seconds by `Duration::new`.
+    let since_epoch = Duration::new(
+        u64::try_from(date_time.timestamp()).map_err(|_| AttestationError::TimestampParse)?,
+        date_time.timestamp_subsec_nanos(),
+    );
+
+    Ok(UNIX_EPOCH + since_epoch)
+}
+
+/// Age of a report generated at `timestamp` in whole seconds, which has the
+/// same granularity as `webpki::Time` used for checking certificate validity.
+/// A timestamp less than one second, or up to `clock_skew` (in whole seconds),
+/// ahead of `now` is considered as age zero, and a timestamp further ahead
+/// results in `AttestationError::ClockSkew`.
+pub(crate) fn report_age(
+    timestamp: SystemTime,
+    now: SystemTime,
+    clock_skew: Duration,
+) -> Result<Duration> {
+    let age = match now.duration_since(timestamp) {
+        Ok(age) => age,
+        Err(e) if e.duration().as_secs() <= clock_skew.as_secs() => Duration::from_secs(0),
+        Err(e) => bail!(AttestationError::ClockSkew {
+            ahead: e.duration(),
+            clock_skew,
+        }),
+    };
+
+    Ok(Duration::from_secs(age.as_secs()))
+}
+
+/// Maximum size of a decompressed quote body.
+#[cfg(feature = "compressed_quote")]
+const MAX_DECOMPRESSED_QUOTE_SIZE: usize = 64 * 1024;
+
+/// Decompress a quote body compressed by a relay, which is auto-detected by
+/// the gzip (RFC 1952) or zlib (RFC 1950) magic bytes. These never collide
+/// with the version field of an Intel quote. Other bodies are returned
+/// unchanged, as well as all bodies if the `compressed_quote` feature is
+/// disabled.
+fn decompress_quote(bytes: Vec<u8>) -> Result<Vec<u8>> {
+    #[cfg(feature = "compressed_quote")]
+    {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            return gunzip(&bytes);
+        }
+        // CMF of deflate with 32K window, and the check bits of FLG
+        if bytes.len() >= 2
+            && bytes[0] == 0x78
+            && (u16::from(bytes[0]) << 8 | u16::from(bytes[1])) % 31 == 0
+        {
+            return inflate_quote(inflate::InflateStream::from_zlib(), &bytes);
+        }
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(feature = "compressed_quote")]
+fn inflate_quote(mut stream: inflate::InflateStream, bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    let mut pos = 0;
+    loop {
+        let (n, output) = stream
+            .update(&bytes[pos..])
+            .map_err(|e| anyhow!("Quote decompression error: {}", e))?;
+        if output.is_empty() {
+            break;
+        }
+        pos += n;
+        decompressed.extend_from_slice(output);
+        ensure!(
+            decompressed.len() <= MAX_DECOMPRESSED_QUOTE_SIZE,
+            "Decompressed quote is too large."
+        );
+    }
+
+    Ok(decompressed)
+}
+
+/// Decompress a single-member gzip stream and verify its CRC-32 and size.
+#[cfg(feature = "compressed_quote")]
+fn gunzip(bytes: &[u8]) -> Result<Vec<u8>> {
+    const FHCRC: u8 = 0x02;
+    const FEXTRA: u8 = 0x04;
+    const FNAME: u8 = 0x08;
+    const FCOMMENT: u8 = 0x10;
+
+    let error = || anyhow!("Quote decompression error: invalid gzip stream");
+    // ID1, ID2, CM, FLG, MTIME (4), XFL, OS and the trailer of CRC32, ISIZE
+    ensure!(bytes.len() >= 18 && bytes[2] == 8, error());
+    let flags = bytes[3];
+    let (body, trailer) = bytes.split_at(bytes.len() - 8);
+
+    let mut pos = 10;
+    if flags & FEXTRA != 0 {
+        let xlen = body.get(pos..pos + 2).ok_or_else(error)?;
+        pos += 2 + usize::from(u16::from_le_bytes([xlen[0], xlen[1]]));
+    }
+    for flag in &[FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let len = body
+                .get(pos..)
+                .and_then(|b| b.iter().position(|c| *c == 0))
+                .ok_or_else(error)?;
+            pos += len + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        pos += 2;
+    }
+    let deflated = body.get(pos..).ok_or_else(error)?;
+
+    let decompressed = inflate_quote(inflate::InflateStream::new(), deflated)?;
+    let crc = u32::from_le_bytes(<[u8; 4]>::try_from(&trailer[..4])?);
+    let size = u32::from_le_bytes(<[u8; 4]>::try_from(&trailer[4..])?);
+    ensure!(
+        crc == crc32(&decompressed) && size as usize == decompressed.len(),
+        error()
+    );
+
+    Ok(decompressed)
+}
+
+/// CRC-32 (IEEE 802.3) used by gzip.
+#[cfg(feature = "compressed_quote")]
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
 }
 
 #[cfg(all(feature = "enclave_unit_test", feature = "mesalock_sgx"))]
 pub mod tests {
     use super::*;
-    use serde_json::json;
+    use crate::quote::tests::attesation_report;
+    use crate::verifier::{SigningCertPurpose, WebpkiBackend};
     use std::io::Read;
     use std::untrusted::fs::File;
 
@@ -661,109 +1288,988 @@ pub mod tests {
         cert
     }
 
-    fn attesation_report() -> Value {
-        let report = json!({
-            "version": 3,
-            "timestamp": "2020-02-11T22:25:59.682915",
-            "platformInfoBlob": "1502006504000900000D0D02040180030000000000000000000\
-                                 A00000B000000020000000000000B2FE0AE0F7FD4D552BF7EF4\
-                                 C938D44E349F1BD0E76F041362DC52B43B7B25994978D792137\
-                                 90362F6DAE91797ACF5BD5072E45F9A60795D1FFB10140421D8\
-                                 691FFD",
-            "isvEnclaveQuoteStatus": "GROUP_OUT_OF_DATE",
-            "isvEnclaveQuoteBody": "AgABAC8LAAAKAAkAAAAAAK1zRQOIpndiP4IhlnW2AkwAAAAA\
-                                    AAAAAAAAAAAAAAAABQ4CBf+AAAAAAAAAAAAAAAAAAAAAAAAA\
-                                    AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABwAAAAAAAAAHAAAA\
-                                    AAAAADMKqRCjd2eA4gAmrj2sB68OWpMfhPH4MH27hZAvWGlT\
-                                    AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAACD1xnn\
-                                    ferKFHD2uvYqTXdDA8iZ22kCD5xw7h38CMfOngAAAAAAAAAA\
-                                    AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA\
-                                    AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA\
-                                    AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA\
-                                    AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA\
-                                    AAAAAAAAAADYIY9k0MVmCdIDUuFLf/2bGIHAfPjO9nvC7fgz\
-                                    rQedeA3WW4dFeI6oe+RCLdV3XYD1n6lEZjITOzPPLWDxulGz",
-            "id": "53530608302195762335736519878284384788",
-            "epidPseudonym": "NRksaQej8R/SyyHpZXzQGNBXqfrzPy5KCxcmJrEjupXrq3xrm2y2+J\
-                              p0IBVtcW15MCekYs9K3UH82fPyj6F5ciJoMsgEMEIvRR+csX9uyd54\
-                              p+m+/RVyuGYhWbhUcpJigdI5Q3x04GG/A7EP10j/zypwqhYLQh0qN1\
-                              ykYt1N1P0="
+    /// Clock fixed at the given time.
+    struct FixedClock(SystemTime);
+
+    impl TimeSource for FixedClock {
+        fn now(&self) -> SystemTime {
+            self.0
+        }
+    }
+
+    /// A time when the report of the v4 fixture is fresh, i.e., one minute
+    /// after it was generated.
+    fn tls_ra_cert_v4_verified_at() -> SystemTime {
+        parse_timestamp("2022-01-17T22:07:49.022259677").unwrap() + Duration::from_secs(60)
+    }
+
+    /// Certificates of the v4 fixture, and the report verified from them.
+    fn tls_ra_cert_v4_report() -> (Vec<rustls::Certificate>, AttestationReport) {
+        let certs = vec![rustls::Certificate(tls_ra_cert_der_v4())];
+        let clock = FixedClock(tls_ra_cert_v4_verified_at());
+        let report =
+            AttestationReport::from_cert_with_time(&certs, &dcap_root_ca_cert_der(), &clock)
+                .unwrap();
+
+        (certs, report)
+    }
+
+    /// Verifier of the v4 fixture with the default options.
+    fn dcap_verifier() -> AttestationVerifier {
+        AttestationVerifier::new(&dcap_root_ca_cert_der()).unwrap()
+    }
+
+    /// RA-TLS certificate carrying a `ToyQuote`, and its report CA.
+    fn vendor_quote_certs() -> (Vec<u8>, Vec<u8>) {
+        let read = |path| {
+            let mut der = vec![];
+            let mut f = File::open(path).unwrap();
+            f.read_to_end(&mut der).unwrap();
+            der
+        };
+
+        (
+            read("fixtures/vendor_quote/tls_ra_cert.der"),
+            read("fixtures/vendor_quote/root_ca_cert.der"),
+        )
+    }
+
+    /// Quote body of a toy vendor format, i.e., "TOYQ", a version (u16 LE) and
+    /// an SGX enclave report.
+    #[derive(Debug)]
+    struct ToyQuote {
+        version: u16,
+        isv_enclave_report: SgxEnclaveReport,
+    }
+
+    impl QuoteBodyParser for ToyQuote {
+        fn parse_quote_body(bytes: &[u8]) -> Result<Self> {
+            ensure!(bytes.starts_with(b"TOYQ"), "Not a toy quote.");
+            let version = bytes.get(4..6).ok_or(AttestationError::QuoteTruncated)?;
+            Ok(Self {
+                version: u16::from_le_bytes(<[u8; 2]>::try_from(version)?),
+                isv_enclave_report: SgxEnclaveReport::parse_from(&bytes[6..])?,
+            })
+        }
+
+        fn report_data(&self) -> &[u8] {
+            &self.isv_enclave_report.report_data
+        }
+
+        fn isv_enclave_report(&self) -> &SgxEnclaveReport {
+            &self.isv_enclave_report
+        }
+    }
+
+    pub fn test_report_timestamp_second_boundaries() {
+        let ts = parse_timestamp("2020-02-11T22:25:59.682915").unwrap();
+        assert_eq!(
+            ts.duration_since(UNIX_EPOCH).unwrap(),
+            Duration::new(1_581_459_959, 682_915_000)
+        );
+        assert_eq!(
+            parse_timestamp("2020-02-11T22:25:59")
+                .unwrap()
+                .duration_since(UNIX_EPOCH)
+                .unwrap(),
+            Duration::from_secs(1_581_459_959)
+        );
+        assert_eq!(
+            parse_timestamp("2020-02-11T22:25:59.000").unwrap(),
+            parse_timestamp("2020-02-11T22:25:59").unwrap()
+        );
+
+        // Leap second is folded into the following second
+        assert_eq!(
+            parse_timestamp("2016-12-31T23:59:60.5").unwrap(),
+            parse_timestamp("2017-01-01T00:00:00.5").unwrap()
+        );
+        let error = parse_timestamp("2020-02-11 22:25:59").unwrap_err();
+        match error.downcast_ref::<AttestationError>() {
+            Some(AttestationError::TimestampParse) => (),
+            _ => panic!("unexpected error: {:?}", error),
+        }
+
+        let age = |now: SystemTime| report_age(ts, now, Duration::from_secs(0));
+        assert_eq!(age(ts).unwrap(), Duration::from_secs(0));
+        assert_eq!(
+            age(ts + Duration::from_millis(999)).unwrap(),
+            Duration::from_secs(0)
+        );
+        assert_eq!(
+            age(ts + Duration::from_secs(1)).unwrap(),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            age(ts - Duration::from_millis(999)).unwrap(),
+            Duration::from_secs(0)
+        );
+        let err = age(ts - Duration::from_secs(1)).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::ClockSkew { ahead, clock_skew }) => {
+                assert_eq!(*ahead, Duration::from_secs(1));
+                assert_eq!(*clock_skew, Duration::from_secs(0));
+            }
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    pub fn test_decompress_p256_point() {
+        let (_, report) = tls_ra_cert_v4_report();
+        let point = report.report_data();
+
+        let mut compressed = vec![2 + (point[63] & 1)];
+        compressed.extend_from_slice(&point[..32]);
+        assert_eq!(&decompress_p256_point(&compressed).unwrap()[..], &point[..]);
+        compressed[0] ^= 1;
+        let other = decompress_p256_point(&compressed).unwrap();
+        assert_eq!(other[..32], point[..32]);
+        assert_ne!(other[32..], point[32..]);
+
+        // x = 1 is not on the curve, and x must be less than p
+        let mut not_on_curve = vec![2];
+        not_on_curve.extend_from_slice(&[0u8; 31]);
+        not_on_curve.push(1);
+        assert!(decompress_p256_point(&not_on_curve).is_err());
+        let mut too_large = vec![2];
+        too_large.extend_from_slice(&[0xffu8; 32]);
+        assert!(decompress_p256_point(&too_large).is_err());
+        assert!(decompress_p256_point(&point[..33]).is_err());
+    }
+
+    pub fn test_report_advisory_ids() {
+        let attn_report = json!({
+            "isvEnclaveQuoteStatus": "SW_HARDENING_NEEDED",
+            "advisoryIDs": ["INTEL-SA-00334", "INTEL-SA-00615"],
         });
+        assert_eq!(
+            report_advisory_ids(&attn_report).unwrap(),
+            vec!["INTEL-SA-00334", "INTEL-SA-00615"]
+        );
+        assert!(
+            report_advisory_ids(&json!({ "isvEnclaveQuoteStatus": "OK" }))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(report_advisory_ids(&json!({ "advisoryIDs": "INTEL-SA-00334" })).is_err());
+        assert!(report_advisory_ids(&json!({ "advisoryIDs": [334] })).is_err());
+    }
+
+    pub fn test_verify_report_nonce() {
+        let attn_report = json!({ "nonce": "0123456789abcdef" });
+        assert!(verify_report_nonce(&attn_report, "0123456789abcdef").is_ok());
+        for (attn_report, nonce) in &[
+            (attn_report.clone(), "0123456789abcdeg"),
+            (attn_report, ""),
+            (json!({}), "0123456789abcdef"),
+            (json!({ "nonce": 0 }), "0"),
+        ] {
+            match verify_report_nonce(attn_report, nonce)
+                .unwrap_err()
+                .downcast_ref::<AttestationError>()
+            {
+                Some(AttestationError::NonceMismatch) => (),
+                e => panic!("unexpected error: {:?}", e),
+            }
+        }
+
+        // The report in the fixture certificate carries no nonce
+        let certs = vec![rustls::Certificate(tls_ra_cert_der_v4())];
+        let error =
+            AttestationReport::from_cert_with_nonce(&certs, &dcap_root_ca_cert_der(), "nonce")
+                .unwrap_err();
+        match error.downcast_ref::<AttestationError>() {
+            Some(AttestationError::NonceMismatch) => (),
+            _ => panic!("unexpected error: {:?}", error),
+        }
+    }
 
-        report
+    pub fn test_report_platform_info_blob() {
+        let attn_report = json!({
+            "isvEnclaveQuoteStatus": "GROUP_OUT_OF_DATE",
+            "platformInfoBlob": "1502006504000700000f0f",
+        });
+        assert_eq!(
+            report_platform_info_blob(&attn_report, &SgxQuoteStatus::GroupOutOfDate).unwrap(),
+            Some(vec![
+                0x15, 0x02, 0x00, 0x65, 0x04, 0x00, 0x07, 0x00, 0x00, 0x0f, 0x0f
+            ])
+        );
+        assert_eq!(
+            report_platform_info_blob(&attn_report, &SgxQuoteStatus::OK).unwrap(),
+            None
+        );
+        let no_blob = json!({ "isvEnclaveQuoteStatus": "GROUP_OUT_OF_DATE" });
+        assert_eq!(
+            report_platform_info_blob(&no_blob, &SgxQuoteStatus::GroupOutOfDate).unwrap(),
+            None
+        );
+        let invalid = json!({ "platformInfoBlob": "not hex" });
+        assert!(report_platform_info_blob(&invalid, &SgxQuoteStatus::GroupOutOfDate).is_err());
     }
 
-    pub fn test_sgx_quote_parse_from() {
+    pub fn test_decompress_quote() {
         let attn_report = attesation_report();
         let sgx_quote_body_encoded = attn_report["isvEnclaveQuoteBody"].as_str().unwrap();
         let quote_raw = base64::decode(&sgx_quote_body_encoded.as_bytes()).unwrap();
-        let sgx_quote = SgxQuote::parse_from(quote_raw.as_slice()).unwrap();
+        assert_eq!(decompress_quote(quote_raw.clone()).unwrap(), quote_raw);
+
+        #[cfg(feature = "compressed_quote")]
+        {
+            // "Hello, zlib!" and "Hello, gzip!" compressed by zlib and gzip
+            let zlib = vec![
+                0x78, 0x9c, 0xf3, 0x48, 0xcd, 0xc9, 0xc9, 0xd7, 0x51, 0xa8, 0xca, 0xc9, 0x4c, 0x52,
+                0x04, 0x00, 0x1b, 0x65, 0x04, 0x13,
+            ];
+            assert_eq!(decompress_quote(zlib).unwrap(), b"Hello, zlib!");
+
+            let mut gzip = vec![
+                0x1f, 0x8b, 0x08, 0x08, 0x00, 0x00, 0x00, 0x00, 0x02, 0xff, 0x71, 0x00, 0xf3, 0x48,
+                0xcd, 0xc9, 0xc9, 0xd7, 0x51, 0x48, 0xaf, 0xca, 0x2c, 0x50, 0x04, 0x00, 0x3e, 0x3d,
+                0x0f, 0x10, 0x0c, 0x00, 0x00, 0x00,
+            ];
+            assert_eq!(decompress_quote(gzip.clone()).unwrap(), b"Hello, gzip!");
+
+            // Corrupted CRC-32
+            let len = gzip.len();
+            gzip[len - 8] ^= 1;
+            assert!(decompress_quote(gzip).is_err());
+        }
+    }
 
+    pub fn test_verify_issuance_time() {
+        let timestamp = parse_timestamp("2020-02-11T22:25:59.682915").unwrap();
+        let ext = |time: &str| {
+            let time = chrono::DateTime::parse_from_rfc3339(time).unwrap();
+            yasna::construct_der(|writer| {
+                writer.write_generalized_time(&yasna::models::GeneralizedTime::from_datetime(&time))
+            })
+        };
+        let max_deviation = Duration::from_secs(60);
+
+        let issued_at =
+            verify_issuance_time(&ext("2020-02-11T22:26:30Z"), timestamp, max_deviation).unwrap();
         assert_eq!(
-            sgx_quote.version,
-            SgxQuoteVersion::V2(SgxEpidQuoteSigType::Linkable)
+            issued_at.duration_since(UNIX_EPOCH).unwrap(),
+            Duration::from_secs(1_581_459_990)
         );
-        assert_eq!(sgx_quote.gid, 2863);
-        assert_eq!(sgx_quote.isv_svn_qe, 10);
-        assert_eq!(sgx_quote.isv_svn_pce, 9);
-        assert_eq!(
-            sgx_quote.qe_vendor_id,
-            Uuid::parse_str("00000000-ad73-4503-88a6-77623f822196").unwrap()
+        assert!(
+            verify_issuance_time(&ext("2020-02-11T22:25:00Z"), timestamp, max_deviation).is_ok()
         );
-        assert_eq!(
-            sgx_quote.user_data,
-            [117, 182, 2, 76, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        assert!(
+            verify_issuance_time(&ext("2020-02-11T22:27:00Z"), timestamp, max_deviation).is_err()
+        );
+        assert!(
+            verify_issuance_time(&ext("2020-02-11T22:24:59Z"), timestamp, max_deviation).is_err()
         );
+        assert!(verify_issuance_time(&[0x05, 0x00], timestamp, max_deviation).is_err());
+    }
 
-        let isv_enclave_report = sgx_quote.isv_enclave_report;
+    pub fn test_verify_report_signing_cert_revocation() {
+        #[cfg(feature = "report_revocation")]
+        {
+            let read = |name: &str| {
+                let mut der = vec![];
+                let mut f = File::open(format!("fixtures/report_signing_chain/{}", name)).unwrap();
+                f.read_to_end(&mut der).unwrap();
+                der
+            };
+            let root_ca_cert = read("root_ca_cert.der");
+            let certs = vec![read("signing_cert.der"), read("intermediate_ca_cert.der")];
+            let crl = read("intermediate_ca_crl.der");
+            let revoked_crl = read("intermediate_ca_crl_revoked.der");
+            let now = SystemTime::now();
+
+            assert!(
+                verify_report_signing_cert_revocation(&certs, &root_ca_cert, &crl, now).is_ok()
+            );
+            let err =
+                verify_report_signing_cert_revocation(&certs, &root_ca_cert, &revoked_crl, now)
+                    .unwrap_err();
+            match err.downcast_ref::<AttestationError>() {
+                Some(AttestationError::CertRevoked) => (),
+                _ => panic!("unexpected error: {:?}", err),
+            }
+            // Without the intermediate CA, the CRL is checked against the report
+            // CA, which did not issue it.
+            assert!(
+                verify_report_signing_cert_revocation(&certs[..1], &root_ca_cert, &crl, now)
+                    .is_err()
+            );
+            let mut tampered = crl.clone();
+            let last = tampered.len() - 1;
+            tampered[last] ^= 1;
+            assert!(
+                verify_report_signing_cert_revocation(&certs, &root_ca_cert, &tampered, now)
+                    .is_err()
+            );
+        }
+    }
+
+    pub fn test_verified_report_warnings() {
+        let report = |sgx_quote_status| AttestationReport {
+            freshness: Duration::from_secs(0),
+            timestamp: UNIX_EPOCH,
+            sgx_quote_status,
+            sgx_quote_body: (),
+            platform_identity: None,
+            advisory_ids: vec!["INTEL-SA-00334".to_string()],
+            platform_info_blob: None,
+            public_key: Vec::new(),
+            cert_fingerprint: [0u8; 32],
+            clock_used: ClockSource::Injected(UNIX_EPOCH),
+        };
+        let warnings = |status| VerifiedReport::new(report(status)).unwrap().warnings;
+        let advisory_ids = vec!["INTEL-SA-00334".to_string()];
+
+        assert!(warnings(SgxQuoteStatus::OK).is_empty());
         assert_eq!(
-            isv_enclave_report.cpu_svn,
-            [5, 14, 2, 5, 255, 128, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+            warnings(SgxQuoteStatus::GroupOutOfDate),
+            vec![VerificationWarning::TcbOutOfDate(advisory_ids.clone())]
         );
-        assert_eq!(isv_enclave_report.misc_select, 0);
         assert_eq!(
-            isv_enclave_report.attributes,
-            [7, 0, 0, 0, 0, 0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0]
+            warnings(SgxQuoteStatus::SwHardeningNeeded),
+            vec![VerificationWarning::SwHardeningNeeded(advisory_ids.clone())]
         );
         assert_eq!(
-            isv_enclave_report.mr_enclave,
-            [
-                51, 10, 169, 16, 163, 119, 103, 128, 226, 0, 38, 174, 61, 172, 7, 175, 14, 90, 147,
-                31, 132, 241, 248, 48, 125, 187, 133, 144, 47, 88, 105, 83
+            warnings(SgxQuoteStatus::OutOfDateConfigurationNeeded),
+            vec![
+                VerificationWarning::TcbOutOfDate(advisory_ids.clone()),
+                VerificationWarning::ConfigurationNeeded(advisory_ids)
             ]
         );
+        let error = VerifiedReport::new(report(SgxQuoteStatus::GroupRevoked)).unwrap_err();
+        match error.downcast_ref::<AttestationError>() {
+            Some(AttestationError::QuoteStatusUntrusted(SgxQuoteStatus::GroupRevoked)) => (),
+            _ => panic!("unexpected error: {:?}", error),
+        }
+    }
+
+    pub fn test_same_platform() {
+        let report = |platform_identity| AttestationReport {
+            freshness: Duration::from_secs(0),
+            timestamp: UNIX_EPOCH,
+            sgx_quote_status: SgxQuoteStatus::OK,
+            sgx_quote_body: (),
+            platform_identity,
+            advisory_ids: Vec::new(),
+            platform_info_blob: None,
+            public_key: Vec::new(),
+            cert_fingerprint: [0u8; 32],
+            clock_used: ClockSource::Injected(UNIX_EPOCH),
+        };
+        let epid =
+            |pseudonym: &[u8]| report(Some(PlatformIdentity::EpidPseudonym(pseudonym.to_vec())));
+        let ppid = |ppid: &[u8]| report(Some(PlatformIdentity::Ppid(ppid.to_vec())));
+
+        assert_eq!(same_platform(&epid(&[1; 64]), &epid(&[1; 64])), Some(true));
+        assert_eq!(same_platform(&epid(&[1; 64]), &epid(&[2; 64])), Some(false));
+        assert_eq!(same_platform(&ppid(&[1; 16]), &ppid(&[1; 16])), Some(true));
+        assert_eq!(same_platform(&ppid(&[1; 16]), &ppid(&[2; 16])), Some(false));
+        assert_eq!(same_platform(&epid(&[1; 16]), &ppid(&[1; 16])), None);
+        assert_eq!(same_platform(&epid(&[1; 64]), &report(None)), None);
+        assert_eq!(same_platform(&report(None), &ppid(&[1; 16])), None);
+    }
+
+    pub fn test_attestation_report_verify_freshness() {
+        let timestamp = parse_timestamp("2020-02-11T22:25:59.682915").unwrap();
+        let report = AttestationReport {
+            freshness: Duration::from_secs(0),
+            timestamp,
+            sgx_quote_status: SgxQuoteStatus::OK,
+            sgx_quote_body: (),
+            platform_identity: None,
+            advisory_ids: Vec::new(),
+            platform_info_blob: None,
+            public_key: Vec::new(),
+            cert_fingerprint: [0u8; 32],
+            clock_used: ClockSource::Injected(timestamp),
+        };
+        let max_age = Duration::from_secs(60);
+
+        let now = timestamp + Duration::from_secs(60);
+        assert_eq!(report.verify_freshness(max_age, now).unwrap(), max_age);
+
+        let skew = Duration::from_secs(5);
+        let now = timestamp + Duration::from_secs(65);
+        assert_eq!(
+            report
+                .verify_freshness_with_clock_skew(max_age, now, skew)
+                .unwrap(),
+            Duration::from_secs(65)
+        );
+        let now = timestamp - Duration::from_secs(5);
         assert_eq!(
-            isv_enclave_report.mr_signer,
-            [
-                131, 215, 25, 231, 125, 234, 202, 20, 112, 246, 186, 246, 42, 77, 119, 67, 3, 200,
-                153, 219, 105, 2, 15, 156, 112, 238, 29, 252, 8, 199, 206, 158
-            ]
+            report
+                .verify_freshness_with_clock_skew(max_age, now, skew)
+                .unwrap(),
+            Duration::from_secs(0)
         );
-        assert_eq!(isv_enclave_report.isv_prod_id, 0);
-        assert_eq!(isv_enclave_report.isv_svn, 0);
+        assert!(report.verify_freshness(max_age, now).is_err());
+        let now = timestamp - Duration::from_secs(6);
+        let err = report
+            .verify_freshness_with_clock_skew(max_age, now, skew)
+            .unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::ClockSkew { ahead, clock_skew }) => {
+                assert_eq!(*ahead, Duration::from_secs(6));
+                assert_eq!(*clock_skew, skew);
+            }
+            _ => panic!("unexpected error: {:?}", err),
+        }
+        let now = timestamp + Duration::from_secs(66);
+        assert!(report
+            .verify_freshness_with_clock_skew(max_age, now, skew)
+            .is_err());
+
+        let now = timestamp + Duration::from_secs(61);
+        let err = report.verify_freshness(max_age, now).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::Stale { age, max_age }) => {
+                assert_eq!(*age, Duration::from_secs(61));
+                assert_eq!(*max_age, Duration::from_secs(60));
+            }
+            _ => panic!("unexpected error: {:?}", err),
+        }
+
+        let mut report = report;
+        report.freshness = Duration::from_secs(20);
         assert_eq!(
-            isv_enclave_report.report_data.to_vec(),
-            [
-                216, 33, 143, 100, 208, 197, 102, 9, 210, 3, 82, 225, 75, 127, 253, 155, 24, 129,
-                192, 124, 248, 206, 246, 123, 194, 237, 248, 51, 173, 7, 157, 120, 13, 214, 91,
-                135, 69, 120, 142, 168, 123, 228, 66, 45, 213, 119, 93, 128, 245, 159, 169, 68,
-                102, 50, 19, 59, 51, 207, 45, 96, 241, 186, 81, 179
-            ]
-            .to_vec()
+            report.remaining_validity(max_age),
+            Some(Duration::from_secs(40))
+        );
+        assert_eq!(
+            report.remaining_validity(Duration::from_secs(20)),
+            Some(Duration::from_secs(0))
         );
+        assert_eq!(report.remaining_validity(Duration::from_secs(19)), None);
     }
 
     pub fn test_attestation_report_from_cert() {
         let tls_ra_cert = tls_ra_cert_der_v4();
         let dcap_root_ca_cert = dcap_root_ca_cert_der();
         let certs = vec![rustls::Certificate(tls_ra_cert)];
-        let report = AttestationReport::from_cert(&certs, &dcap_root_ca_cert);
+        let clock = FixedClock(tls_ra_cert_v4_verified_at());
+        let report = AttestationReport::from_cert_with_time(&certs, &dcap_root_ca_cert, &clock);
         assert!(report.is_ok());
 
         let report = report.unwrap();
         assert_eq!(report.sgx_quote_status, SgxQuoteStatus::OK);
     }
 
+    pub fn test_attestation_report_from_cert_stale() {
+        let (certs, _) = tls_ra_cert_v4_report();
+        let dcap_root_ca_cert = dcap_root_ca_cert_der();
+        // The report of the fixture was generated in 2022.
+        let root_ca = rustls::Certificate(dcap_root_ca_cert.clone());
+        let errors = vec![
+            AttestationReport::from_cert(&certs, &dcap_root_ca_cert).unwrap_err(),
+            AttestationReport::from_rustls_cert(&certs[0], &root_ca).unwrap_err(),
+            AttestationReport::from_cert_with_warnings(&certs, &dcap_root_ca_cert)
+                .err()
+                .unwrap(),
+        ];
+        for error in errors {
+            match error.downcast_ref::<AttestationError>() {
+                Some(AttestationError::Stale { max_age, .. }) => {
+                    assert_eq!(*max_age, DEFAULT_MAX_AGE)
+                }
+                _ => panic!("unexpected error: {:?}", error),
+            }
+        }
+    }
+
+    pub fn test_attestation_report_from_cert_with_warnings() {
+        let (_, report) = tls_ra_cert_v4_report();
+        assert!(report.advisory_ids.is_empty());
+        let verified = VerifiedReport::new(report);
+        assert!(verified.unwrap().warnings.is_empty());
+    }
+
+    pub fn test_attestation_report_malformed_certificate() {
+        let (certs, _) = tls_ra_cert_v4_report();
+        let dcap_root_ca_cert = dcap_root_ca_cert_der();
+        let malformed = |certs: &[rustls::Certificate]| {
+            let error = AttestationReport::from_cert(certs, &dcap_root_ca_cert).unwrap_err();
+            match error.downcast_ref::<AttestationError>() {
+                Some(AttestationError::MalformedCertificate) => (),
+                _ => panic!("unexpected error: {:?}", error),
+            }
+        };
+        malformed(&[]);
+        malformed(&[rustls::Certificate(Vec::new())]);
+        malformed(&[rustls::Certificate(b"not a certificate".to_vec())]);
+        malformed(&[rustls::Certificate(certs[0].0[..100].to_vec())]);
+        let error = AttestationReport::parse_unverified(&[0x30, 0x03, 0x02]).unwrap_err();
+        match error.downcast_ref::<AttestationError>() {
+            Some(AttestationError::MalformedCertificate) => (),
+            _ => panic!("unexpected error: {:?}", error),
+        }
+    }
+
+    pub fn test_attestation_report_cert_chain_invalid() {
+        let (certs, _) = tls_ra_cert_v4_report();
+        let error = AttestationReport::from_cert(&certs, &ias_root_ca_cert_der()).unwrap_err();
+        match error.downcast_ref::<AttestationError>() {
+            Some(AttestationError::CertChainInvalid) => (),
+            _ => panic!("unexpected error: {:?}", error),
+        }
+        #[cfg(feature = "ias_report_ca")]
+        {
+            assert_eq!(IAS_REPORT_CA, ias_root_ca_cert_der().as_slice());
+            let error = AttestationReport::from_cert_default_ca(&certs).unwrap_err();
+            match error.downcast_ref::<AttestationError>() {
+                Some(AttestationError::CertChainInvalid) => (),
+                _ => panic!("unexpected error: {:?}", error),
+            }
+        }
+    }
+
+    pub fn test_attestation_report_from_cert_with_report_cas() {
+        let (certs, _) = tls_ra_cert_v4_report();
+        let dcap_root_ca_cert = dcap_root_ca_cert_der();
+        let ias_root_ca_cert = ias_root_ca_cert_der();
+        let rotation = [ias_root_ca_cert.as_slice(), dcap_root_ca_cert.as_slice()];
+        let verifier = AttestationVerifier::with_report_cas(&rotation).unwrap();
+        assert!(verifier
+            .verify_at(&certs[0].0, tls_ra_cert_v4_verified_at())
+            .is_ok());
+        let error =
+            AttestationReport::from_cert_with_report_cas(&certs, &rotation[..1]).unwrap_err();
+        match error.downcast_ref::<AttestationError>() {
+            Some(AttestationError::CertChainInvalid) => (),
+            _ => panic!("unexpected error: {:?}", error),
+        }
+        assert!(AttestationReport::from_cert_with_report_cas(&certs, &[]).is_err());
+    }
+
+    pub fn test_attestation_report_public_key() {
+        let (certs, report) = tls_ra_cert_v4_report();
+        assert_eq!(report.public_key.len(), 65);
+        assert_eq!(report.public_key[0], 4);
+        assert_eq!(
+            &report.public_key[1..],
+            &report.sgx_quote_body.isv_enclave_report.report_data[..]
+        );
+
+        let fingerprint = ring::digest::digest(&ring::digest::SHA256, &certs[0].0);
+        assert_eq!(&report.cert_fingerprint()[..], fingerprint.as_ref());
+    }
+
+    pub fn test_attestation_report_parse_unverified() {
+        let (certs, report) = tls_ra_cert_v4_report();
+        let unverified = AttestationReport::parse_unverified(&certs[0].0).unwrap();
+        assert_eq!(unverified.sgx_quote_status, report.sgx_quote_status);
+        assert_eq!(unverified.timestamp, report.timestamp);
+        assert_eq!(unverified.build_identity(), report.build_identity());
+        assert_eq!(unverified.cert_fingerprint, report.cert_fingerprint);
+        assert_eq!(unverified.public_key, report.public_key);
+        assert!(AttestationReport::parse_unverified(&ias_root_ca_cert_der()).is_err());
+    }
+
+    pub fn test_attestation_report_from_cert_with_max_age() {
+        let (certs, report) = tls_ra_cert_v4_report();
+        let dcap_root_ca_cert = dcap_root_ca_cert_der();
+        let error = AttestationReport::from_cert_with_max_age(
+            &certs,
+            &dcap_root_ca_cert,
+            Duration::from_secs(24 * 60 * 60),
+        )
+        .unwrap_err();
+        match error.downcast_ref::<AttestationError>() {
+            Some(AttestationError::Stale { max_age, .. }) => {
+                assert_eq!(*max_age, Duration::from_secs(24 * 60 * 60))
+            }
+            _ => panic!("unexpected error: {:?}", error),
+        }
+        let age = SystemTime::now().duration_since(report.timestamp).unwrap();
+        assert!(AttestationReport::from_cert_with_max_age(
+            &certs,
+            &dcap_root_ca_cert,
+            age + Duration::from_secs(3600)
+        )
+        .is_ok());
+    }
+
+    pub fn test_attestation_report_qe_vendor_id() {
+        let (certs, report) = tls_ra_cert_v4_report();
+        assert!(report.sgx_quote_body.verify_qe_vendor().is_ok());
+        // EPID quotes carry no QE vendor ID
+        let attn_report = attesation_report();
+        let sgx_quote_body_encoded = attn_report["isvEnclaveQuoteBody"].as_str().unwrap();
+        let quote_raw = base64::decode(sgx_quote_body_encoded).unwrap();
+        let sgx_quote = SgxQuote::parse_from(&quote_raw).unwrap();
+        assert!(sgx_quote.verify_qe_vendor().is_ok());
+        let mut verifier = dcap_verifier();
+        verifier.qe_vendor_id = Some(Uuid::nil());
+        let error = verifier
+            .verify_at(&certs[0].0, tls_ra_cert_v4_verified_at())
+            .unwrap_err();
+        match error.downcast_ref::<AttestationError>() {
+            Some(AttestationError::QeVendorIdMismatch) => (),
+            _ => panic!("unexpected error: {:?}", error),
+        }
+        verifier.qe_vendor_id = None;
+        assert!(verifier
+            .verify_at(&certs[0].0, tls_ra_cert_v4_verified_at())
+            .is_ok());
+    }
+
+    pub fn test_attestation_report_quote_versions() {
+        // The fixture carries an ECDSA (version 3) quote.
+        let (certs, _) = tls_ra_cert_v4_report();
+        let mut verifier = dcap_verifier();
+        verifier.quote_versions = Some(SUPPORTED_QUOTE_VERSIONS.to_vec());
+        assert!(verifier
+            .verify_at(&certs[0].0, tls_ra_cert_v4_verified_at())
+            .is_ok());
+        verifier.quote_versions = Some(vec![1, 2]);
+        let error = verifier
+            .verify_at(&certs[0].0, tls_ra_cert_v4_verified_at())
+            .unwrap_err();
+        match error.downcast_ref::<AttestationError>() {
+            Some(AttestationError::UnsupportedQuoteVersion(3)) => (),
+            _ => panic!("unexpected error: {:?}", error),
+        }
+        // The version is checked before the report signature.
+        let mut verifier = AttestationVerifier::new(&ias_root_ca_cert_der()).unwrap();
+        verifier.quote_versions = Some(vec![1, 2]);
+        let error = verifier
+            .verify_at(&certs[0].0, tls_ra_cert_v4_verified_at())
+            .unwrap_err();
+        match error.downcast_ref::<AttestationError>() {
+            Some(AttestationError::UnsupportedQuoteVersion(_)) => (),
+            _ => panic!("unexpected error: {:?}", error),
+        }
+    }
+
+    pub fn test_attestation_report_verify_production() {
+        let (_, report) = tls_ra_cert_v4_report();
+        let mr_signer = report.sgx_quote_body.isv_enclave_report.mr_signer;
+        let attributes = report.sgx_quote_body.isv_enclave_report.sgx_attributes();
+        assert!(attributes.is_debug() && attributes.is_mode64bit());
+        assert!(!attributes.is_provision_key() && !attributes.is_kss());
+        assert!(report.verify_production(&[0u8; 32]).is_err());
+        // The fixture enclave is in debug mode
+        assert!(report.verify_production(&mr_signer).is_err());
+        let error = report
+            .sgx_quote_body
+            .isv_enclave_report
+            .verify_not_debug()
+            .unwrap_err();
+        match error.downcast_ref::<AttestationError>() {
+            Some(AttestationError::NotProductionEnclave("debug mode")) => (),
+            _ => panic!("unexpected error: {:?}", error),
+        }
+    }
+
+    pub fn test_attestation_report_spki_hash_binding() {
+        let (certs, report) = tls_ra_cert_v4_report();
+        let mut verifier = dcap_verifier();
+        verifier.report_data_binding = ReportDataBinding::SpkiHash;
+        let error = verifier
+            .verify_at(&certs[0].0, tls_ra_cert_v4_verified_at())
+            .unwrap_err();
+        match error.downcast_ref::<AttestationError>() {
+            Some(AttestationError::ReportDataMismatch) => (),
+            _ => panic!("unexpected error: {:?}", error),
+        }
+        let spki = crate::cert::tbs_cert_fields(&certs[0].0)
+            .unwrap()
+            .subject_public_key_info;
+        assert!(spki.ends_with(report.report_data()));
+    }
+
+    pub fn test_attestation_report_from_cert_with_report_data() {
+        let (certs, report) = tls_ra_cert_v4_report();
+        let now = tls_ra_cert_v4_verified_at();
+        let mut expected = *report.report_data();
+        let mut verifier = dcap_verifier();
+        verifier.report_data_binding = ReportDataBinding::Expected(expected);
+        assert!(verifier.verify_at(&certs[0].0, now).is_ok());
+        expected[0] ^= 1;
+        verifier.report_data_binding = ReportDataBinding::Expected(expected);
+        let error = verifier.verify_at(&certs[0].0, now).unwrap_err();
+        match error.downcast_ref::<AttestationError>() {
+            Some(AttestationError::ReportDataMismatch) => (),
+            _ => panic!("unexpected error: {:?}", error),
+        }
+    }
+
+    pub fn test_attestation_report_verify_report_data_transcript() {
+        let (_, mut report) = tls_ra_cert_v4_report();
+        assert!(report
+            .verify_report_data_transcript(&[b"hello", b"world"])
+            .is_err());
+        let digest = ring::digest::digest(&ring::digest::SHA256, b"helloworld");
+        report.sgx_quote_body.isv_enclave_report.report_data[..32].copy_from_slice(digest.as_ref());
+        assert!(report
+            .verify_report_data_transcript(&[b"hello", b"world"])
+            .is_ok());
+        assert!(report
+            .verify_report_data_transcript(&[b"helloworld"])
+            .is_ok());
+        assert!(report
+            .verify_report_data_transcript(&[b"hello", b"world", b"!"])
+            .is_err());
+    }
+
+    pub fn test_peek_attestation_type() {
+        assert_eq!(
+            peek_attestation_type(&tls_ra_cert_der_v4()).unwrap(),
+            AttestationType::Ecdsa
+        );
+        assert!(peek_attestation_type(&dcap_root_ca_cert_der()).is_err());
+    }
+
+    pub fn test_attestation_report_from_cert_with_parser() {
+        use crate::policy::{ComplianceProfile, ComplianceProfiles, Policy};
+        use crate::verifier::ReferenceValue;
+        use teaclave_types::EnclaveMeasurement;
+
+        let (cert, report_ca_cert) = vendor_quote_certs();
+        let certs = vec![rustls::Certificate(cert.clone())];
+        // The report is parsed and its signature verified before its age is
+        // checked against DEFAULT_MAX_AGE
+        let err = AttestationReport::<ToyQuote>::from_cert_with_parser(&certs, &report_ca_cert)
+            .unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::Stale { .. }) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+        assert!(AttestationReport::from_cert(&certs, &report_ca_cert).is_err());
+
+        let mut verifier = AttestationVerifier::new(&report_ca_cert).unwrap();
+        verifier.max_age = None;
+        let report = verifier.verify_with_parser::<ToyQuote>(&cert).unwrap();
+        assert_eq!(report.sgx_quote_body.version, 1);
+        assert_eq!(report.sgx_quote_status, SgxQuoteStatus::OK);
+
+        let reference_value = ReferenceValue {
+            mr_enclave: [0x11; 32],
+            mr_signer: [0x22; 32],
+            isv_prod_id: Some(3),
+            min_isv_svn: 4,
+        };
+        let mut other = reference_value.clone();
+        other.min_isv_svn = 5;
+        assert!(report.verify_measurement(&[reference_value]).is_ok());
+        verifier.reference_values = Some(vec![other]);
+        let err = verifier.verify_with_parser::<ToyQuote>(&cert).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::ReferenceValueMismatch) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+
+        let claims = report.as_claims();
+        assert_eq!(claims["isv_prod_id"], 3);
+        assert!(!claims.contains_key("isv_svn_pce"));
+        let policy = Policy::parse(r#"isv_svn >= 4 && !debug && status == "OK""#).unwrap();
+        assert!(policy.verify(&report).unwrap());
+        assert!(Policy::parse("isv_svn_pce >= 0")
+            .unwrap()
+            .verify(&report)
+            .is_err());
+
+        let mut profiles = ComplianceProfiles::new();
+        profiles.register(
+            "production",
+            ComplianceProfile {
+                accepted_measurements: vec![EnclaveMeasurement::new([0x11; 32], [0x22; 32])],
+                accepted_statuses: vec![SgxQuoteStatus::OK],
+                reject_debug: true,
+                policy: Some(policy),
+                ..Default::default()
+            },
+        );
+        profiles.register(
+            "pce",
+            ComplianceProfile {
+                pce_svn_range: Some(0..=u16::MAX),
+                accepted_statuses: vec![SgxQuoteStatus::OK],
+                ..Default::default()
+            },
+        );
+        assert!(report.satisfies(&profiles, "production").unwrap());
+        // The toy quote carries no PCE SVN
+        assert!(!report.satisfies(&profiles, "pce").unwrap());
+    }
+
+    pub fn test_attestation_report_archived() {
+        let (certs, report) = tls_ra_cert_v4_report();
+        // After the report signing certificate expired
+        let clock = ClockSource::External(parse_timestamp("2030-02-03T00:00:00").unwrap());
+        let mut verifier = dcap_verifier();
+        verifier.max_age = None;
+        assert!(verifier.verify_with_clock(&certs[0].0, clock).is_err());
+
+        // The age of archived reports is not bounded by max_age
+        verifier.max_age = Some(DEFAULT_MAX_AGE);
+        verifier.at_report_timestamp = true;
+        let archived = verifier.verify_with_clock(&certs[0].0, clock).unwrap();
+        assert_eq!(archived.timestamp, report.timestamp);
+        assert!(archived.freshness > DEFAULT_MAX_AGE);
+    }
+
+    pub fn test_attestation_report_clock() {
+        let (certs, report) = tls_ra_cert_v4_report();
+        let mut verifier = dcap_verifier();
+        verifier.max_age = None;
+        match verifier.verify(&certs[0].0).unwrap().clock_used {
+            ClockSource::System(now) => assert!(now >= report.timestamp),
+            clock => panic!("unexpected clock: {:?}", clock),
+        }
+        let clock = ClockSource::External(report.timestamp + Duration::from_secs(60));
+        let at = verifier.verify_with_clock(&certs[0].0, clock).unwrap();
+        assert_eq!(at.clock_used, clock);
+        assert_eq!(at.freshness, Duration::from_secs(60));
+    }
+
+    pub fn test_attestation_report_extension_oid() {
+        let (certs, _) = tls_ra_cert_v4_report();
+        let dcap_root_ca_cert = dcap_root_ca_cert_der();
+        // Move the endorsed report to another extension OID
+        let comment_oid = [
+            0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x86, 0xf8, 0x42, 0x01, 0x0d,
+        ];
+        let pos = certs[0]
+            .0
+            .windows(comment_oid.len())
+            .position(|w| w == comment_oid)
+            .unwrap();
+        let mut other_cert = certs[0].0.clone();
+        other_cert[pos + comment_oid.len() - 1] = 0x0e;
+        let other_certs = vec![rustls::Certificate(other_cert)];
+        assert!(AttestationReport::from_cert(&other_certs, &dcap_root_ca_cert).is_err());
+        let mut verifier = dcap_verifier();
+        verifier.cert_ext_oid = vec![2, 16, 840, 1, 113_730, 1, 14];
+        assert!(verifier
+            .verify_at(&other_certs[0].0, tls_ra_cert_v4_verified_at())
+            .is_ok());
+    }
+
+    pub fn test_attestation_report_issuance_time() {
+        let (certs, _) = tls_ra_cert_v4_report();
+        let mut verifier = dcap_verifier();
+        verifier.issuance_time = Some(IssuanceTimeExtension {
+            oid: vec![1, 3, 6, 1, 4, 1, 99_999, 1],
+            max_deviation: Duration::from_secs(60),
+        });
+        assert!(verifier.verify(&certs[0].0).is_err());
+    }
+
+    pub fn test_attestation_report_clock_skew() {
+        let (certs, _) = tls_ra_cert_v4_report();
+        let mut verifier = dcap_verifier();
+        verifier.clock_skew = Duration::from_secs(60);
+        assert!(verifier
+            .verify_at(&certs[0].0, tls_ra_cert_v4_verified_at())
+            .is_ok());
+    }
+
+    pub fn test_attestation_report_verify_config_id() {
+        let (_, report) = tls_ra_cert_v4_report();
+        assert!(report.verify_config_id(&[0u8; 64], 0).is_ok());
+        assert!(report.verify_config_id(&[0u8; 64], 1).is_err());
+        assert!(report.verify_config_id(&[1u8; 64], 0).is_err());
+
+        let (_, mut report) = tls_ra_cert_v4_report();
+        let mut quote_raw = report.sgx_quote_body.raw().unwrap().to_vec();
+        quote_raw[48 + 192..48 + 256].copy_from_slice(&[1u8; 64]);
+        quote_raw[48 + 260] = 2;
+        report.sgx_quote_body = SgxQuote::parse_from(&quote_raw).unwrap();
+        assert_eq!(
+            &report.sgx_quote_body.isv_enclave_report.config_id[..],
+            &[0u8; 64][..]
+        );
+        assert!(report.verify_config_id(&[1u8; 64], 2).is_ok());
+        assert!(report.verify_config_id(&[1u8; 64], 3).is_err());
+        assert!(report.verify_config_id(&[0u8; 64], 0).is_err());
+    }
+
+    pub fn test_attestation_report_build_identity() {
+        let (_, report) = tls_ra_cert_v4_report();
+        let build_identity = report.build_identity();
+        let enclave_report = &report.sgx_quote_body.isv_enclave_report;
+        assert_eq!(build_identity.mr_enclave, enclave_report.mr_enclave);
+        assert_eq!(build_identity.mr_signer, enclave_report.mr_signer);
+        assert_eq!(build_identity.isv_prod_id, 0);
+        assert_eq!(build_identity.isv_svn, 0);
+    }
+
+    pub fn test_attestation_report_from_cert_with_time() {
+        let certs = vec![rustls::Certificate(tls_ra_cert_der_v4())];
+        let dcap_root_ca_cert = dcap_root_ca_cert_der();
+        let timestamp = parse_timestamp("2022-01-17T22:07:49.022259677").unwrap();
+        let clock = FixedClock(timestamp + Duration::from_secs(60));
+
+        let report =
+            AttestationReport::from_cert_with_time(&certs, &dcap_root_ca_cert, &clock).unwrap();
+        assert_eq!(report.freshness, Duration::from_secs(60));
+        assert_eq!(report.clock_used, ClockSource::Injected(clock.0));
+
+        let before_report = FixedClock(timestamp - Duration::from_secs(60));
+        assert!(
+            AttestationReport::from_cert_with_time(&certs, &dcap_root_ca_cert, &before_report)
+                .is_err()
+        );
+        let far_future = FixedClock(UNIX_EPOCH + Duration::from_secs(4_102_444_800));
+        assert!(
+            AttestationReport::from_cert_with_time(&certs, &dcap_root_ca_cert, &far_future)
+                .is_err()
+        );
+        let error =
+            AttestationReport::from_cert_with_time(&certs, &dcap_root_ca_cert, &SystemClock)
+                .unwrap_err();
+        match error.downcast_ref::<AttestationError>() {
+            Some(AttestationError::Stale { .. }) => (),
+            _ => panic!("unexpected error: {:?}", error),
+        }
+    }
+
+    pub fn test_attestation_report_signing_cert_purpose() {
+        let cert = tls_ra_cert_der_v4();
+        let now = tls_ra_cert_v4_verified_at();
+        let mut verifier = dcap_verifier();
+        verifier.signing_cert_purpose = Some(SigningCertPurpose::default());
+        assert!(verifier.verify_at(&cert, now).is_ok());
+
+        // The fixture signing certificate has no extended key usages
+        verifier.signing_cert_purpose = Some(SigningCertPurpose {
+            extended_key_usages: vec![vec![1, 3, 6, 1, 5, 5, 7, 3, 1]],
+            policies: vec![],
+        });
+        let err = verifier.verify_at(&cert, now).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::SigningCertPurposeMismatch) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    pub fn test_attestation_report_signature_backend() {
+        struct RejectingBackend;
+        impl ReportSignatureBackend for RejectingBackend {
+            fn verify(
+                &self,
+                _: &[Vec<u8>],
+                _: &[u8],
+                _: &[u8],
+                _: &[u8],
+                _: SystemTime,
+            ) -> Result<()> {
+                bail!("rejected")
+            }
+        }
+
+        let cert = tls_ra_cert_der_v4();
+        let now = tls_ra_cert_v4_verified_at();
+        let mut verifier = dcap_verifier();
+        verifier.signature_backend = Some(Box::new(WebpkiBackend));
+        assert!(verifier.verify_at(&cert, now).is_ok());
+        verifier.signature_backend = Some(Box::new(RejectingBackend));
+        assert!(verifier.verify_at(&cert, now).is_err());
+    }
+
     pub fn test_attestation_report_from_cert_api_version_not_compatible() {
         let tls_ra_cert = tls_ra_cert_der_v3();
         let ias_root_ca_cert = ias_root_ca_cert_der();
@@ -29,7 +29,7 @@ use std::net::TcpStream;
 use std::prelude::v1::*;
 use std::sync::Arc;
 
-use anyhow::{anyhow, bail, Result};
+use anyhow::{bail, Result};
 use log::{debug, trace};
 use serde_json::json;
 use sgx_types::*;
@@ -229,30 +229,18 @@ fn get_report(
     let signature = header_map
         .get(signature_header)
         .ok_or_else(|| AttestationServiceError::MissingHeader(signature_header.to_string()))?;
-    let signature = base64::decode(signature)?;
 
     debug!("get_signing_cert");
     let signing_cert_header = match algo {
         AttestationAlgorithm::SgxEpid => "X-IASReport-Signing-Certificate",
         AttestationAlgorithm::SgxEcdsa => "X-DCAPReport-Signing-Certificate",
     };
-    let certs: Vec<Vec<u8>> = {
-        let cert_str = header_map.get(signing_cert_header).ok_or_else(|| {
-            AttestationServiceError::MissingHeader(signing_cert_header.to_string())
-        })?;
-        let decoded_cert = percent_encoding::percent_decode_str(cert_str).decode_utf8()?;
-        let certs = rustls::internal::pemfile::certs(&mut decoded_cert.as_bytes())
-            .map_err(|_| anyhow!("pemfile error"))?;
-        certs.iter().map(|c| c.0.clone()).collect()
-    };
+    let certs = header_map
+        .get(signing_cert_header)
+        .ok_or_else(|| AttestationServiceError::MissingHeader(signing_cert_header.to_string()))?;
 
     debug!("return_report");
-    let report = response[header_len..].to_vec();
-    Ok(EndorsedAttestationReport {
-        report,
-        signature,
-        certs,
-    })
+    EndorsedAttestationReport::from_ias_response(&response[header_len..], signature, certs)
 }
 
 fn parse_headers(resp: &httparse::Response) -> HashMap<String, String> {
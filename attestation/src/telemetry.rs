@@ -0,0 +1,201 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! This module records verification decisions as spans following the
+//! OpenTelemetry data model with standardized attribute keys, so that
+//! attestation decisions can be correlated in a distributed tracing backend.
+//! Spans are handed to a `SpanRecorder`, which is usually a thin bridge to the
+//! OpenTelemetry tracer of the service.
+
+use std::prelude::v1::*;
+
+use crate::report::AttestationReport;
+use crate::verifier::AttestationVerifier;
+
+use std::time::SystemTime;
+#[cfg(feature = "mesalock_sgx")]
+use std::untrusted::time::SystemTimeEx;
+
+use anyhow::Result;
+
+/// Name of the span of verifying an attestation report
+pub const SPAN_NAME: &str = "attestation.verify";
+/// MRENCLAVE of the attested enclave in lower-case hex
+pub const SGX_MRENCLAVE: &str = "sgx.mrenclave";
+/// MRSIGNER of the attested enclave in lower-case hex
+pub const SGX_MRSIGNER: &str = "sgx.mrsigner";
+/// Quote status as reported by the attestation service
+pub const SGX_QUOTE_STATUS: &str = "sgx.quote_status";
+/// Verification result, either `success` or `failure`
+pub const ATTESTATION_RESULT: &str = "attestation.result";
+
+/// A finished span of verifying an attestation report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerificationSpan {
+    pub name: &'static str,
+    pub start_time: SystemTime,
+    pub end_time: SystemTime,
+    pub attributes: Vec<(&'static str, String)>,
+    /// Reason of the failure, to be set as the error status of the span
+    pub error: Option<String>,
+}
+
+impl VerificationSpan {
+    /// Value of the attribute with the given key.
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Receiver of verification spans, e.g., an exporter to OpenTelemetry.
+pub trait SpanRecorder {
+    fn record(&self, span: VerificationSpan);
+}
+
+impl AttestationReport {
+    /// Same as `from_cert`, and additionally record the verification decision
+    /// as a span with the `recorder`. The enclave attributes are only
+    /// available if the report is verified successfully.
+    pub fn from_cert_with_span_recorder(
+        certs: &[rustls::Certificate],
+        report_ca_cert: &[u8],
+        recorder: &dyn SpanRecorder,
+    ) -> Result<Self> {
+        Self::record_verification(recorder, || Self::from_cert(certs, report_ca_cert))
+    }
+
+    /// Run `verify` and record its decision as a span with the `recorder`.
+    fn record_verification(
+        recorder: &dyn SpanRecorder,
+        verify: impl FnOnce() -> Result<Self>,
+    ) -> Result<Self> {
+        let start_time = SystemTime::now();
+        let result = verify();
+        let end_time = SystemTime::now();
+
+        let (attributes, error) = match &result {
+            Ok(report) => {
+                let enclave_report = &report.sgx_quote_body.isv_enclave_report;
+                let attributes = vec![
+                    (SGX_MRENCLAVE, hex::encode(enclave_report.mr_enclave)),
+                    (SGX_MRSIGNER, hex::encode(enclave_report.mr_signer)),
+                    (
+                        SGX_QUOTE_STATUS,
+                        report.sgx_quote_status.as_str().to_string(),
+                    ),
+                    (ATTESTATION_RESULT, "success".to_string()),
+                ];
+                (attributes, None)
+            }
+            Err(e) => (
+                vec![(ATTESTATION_RESULT, "failure".to_string())],
+                Some(e.to_string()),
+            ),
+        };
+
+        recorder.record(VerificationSpan {
+            name: SPAN_NAME,
+            start_time,
+            end_time,
+            attributes,
+            error,
+        });
+
+        result
+    }
+}
+
+impl AttestationVerifier {
+    /// Same as `verify`, and additionally record the verification decision as
+    /// a span with the `recorder`.
+    pub fn verify_with_span_recorder(
+        &self,
+        cert: &[u8],
+        recorder: &dyn SpanRecorder,
+    ) -> Result<AttestationReport> {
+        AttestationReport::record_verification(recorder, || self.verify(cert))
+    }
+}
+
+#[cfg(all(feature = "enclave_unit_test", feature = "mesalock_sgx"))]
+pub mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::io::Read;
+    use std::untrusted::fs::File;
+
+    struct Spans(RefCell<Vec<VerificationSpan>>);
+
+    impl SpanRecorder for Spans {
+        fn record(&self, span: VerificationSpan) {
+            self.0.borrow_mut().push(span);
+        }
+    }
+
+    fn read_fixture(path: &str) -> Vec<u8> {
+        let mut bytes = vec![];
+        let mut f = File::open(path).unwrap();
+        f.read_to_end(&mut bytes).unwrap();
+
+        bytes
+    }
+
+    pub fn test_span_recorder() {
+        let certs = vec![rustls::Certificate(read_fixture(
+            "fixtures/tls_ra_cert_v4.der",
+        ))];
+        let dcap_root_ca_cert = read_fixture("fixtures/dcap_root_ca_cert.der");
+        let ias_root_ca_cert = read_fixture("fixtures/ias_root_ca_cert.der");
+        let spans = Spans(RefCell::new(Vec::new()));
+
+        // The report of the fixture is older than `DEFAULT_MAX_AGE`.
+        let mut verifier = AttestationVerifier::new(&dcap_root_ca_cert).unwrap();
+        verifier.max_age = None;
+        let report = verifier
+            .verify_with_span_recorder(&certs[0].0, &spans)
+            .unwrap();
+        assert!(
+            AttestationReport::from_cert_with_span_recorder(&certs, &ias_root_ca_cert, &spans)
+                .is_err()
+        );
+        // The report of the fixture is older than `DEFAULT_MAX_AGE`.
+        assert!(AttestationReport::from_cert_with_span_recorder(
+            &certs,
+            &dcap_root_ca_cert,
+            &spans
+        )
+        .is_err());
+
+        let spans = spans.0.into_inner();
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[0].name, SPAN_NAME);
+        assert_eq!(
+            spans[0].attribute(SGX_MRENCLAVE),
+            Some(hex::encode(report.sgx_quote_body.isv_enclave_report.mr_enclave).as_str())
+        );
+        assert_eq!(spans[0].attribute(SGX_QUOTE_STATUS), Some("OK"));
+        assert_eq!(spans[0].attribute(ATTESTATION_RESULT), Some("success"));
+        assert!(spans[0].error.is_none());
+        assert_eq!(spans[1].attribute(SGX_MRSIGNER), None);
+        assert_eq!(spans[1].attribute(ATTESTATION_RESULT), Some("failure"));
+        assert!(spans[1].error.is_some());
+        assert_eq!(spans[2].attribute(ATTESTATION_RESULT), Some("failure"));
+    }
+}
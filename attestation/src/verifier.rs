@@ -17,12 +17,47 @@
 
 //! This module provides types used to verify attestation reports.
 
-use crate::report::AttestationReport;
+use crate::quote::ct_eq;
+use crate::report::{
+    AttestationReport, ClockSource, IssuanceTimeExtension, QuoteBodyParser, ReportDataBinding,
+    SgxQuote, DEFAULT_MAX_AGE, INTEL_QE_VENDOR_ID, SGX_RA_CERT_EXT_OID,
+};
+use crate::{AttestationError, EndorsedAttestationReport};
 
+use std::collections::HashMap;
+use std::string::String;
+use std::time::{Duration, SystemTime};
 use std::vec::Vec;
 
+use anyhow::{anyhow, bail, ensure, Context, Result};
 use log::{debug, error};
-use teaclave_types::EnclaveAttr;
+use serde::{Deserialize, Deserializer};
+use teaclave_types::{EnclaveAttr, EnclaveInfo};
+use uuid::Uuid;
+use yasna::models::ObjectIdentifier;
+
+/// Signature algorithms accepted in the report signing certificate chain and
+/// for the report signature.
+pub type SignatureAlgorithms = &'static [&'static webpki::SignatureAlgorithm];
+/// Signature algorithms accepted by default.
+pub static SUPPORTED_SIG_ALGS: SignatureAlgorithms = &[
+    &webpki::ECDSA_P256_SHA256,
+    &webpki::ECDSA_P256_SHA384,
+    &webpki::ECDSA_P384_SHA256,
+    &webpki::ECDSA_P384_SHA384,
+    &webpki::RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
+    &webpki::RSA_PSS_2048_8192_SHA384_LEGACY_KEY,
+    &webpki::RSA_PSS_2048_8192_SHA512_LEGACY_KEY,
+    &webpki::RSA_PKCS1_2048_8192_SHA256,
+    &webpki::RSA_PKCS1_2048_8192_SHA384,
+    &webpki::RSA_PKCS1_2048_8192_SHA512,
+    &webpki::RSA_PKCS1_3072_8192_SHA384,
+];
+
+/// OID of the extended key usage extension.
+const EXTENDED_KEY_USAGE_OID: &[u64] = &[2, 5, 29, 37];
+/// OID of the certificate policies extension.
+const CERTIFICATE_POLICIES_OID: &[u64] = &[2, 5, 29, 32];
 
 /// User defined verification function to further verify the attestation report.
 pub type AttestationReportVerificationFn = fn(&AttestationReport) -> bool;
@@ -43,7 +78,10 @@ pub struct AttestationReportVerifier {
 /// Checks if he quote's status is not `UnknownBadStatus`
 pub fn universal_quote_verifier(report: &AttestationReport) -> bool {
     debug!("report.sgx_quote_status: {:?}", report.sgx_quote_status);
-    report.sgx_quote_status != crate::report::SgxQuoteStatus::UnknownBadStatus
+    !matches!(
+        report.sgx_quote_status,
+        crate::report::SgxQuoteStatus::UnknownBadStatus(_)
+    )
 }
 
 impl AttestationReportVerifier {
@@ -73,18 +111,31 @@ impl AttestationReportVerifier {
             .mr_enclave;
 
         self.accepted_enclave_attrs.iter().any(|a| {
-            a.measurement.mr_signer == this_mr_signer && a.measurement.mr_enclave == this_mr_enclave
+            ct_eq(&a.measurement.mr_signer, &this_mr_signer)
+                && ct_eq(&a.measurement.mr_enclave, &this_mr_enclave)
         })
     }
 
     /// Verify TLS certificate.
     fn verify_cert(&self, certs: &[rustls::Certificate]) -> bool {
+        self.verify_cert_with_clock(certs, None)
+    }
+
+    /// Verify TLS certificate at the time given by `clock`, or by the system
+    /// clock if it is `None`.
+    fn verify_cert_with_clock(
+        &self,
+        certs: &[rustls::Certificate],
+        clock: Option<ClockSource>,
+    ) -> bool {
         debug!("verify cert");
         if cfg!(sgx_sim) {
             return true;
         }
 
-        let report = match AttestationReport::from_cert(certs, &self.root_ca) {
+        let report = match AttestationVerifier::new(&self.root_ca)
+            .and_then(|verifier| verifier.verify_certs::<SgxQuote>(certs, clock))
+        {
             Ok(report) => report,
             Err(e) => {
                 error!("cert verification error {:?}", e);
@@ -100,6 +151,738 @@ impl AttestationReportVerifier {
 
         self.verify_measures(&report) && (self.verifier)(&report)
     }
+
+    /// Verify the RA-TLS certificate and return the binding of its public key,
+    /// which can be checked against the certificate of the TLS peer.
+    pub fn channel_binding(&self, certs: &[rustls::Certificate]) -> Result<ChannelBinding> {
+        self.channel_binding_with_clock(certs, None)
+    }
+
+    /// Same as `channel_binding`, but verify the report at `now`.
+    pub fn channel_binding_at(
+        &self,
+        certs: &[rustls::Certificate],
+        now: SystemTime,
+    ) -> Result<ChannelBinding> {
+        self.channel_binding_with_clock(certs, Some(ClockSource::Injected(now)))
+    }
+
+    fn channel_binding_with_clock(
+        &self,
+        certs: &[rustls::Certificate],
+        clock: Option<ClockSource>,
+    ) -> Result<ChannelBinding> {
+        let cert = certs
+            .first()
+            .ok_or_else(|| anyhow!("Certificate is missing."))?;
+        let public_key = crate::cert::tbs_cert_fields(&cert.0)?.subject_public_key_info;
+
+        Ok(ChannelBinding {
+            public_key,
+            verified: self.verify_cert_with_clock(certs, clock),
+        })
+    }
+}
+
+/// Binding of the public key attested in an RA-TLS certificate to a TLS
+/// channel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelBinding {
+    /// DER encoded `SubjectPublicKeyInfo` of the RA-TLS certificate
+    pub public_key: Vec<u8>,
+    /// Whether the attestation report in the certificate is accepted
+    pub verified: bool,
+}
+
+impl ChannelBinding {
+    /// Verify that the attestation is accepted and the TLS peer certificate
+    /// carries the attested public key.
+    pub fn verify_peer_cert(&self, peer_cert: &rustls::Certificate) -> Result<()> {
+        ensure!(self.verified, AttestationError::ReportError);
+        let public_key = crate::cert::tbs_cert_fields(&peer_cert.0)?.subject_public_key_info;
+        ensure!(
+            ct_eq(&public_key, &self.public_key),
+            AttestationError::ChannelBindingMismatch
+        );
+
+        Ok(())
+    }
+}
+
+/// Verifier of reports in a long-lived session which re-attests periodically.
+/// A report older than the last accepted one is rejected, so that an earlier
+/// genuine report cannot be replayed within its freshness window. A report
+/// with the same timestamp as the last accepted one (e.g., the same RA-TLS
+/// certificate presented again on reconnection) is accepted.
+#[derive(Clone, Debug, Default)]
+pub struct SessionVerifier {
+    last_accepted: Option<SystemTime>,
+}
+
+impl SessionVerifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Timestamp of the last accepted report in this session.
+    pub fn last_accepted(&self) -> Option<SystemTime> {
+        self.last_accepted
+    }
+
+    /// Verify that the timestamp of the report is not older than (but may be
+    /// equal to) the last accepted one, and record it on success. Otherwise,
+    /// `AttestationError::TimestampNotMonotonic` is returned.
+    pub fn verify<Q>(&mut self, report: &AttestationReport<Q>) -> Result<()> {
+        if let Some(last_accepted) = self.last_accepted {
+            ensure!(
+                report.timestamp >= last_accepted,
+                AttestationError::TimestampNotMonotonic
+            );
+        }
+        self.last_accepted = Some(report.timestamp);
+
+        Ok(())
+    }
+}
+
+/// Signature algorithms of detached signatures over quotes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DetachedSignatureAlgorithm {
+    /// ECDSA P-256 with SHA-256, the signature is ASN.1 DER encoded and the
+    /// verifying key is an uncompressed point.
+    EcdsaP256Sha256,
+    /// RSA PKCS#1 v1.5 with SHA-256, the verifying key is an ASN.1 DER encoded
+    /// `RSAPublicKey`.
+    RsaPkcs1Sha256,
+    /// Ed25519, the verifying key is 32 bytes.
+    Ed25519,
+}
+
+/// Verify a detached signature over a raw quote, which is distributed by
+/// attestation services signing quotes directly instead of an IAS-like
+/// endorsed attestation report, and then parse the quote.
+pub fn verify_detached(
+    quote: &[u8],
+    signature: &[u8],
+    verifying_key: &[u8],
+    alg: DetachedSignatureAlgorithm,
+) -> Result<SgxQuote> {
+    use ring::signature;
+
+    let alg: &dyn signature::VerificationAlgorithm = match alg {
+        DetachedSignatureAlgorithm::EcdsaP256Sha256 => &signature::ECDSA_P256_SHA256_ASN1,
+        DetachedSignatureAlgorithm::RsaPkcs1Sha256 => &signature::RSA_PKCS1_2048_8192_SHA256,
+        DetachedSignatureAlgorithm::Ed25519 => &signature::ED25519,
+    };
+    signature::UnparsedPublicKey::new(alg, verifying_key)
+        .verify(quote, signature)
+        .map_err(|_| anyhow!("Invalid signature of the quote."))?;
+
+    SgxQuote::parse_from(quote)
+}
+
+/// Expected purpose of the certificate signing attestation reports. Besides
+/// chaining to the root CA, the signing certificate must carry all of the
+/// given extended key usages and certificate policies. Nothing is checked if
+/// both are empty.
+#[derive(Clone, Debug, Default)]
+pub struct SigningCertPurpose {
+    /// OIDs of the required extended key usages
+    pub extended_key_usages: Vec<Vec<u64>>,
+    /// OIDs of the required certificate policies
+    pub policies: Vec<Vec<u64>>,
+}
+
+impl SigningCertPurpose {
+    /// Verify the purpose of the signing certificate in DER.
+    pub fn verify(&self, cert_der: &[u8]) -> Result<()> {
+        if self.extended_key_usages.is_empty() && self.policies.is_empty() {
+            return Ok(());
+        }
+
+        let extensions = crate::cert::extensions(cert_der)?;
+        let extension = |oid: &[u64]| {
+            let oid = ObjectIdentifier::from_slice(oid);
+            extensions.iter().find(|e| e.oid == oid)
+        };
+
+        if !self.extended_key_usages.is_empty() {
+            // ExtKeyUsageSyntax ::= SEQUENCE SIZE (1..MAX) OF KeyPurposeId
+            let usages = match extension(EXTENDED_KEY_USAGE_OID) {
+                Some(e) => yasna::parse_der(&e.value, |r| r.collect_sequence_of(|r| r.read_oid()))?,
+                None => Vec::new(),
+            };
+            ensure!(
+                contains_all(&usages, &self.extended_key_usages),
+                AttestationError::SigningCertPurposeMismatch
+            );
+        }
+
+        if !self.policies.is_empty() {
+            // certificatePolicies ::= SEQUENCE SIZE (1..MAX) OF PolicyInformation
+            // PolicyInformation ::= SEQUENCE {
+            //      policyIdentifier   CertPolicyId,
+            //      policyQualifiers   SEQUENCE SIZE (1..MAX) OF
+            //                         PolicyQualifierInfo OPTIONAL }
+            let policies = match extension(CERTIFICATE_POLICIES_OID) {
+                Some(e) => yasna::parse_der(&e.value, |reader| {
+                    reader.collect_sequence_of(|reader| {
+                        reader.read_sequence(|reader| {
+                            let oid = reader.next().read_oid()?;
+                            reader.read_optional(|reader| reader.read_der())?;
+                            Ok(oid)
+                        })
+                    })
+                })?,
+                None => Vec::new(),
+            };
+            ensure!(
+                contains_all(&policies, &self.policies),
+                AttestationError::SigningCertPurposeMismatch
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn contains_all(oids: &[ObjectIdentifier], expected: &[Vec<u64>]) -> bool {
+    expected
+        .iter()
+        .all(|e| oids.contains(&ObjectIdentifier::from_slice(e)))
+}
+
+/// Backend verifying the certificate chain of the report signing certificate
+/// and the signature of attestation reports, so that the crypto library can be
+/// swapped (e.g., for a FIPS validated one). `WebpkiBackend` is used by
+/// default.
+pub trait ReportSignatureBackend {
+    /// Verify that the signing certificate (`certs[0]`, followed by
+    /// intermediate certificates) chains to `report_ca_cert` and is valid at
+    /// `now`, and that `signature` over `report` is signed by it, e.g., with
+    /// RSA PKCS#1 v1.5 SHA-256 by IAS or with ECDSA by DCAP attestation
+    /// services.
+    fn verify(
+        &self,
+        certs: &[Vec<u8>],
+        report_ca_cert: &[u8],
+        report: &[u8],
+        signature: &[u8],
+        now: SystemTime,
+    ) -> Result<()>;
+}
+
+/// Report signature verification based on webpki (and ring). The report
+/// signature is accepted with any of the supported signature algorithms
+/// matching the public key of the signing certificate.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WebpkiBackend;
+
+impl ReportSignatureBackend for WebpkiBackend {
+    fn verify(
+        &self,
+        certs: &[Vec<u8>],
+        report_ca_cert: &[u8],
+        report: &[u8],
+        signature: &[u8],
+        now: SystemTime,
+    ) -> Result<()> {
+        let trust_anchor = webpki::trust_anchor_util::cert_der_as_trust_anchor(report_ca_cert)?;
+        TrustAnchorsBackend {
+            trust_anchors: &[trust_anchor],
+            sig_algs: SUPPORTED_SIG_ALGS,
+        }
+        .verify(certs, report_ca_cert, report, signature, now)
+    }
+}
+
+/// `WebpkiBackend` with the trust anchors of the report CA parsed in advance
+/// and the accepted signature algorithms.
+pub(crate) struct TrustAnchorsBackend<'a> {
+    pub(crate) trust_anchors: &'a [webpki::TrustAnchor<'a>],
+    pub(crate) sig_algs: SignatureAlgorithms,
+}
+
+impl ReportSignatureBackend for TrustAnchorsBackend<'_> {
+    fn verify(
+        &self,
+        certs: &[Vec<u8>],
+        report_ca_cert: &[u8],
+        report: &[u8],
+        signature: &[u8],
+        now: SystemTime,
+    ) -> Result<()> {
+        ensure!(!certs.is_empty(), "Report signing certificate is missing.");
+        let signing_cert = webpki::EndEntityCert::from(&certs[0])?;
+        let mut chain: Vec<&[u8]> = certs[1..].iter().map(|c| &c[..]).collect();
+        chain.push(report_ca_cert);
+        let time = webpki::Time::try_from(now).map_err(|_| anyhow!("Cannot convert time."))?;
+        signing_cert
+            .verify_is_valid_tls_server_cert(
+                self.sig_algs,
+                &webpki::TLSServerTrustAnchors(self.trust_anchors),
+                &chain,
+                time,
+            )
+            .map_err(|e| anyhow!("{:?}", e).context(AttestationError::CertChainInvalid))?;
+
+        // Verify the signature against the signing cert. Algorithms not
+        // matching the type of its public key are rejected by webpki.
+        let verified = self.sig_algs.iter().any(|alg| {
+            signing_cert
+                .verify_signature(alg, report, signature)
+                .is_ok()
+        });
+        ensure!(verified, AttestationError::SignatureInvalid);
+
+        Ok(())
+    }
+}
+
+/// Verifier of the attestation reports in RA-TLS certificates endorsed by the
+/// same attestation service, e.g., for a gateway terminating many attested TLS
+/// connections. The trust anchors of the report CAs are parsed once on
+/// construction instead of for each certificate.
+///
+/// This is the configurable entry point of report verification: the
+/// `AttestationReport::from_cert*` constructors are shorthands of a verifier
+/// with one option changed from the defaults.
+pub struct AttestationVerifier {
+    report_ca_certs: Vec<Vec<u8>>,
+    /// Root store holding the trust anchors of `report_ca_certs` only
+    root_store: rustls::RootCertStore,
+    /// Reject reports older than the maximum age, by default
+    /// `DEFAULT_MAX_AGE`, unbounded if `None`
+    pub max_age: Option<Duration>,
+    /// Accepted measurements of enclaves, any enclave is accepted if `None`
+    pub reference_values: Option<Vec<ReferenceValue>>,
+    /// Accepted signature algorithms of the report signing certificate chain
+    /// and the report signature, e.g., to only accept ECDSA, by default
+    /// `SUPPORTED_SIG_ALGS`
+    pub sig_algs: SignatureAlgorithms,
+    /// Backend verifying the report signing certificate chain and the report
+    /// signature, e.g., a FIPS validated crypto library, instead of webpki
+    /// with `sig_algs`. The backend is given the first report CA.
+    pub signature_backend: Option<Box<dyn ReportSignatureBackend + Send + Sync>>,
+    /// Expected purpose of the report signing certificate, not checked if
+    /// `None`
+    pub signing_cert_purpose: Option<SigningCertPurpose>,
+    /// Verify the signing certificate chain (and its CRL) at the report
+    /// timestamp instead of the current time, so that archived reports can be
+    /// re-verified after the signing certificate expired. `max_age` is then
+    /// ignored, while the freshness is still measured at the current time.
+    pub at_report_timestamp: bool,
+    /// How the certificate (or an application value) is bound into the
+    /// report data, by default `ReportDataBinding::RawPublicKey`
+    pub report_data_binding: ReportDataBinding,
+    /// OID of the certificate extension carrying the endorsed report, e.g.,
+    /// for certificates generated by other RA-TLS implementations
+    pub cert_ext_oid: Vec<u64>,
+    /// Tolerated skew between the clocks of the attestation service and the
+    /// verifier in both directions, for the report timestamp and the validity
+    /// of the signing certificate
+    pub clock_skew: Duration,
+    /// Measure freshness from the issuance time in the certificate instead of
+    /// the report timestamp, which must be consistent with each other
+    pub issuance_time: Option<IssuanceTimeExtension>,
+    /// Expected vendor ID of the Quoting Enclave of ECDSA quotes (e.g., a
+    /// third-party QE), not checked if `None`, by default
+    /// `INTEL_QE_VENDOR_ID`
+    pub qe_vendor_id: Option<Uuid>,
+    /// Expected nonce sent in the quote request to the attestation service,
+    /// so that a captured report cannot be replayed, not checked if `None`
+    pub nonce: Option<String>,
+    /// Accepted versions of the quote in the report (e.g.,
+    /// `SUPPORTED_QUOTE_VERSIONS`), checked before the report signature so
+    /// that other quotes are rejected early, any version is accepted if `None`
+    pub quote_versions: Option<Vec<u16>>,
+    /// CRL in DER of the issuer of the report signing certificate, the
+    /// revocation is not checked if `None`
+    #[cfg(feature = "report_revocation")]
+    pub report_signing_crl: Option<Vec<u8>>,
+}
+
+impl AttestationVerifier {
+    /// Create a verifier of reports endorsed by `report_ca_cert` in DER.
+    pub fn new(report_ca_cert: &[u8]) -> Result<Self> {
+        Self::with_report_cas(&[report_ca_cert])
+    }
+
+    /// Create a verifier of reports endorsed by any of `report_ca_certs` in
+    /// DER, e.g., both the old and the new CA during a rotation of the
+    /// report CA.
+    pub fn with_report_cas(report_ca_certs: &[&[u8]]) -> Result<Self> {
+        ensure!(
+            !report_ca_certs.is_empty(),
+            "Report CA certificate is missing."
+        );
+        let mut root_store = rustls::RootCertStore::empty();
+        for report_ca_cert in report_ca_certs {
+            root_store.add(&rustls::Certificate(report_ca_cert.to_vec()))?;
+        }
+
+        Ok(Self {
+            report_ca_certs: report_ca_certs.iter().map(|ca| ca.to_vec()).collect(),
+            root_store,
+            max_age: Some(DEFAULT_MAX_AGE),
+            reference_values: None,
+            sig_algs: SUPPORTED_SIG_ALGS,
+            signature_backend: None,
+            signing_cert_purpose: None,
+            at_report_timestamp: false,
+            report_data_binding: ReportDataBinding::RawPublicKey,
+            cert_ext_oid: SGX_RA_CERT_EXT_OID.to_vec(),
+            clock_skew: Duration::from_secs(0),
+            issuance_time: None,
+            qe_vendor_id: Some(INTEL_QE_VENDOR_ID),
+            nonce: None,
+            quote_versions: None,
+            #[cfg(feature = "report_revocation")]
+            report_signing_crl: None,
+        })
+    }
+
+    /// Verify the attestation report in an RA-TLS certificate in DER as
+    /// `AttestationReport::from_cert`, as well as its age and measurement if
+    /// configured.
+    pub fn verify(&self, cert: &[u8]) -> Result<AttestationReport> {
+        self.verify_report(cert, None)
+    }
+
+    /// Same as `verify`, but verify the report at `now` instead of reading
+    /// `SystemTime::now()`, which is an OCALL in enclaves, e.g., with a time
+    /// read once for many handshakes or cached by a background task.
+    pub fn verify_at(&self, cert: &[u8], now: SystemTime) -> Result<AttestationReport> {
+        self.verify_with_clock(cert, ClockSource::Injected(now))
+    }
+
+    /// Same as `verify`, but verify the report at the time given by `clock`,
+    /// which is recorded in `AttestationReport::clock_used`.
+    pub fn verify_with_clock(&self, cert: &[u8], clock: ClockSource) -> Result<AttestationReport> {
+        self.verify_report(cert, Some(clock))
+    }
+
+    /// Same as `verify`, but parse the quote body with the given
+    /// `QuoteBodyParser` instead of the Intel SGX quote layout, e.g., for a
+    /// vendor-specific quote format.
+    pub fn verify_with_parser<Q: QuoteBodyParser>(
+        &self,
+        cert: &[u8],
+    ) -> Result<AttestationReport<Q>> {
+        self.verify_report(cert, None)
+    }
+
+    fn verify_report<Q: QuoteBodyParser>(
+        &self,
+        cert: &[u8],
+        clock: Option<ClockSource>,
+    ) -> Result<AttestationReport<Q>> {
+        let certs = [rustls::Certificate(cert.to_vec())];
+        let report = self.verify_certs(&certs, clock)?;
+        if let Some(reference_values) = &self.reference_values {
+            report.verify_measurement(reference_values)?;
+        }
+
+        Ok(report)
+    }
+
+    /// Verify the attestation report in the first of `certs`, with the quote
+    /// body parsed by `Q`. The measurement is not checked here.
+    pub(crate) fn verify_certs<Q: QuoteBodyParser>(
+        &self,
+        certs: &[rustls::Certificate],
+        clock: Option<ClockSource>,
+    ) -> Result<AttestationReport<Q>> {
+        let trust_anchors: Vec<_> = self
+            .root_store
+            .roots
+            .iter()
+            .map(|root| root.to_trust_anchor())
+            .collect();
+        let default_backend = TrustAnchorsBackend {
+            trust_anchors: &trust_anchors,
+            sig_algs: self.sig_algs,
+        };
+        let backend: &dyn ReportSignatureBackend = match &self.signature_backend {
+            Some(backend) => backend.as_ref(),
+            None => &default_backend,
+        };
+
+        AttestationReport::verify_from_cert(certs, &self.report_ca_certs[0], backend, self, clock)
+    }
+}
+
+/// Acceptable measurements of an enclave, e.g., in a `ReferenceValueManifest`
+/// or an allowlist of `AttestationReport::verify_measurement`.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct ReferenceValue {
+    #[serde(deserialize_with = "measurement_from_hex")]
+    pub mr_enclave: [u8; 32],
+    #[serde(deserialize_with = "measurement_from_hex")]
+    pub mr_signer: [u8; 32],
+    /// Product ID of the enclave, any product ID is accepted if not given
+    #[serde(default)]
+    pub isv_prod_id: Option<u16>,
+    /// Minimum security version number of the enclave
+    #[serde(default)]
+    pub min_isv_svn: u16,
+}
+
+/// A signed manifest of reference values, i.e., acceptable enclave
+/// measurements, distributed by a trusted build pipeline (similar to the
+/// Reference Integrity Manifest of IETF RATS). The manifest is in JSON:
+///
+/// ```json
+/// {
+///   "reference_values": [
+///     {
+///       "mr_enclave": "<hex>",
+///       "mr_signer": "<hex>",
+///       "isv_prod_id": 0,
+///       "min_isv_svn": 0
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+pub struct ReferenceValueManifest {
+    pub reference_values: Vec<ReferenceValue>,
+}
+
+impl ReferenceValue {
+    /// Whether the enclave of the report has the measurements, and its ISV SVN
+    /// is at least the minimum.
+    fn matches<Q: QuoteBodyParser>(&self, report: &AttestationReport<Q>) -> bool {
+        let enclave_report = report.sgx_quote_body.isv_enclave_report();
+        ct_eq(&self.mr_enclave, &enclave_report.mr_enclave)
+            && ct_eq(&self.mr_signer, &enclave_report.mr_signer)
+            && (self.isv_prod_id.is_none() || self.isv_prod_id == Some(enclave_report.isv_prod_id))
+            && enclave_report.isv_svn >= self.min_isv_svn
+    }
+}
+
+impl<Q: QuoteBodyParser> AttestationReport<Q> {
+    /// Verify that the enclave of the report matches one of the `allowed`
+    /// measurements. Otherwise, `AttestationError::ReferenceValueMismatch` is
+    /// returned.
+    pub fn verify_measurement(&self, allowed: &[ReferenceValue]) -> Result<()> {
+        ensure!(
+            allowed.iter().any(|r| r.matches(self)),
+            AttestationError::ReferenceValueMismatch
+        );
+
+        Ok(())
+    }
+}
+
+fn measurement_from_hex<'de, D>(deserializer: D) -> std::result::Result<[u8; 32], D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::de::Error;
+    use std::convert::TryFrom;
+
+    let string = String::deserialize(deserializer)?;
+    let bytes = hex::decode(&string).map_err(|_| Error::custom("Invalid hex string"))?;
+    <[u8; 32]>::try_from(bytes.as_slice()).map_err(|_| Error::custom("Invalid measurement"))
+}
+
+impl ReferenceValueManifest {
+    /// Verify the signatures of the manifest (RSA PKCS#1 SHA-256, the same as
+    /// `EnclaveInfo`) with the public keys of the signers, then load it. At
+    /// least one signature is required and each signature must be verified by
+    /// one of the public keys.
+    pub fn verify_and_new<T, U>(
+        manifest: &[u8],
+        public_keys: &[T],
+        signatures: &[U],
+    ) -> Result<Self>
+    where
+        T: AsRef<[u8]>,
+        U: AsRef<[u8]>,
+    {
+        ensure!(
+            !signatures.is_empty() && signatures.len() <= public_keys.len(),
+            "Invalid number of signatures"
+        );
+        ensure!(
+            EnclaveInfo::verify(manifest, public_keys, signatures),
+            "Invalid reference value manifest"
+        );
+
+        Ok(serde_json::from_slice(manifest)?)
+    }
+
+    /// Verify that the measurements of the report match one of the reference
+    /// values.
+    pub fn verify_report<Q: QuoteBodyParser>(&self, report: &AttestationReport<Q>) -> Result<()> {
+        report.verify_measurement(&self.reference_values)
+    }
+}
+
+/// Minimum security version numbers of enclaves by MRENCLAVE, for fleets
+/// running several enclave builds with different SVN baselines at the same
+/// time.
+#[derive(Clone, Debug, Default)]
+pub struct IsvSvnPolicy {
+    /// Minimum ISV SVN of each enclave build
+    pub min_isv_svn: HashMap<[u8; 32], u16>,
+    /// Minimum ISV SVN of enclave builds not in `min_isv_svn`, which are
+    /// rejected if not given
+    pub default_min_isv_svn: Option<u16>,
+}
+
+impl IsvSvnPolicy {
+    /// Verify that the ISV SVN of the report is not lower than the minimum of
+    /// its MRENCLAVE.
+    pub fn verify<Q: QuoteBodyParser>(&self, report: &AttestationReport<Q>) -> Result<()> {
+        let enclave_report = report.sgx_quote_body.isv_enclave_report();
+        let min_isv_svn = self
+            .min_isv_svn
+            .get(&enclave_report.mr_enclave)
+            .copied()
+            .or(self.default_min_isv_svn)
+            .ok_or(AttestationError::IsvSvnNotAccepted)?;
+        ensure!(
+            enclave_report.isv_svn >= min_isv_svn,
+            AttestationError::IsvSvnNotAccepted
+        );
+
+        Ok(())
+    }
+}
+
+/// PEM label of the attestation report in an `AttestationBundle`.
+const PEM_REPORT_LABEL: &str = "ATTESTATION REPORT";
+/// PEM label of the report signature in an `AttestationBundle`.
+const PEM_REPORT_SIGNATURE_LABEL: &str = "ATTESTATION REPORT SIGNATURE";
+/// PEM label of certificates.
+const PEM_CERTIFICATE_LABEL: &str = "CERTIFICATE";
+
+/// Parse the objects of a PEM file as pairs of the label and the decoded
+/// content. Text outside of the objects, e.g., comments, is ignored.
+fn pem_objects(pem: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+    let pem = std::str::from_utf8(pem).context("Invalid PEM file.")?;
+    let mut objects = Vec::new();
+    let mut current: Option<(&str, String)> = None;
+    for line in pem.lines().map(str::trim) {
+        current = match current {
+            None => line
+                .strip_prefix("-----BEGIN ")
+                .and_then(|line| line.strip_suffix("-----"))
+                .map(|label| (label, String::new())),
+            Some((label, mut content)) => {
+                if let Some(end) = line
+                    .strip_prefix("-----END ")
+                    .and_then(|line| line.strip_suffix("-----"))
+                {
+                    ensure!(end == label, "Mismatched PEM label {}.", end);
+                    let content = base64::decode(&content)
+                        .with_context(|| format!("Invalid PEM object {}.", label))?;
+                    objects.push((label.to_string(), content));
+                    None
+                } else {
+                    content.push_str(line);
+                    Some((label, content))
+                }
+            }
+        };
+    }
+    ensure!(current.is_none(), "Unterminated PEM object.");
+
+    Ok(objects)
+}
+
+/// Attestation evidence archived in a single PEM file, i.e., the report CA
+/// certificates and an endorsed attestation report, in any order:
+///
+/// ```text
+/// -----BEGIN CERTIFICATE-----            (report CA, self-signed)
+/// -----BEGIN CERTIFICATE-----            (signing certificate chain)
+/// -----BEGIN ATTESTATION REPORT-----     (report JSON)
+/// -----BEGIN ATTESTATION REPORT SIGNATURE-----
+/// ```
+#[derive(Debug, Default)]
+pub struct AttestationBundle {
+    /// Report CA certificates in DER
+    pub report_ca_certs: Vec<Vec<u8>>,
+    /// Endorsed report, whose certificates start from the signing certificate
+    pub report: EndorsedAttestationReport,
+}
+
+impl AttestationBundle {
+    /// Split a PEM bundle into the report CA certificates, i.e., the
+    /// self-signed certificates, and the endorsed report. The signing
+    /// certificate is the one of the other certificates not issuing any of
+    /// them, and is placed first in the chain.
+    pub fn from_pem(pem: &[u8]) -> Result<Self> {
+        let mut report_ca_certs = Vec::new();
+        let mut chain = Vec::new();
+        let mut report = None;
+        let mut signature = None;
+        for (label, content) in pem_objects(pem)? {
+            match label.as_str() {
+                PEM_CERTIFICATE_LABEL => {
+                    let fields = crate::cert::tbs_cert_fields(&content)?;
+                    if fields.subject == fields.issuer {
+                        report_ca_certs.push(content);
+                    } else {
+                        chain.push((fields, content));
+                    }
+                }
+                PEM_REPORT_LABEL if report.is_none() => report = Some(content),
+                PEM_REPORT_SIGNATURE_LABEL if signature.is_none() => signature = Some(content),
+                _ => bail!("Unexpected PEM object {} in the bundle.", label),
+            }
+        }
+        ensure!(
+            !report_ca_certs.is_empty(),
+            "Report CA certificate is missing in the bundle."
+        );
+
+        let signing_cert = chain
+            .iter()
+            .position(|(fields, _)| {
+                chain
+                    .iter()
+                    .all(|(other, _)| other.issuer != fields.subject)
+            })
+            .ok_or_else(|| anyhow!("Report signing certificate is missing in the bundle."))?;
+        let (_, signing_cert) = chain.remove(signing_cert);
+        let mut certs = vec![signing_cert];
+        certs.extend(chain.into_iter().map(|(_, cert)| cert));
+
+        Ok(Self {
+            report_ca_certs,
+            report: EndorsedAttestationReport {
+                report: report.ok_or_else(|| anyhow!("Report is missing in the bundle."))?,
+                signature: signature
+                    .ok_or_else(|| anyhow!("Report signature is missing in the bundle."))?,
+                certs,
+            },
+        })
+    }
+
+    /// Verify the signature of the report and its signing certificate chain
+    /// up to any of the report CAs at `now`.
+    pub fn verify(&self, now: SystemTime) -> Result<()> {
+        let trust_anchors = self
+            .report_ca_certs
+            .iter()
+            .map(|ca| webpki::trust_anchor_util::cert_der_as_trust_anchor(ca))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        TrustAnchorsBackend {
+            trust_anchors: &trust_anchors,
+            sig_algs: SUPPORTED_SIG_ALGS,
+        }
+        .verify(
+            &self.report.certs,
+            &self.report_ca_certs[0],
+            &self.report.report,
+            &self.report.signature,
+            now,
+        )
+    }
 }
 
 impl rustls::ServerCertVerifier for AttestationReportVerifier {
@@ -153,3 +936,414 @@ impl rustls::ClientCertVerifier for AttestationReportVerifier {
         }
     }
 }
+
+#[cfg(all(feature = "enclave_unit_test", feature = "mesalock_sgx"))]
+pub mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::untrusted::fs::File;
+
+    fn read_fixture(path: &str) -> Vec<u8> {
+        let mut bytes = vec![];
+        let mut f = File::open(path).unwrap();
+        f.read_to_end(&mut bytes).unwrap();
+
+        bytes
+    }
+
+    pub fn test_session_verifier() {
+        use crate::report::SgxQuoteStatus;
+        use std::time::UNIX_EPOCH;
+
+        let report = |secs| AttestationReport {
+            freshness: Duration::from_secs(0),
+            timestamp: UNIX_EPOCH + Duration::from_secs(secs),
+            sgx_quote_status: SgxQuoteStatus::OK,
+            sgx_quote_body: (),
+            platform_identity: None,
+            advisory_ids: Vec::new(),
+            platform_info_blob: None,
+            public_key: Vec::new(),
+            cert_fingerprint: [0u8; 32],
+            clock_used: ClockSource::Injected(UNIX_EPOCH),
+        };
+
+        let mut session = SessionVerifier::new();
+        assert_eq!(session.last_accepted(), None);
+        assert!(session.verify(&report(10)).is_ok());
+        assert!(session.verify(&report(20)).is_ok());
+        assert_eq!(
+            session.last_accepted(),
+            Some(UNIX_EPOCH + Duration::from_secs(20))
+        );
+        // The same report again
+        assert!(session.verify(&report(20)).is_ok());
+
+        let mut session = SessionVerifier::new();
+        assert!(session.verify(&report(20)).is_ok());
+        let err = session.verify(&report(10)).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::TimestampNotMonotonic) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+        assert_eq!(
+            session.last_accepted(),
+            Some(UNIX_EPOCH + Duration::from_secs(20))
+        );
+    }
+
+    pub fn test_verify_detached() {
+        use ring::signature::{self, KeyPair};
+
+        let mut quote = Vec::new();
+        quote.extend_from_slice(&2u16.to_le_bytes());
+        quote.extend_from_slice(&1u16.to_le_bytes());
+        quote.resize(432, 0);
+
+        let rng = ring::rand::SystemRandom::new();
+        let pkcs8 = signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let key_pair = signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let signature = key_pair.sign(&quote);
+        let public_key = key_pair.public_key().as_ref();
+
+        let alg = DetachedSignatureAlgorithm::Ed25519;
+        assert!(verify_detached(&quote, signature.as_ref(), public_key, alg).is_ok());
+        quote[8] ^= 1;
+        assert!(verify_detached(&quote, signature.as_ref(), public_key, alg).is_err());
+    }
+
+    pub fn test_webpki_backend_intermediate_ca() {
+        let dir = "fixtures/report_signing_chain";
+        let root_ca_cert = read_fixture(&format!("{}/root_ca_cert.der", dir));
+        let intermediate_ca_cert = read_fixture(&format!("{}/intermediate_ca_cert.der", dir));
+        let signing_cert = read_fixture(&format!("{}/signing_cert.der", dir));
+        let report = read_fixture(&format!("{}/report.json", dir));
+        let signature = read_fixture(&format!("{}/report.sig", dir));
+        let now = SystemTime::now();
+
+        let chain = vec![signing_cert.clone(), intermediate_ca_cert.clone()];
+        assert!(WebpkiBackend
+            .verify(&chain, &root_ca_cert, &report, &signature, now)
+            .is_ok());
+        let other_root_ca_cert = read_fixture("fixtures/ias_root_ca_cert.der");
+        assert!(WebpkiBackend
+            .verify(&chain, &other_root_ca_cert, &report, &signature, now)
+            .is_err());
+        let no_intermediate = vec![signing_cert];
+        assert!(WebpkiBackend
+            .verify(&no_intermediate, &root_ca_cert, &report, &signature, now)
+            .is_err());
+        let bundle_with_root = vec![chain[0].clone(), chain[1].clone(), root_ca_cert.clone()];
+        assert!(WebpkiBackend
+            .verify(&bundle_with_root, &root_ca_cert, &report, &signature, now)
+            .is_ok());
+    }
+
+    pub fn test_endorsed_report_from_ias_response() {
+        use crate::EndorsedAttestationReport;
+        use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+
+        let dir = "fixtures/report_signing_chain";
+        let root_ca_cert = read_fixture(&format!("{}/root_ca_cert.der", dir));
+        let intermediate_ca_cert = read_fixture(&format!("{}/intermediate_ca_cert.der", dir));
+        let signing_cert = read_fixture(&format!("{}/signing_cert.der", dir));
+        let report = read_fixture(&format!("{}/report.json", dir));
+        let signature = read_fixture(&format!("{}/report.sig", dir));
+
+        let pem: String = [&signing_cert, &intermediate_ca_cert]
+            .iter()
+            .map(|der| {
+                format!(
+                    "-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n",
+                    base64::encode(der)
+                )
+            })
+            .collect();
+        let cert_header = utf8_percent_encode(&pem, NON_ALPHANUMERIC).to_string();
+        let sig_header = base64::encode(&signature);
+
+        let endorsed =
+            EndorsedAttestationReport::from_ias_response(&report, &sig_header, &cert_header)
+                .unwrap();
+        assert_eq!(endorsed.report, report);
+        assert_eq!(endorsed.signature, signature);
+        assert_eq!(endorsed.certs, vec![signing_cert, intermediate_ca_cert]);
+        assert!(WebpkiBackend
+            .verify(
+                &endorsed.certs,
+                &root_ca_cert,
+                &endorsed.report,
+                &endorsed.signature,
+                SystemTime::now()
+            )
+            .is_ok());
+
+        assert!(
+            EndorsedAttestationReport::from_ias_response(&report, "not base64!", &cert_header)
+                .is_err()
+        );
+        let err =
+            EndorsedAttestationReport::from_ias_response(&report, &sig_header, "").unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::CertChainInvalid) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    pub fn test_attestation_bundle() {
+        let dir = "fixtures/report_signing_chain";
+        let root_ca_cert = read_fixture(&format!("{}/root_ca_cert.der", dir));
+        let intermediate_ca_cert = read_fixture(&format!("{}/intermediate_ca_cert.der", dir));
+        let signing_cert = read_fixture(&format!("{}/signing_cert.der", dir));
+        let report = read_fixture(&format!("{}/report.json", dir));
+        let signature = read_fixture(&format!("{}/report.sig", dir));
+        let pem_object = |label: &str, content: &[u8]| {
+            format!(
+                "-----BEGIN {}-----\n{}\n-----END {}-----\n",
+                label,
+                base64::encode(content),
+                label
+            )
+        };
+
+        let bundle = [
+            "# Archived on 2021-06-01\n".to_string(),
+            pem_object("ATTESTATION REPORT SIGNATURE", &signature),
+            pem_object("CERTIFICATE", &intermediate_ca_cert),
+            "Report CA:\n".to_string(),
+            pem_object("CERTIFICATE", &root_ca_cert),
+            pem_object("ATTESTATION REPORT", &report),
+            pem_object("CERTIFICATE", &signing_cert),
+        ]
+        .concat();
+        let parsed = AttestationBundle::from_pem(bundle.as_bytes()).unwrap();
+        assert_eq!(parsed.report_ca_certs, vec![root_ca_cert]);
+        assert_eq!(
+            parsed.report.certs,
+            vec![signing_cert.clone(), intermediate_ca_cert]
+        );
+        assert_eq!(parsed.report.report, report);
+        assert_eq!(parsed.report.signature, signature);
+        assert!(parsed.verify(SystemTime::now()).is_ok());
+
+        let mut tampered = parsed;
+        tampered.report.report[0] ^= 1;
+        assert!(tampered.verify(SystemTime::now()).is_err());
+
+        let without_ca = [
+            pem_object("CERTIFICATE", &signing_cert),
+            pem_object("ATTESTATION REPORT", &report),
+            pem_object("ATTESTATION REPORT SIGNATURE", &signature),
+        ]
+        .concat();
+        assert!(AttestationBundle::from_pem(without_ca.as_bytes()).is_err());
+        let unknown_object = [bundle.clone(), pem_object("PRIVATE KEY", &[0u8; 4])].concat();
+        assert!(AttestationBundle::from_pem(unknown_object.as_bytes()).is_err());
+        let unterminated = bundle
+            .trim_end()
+            .trim_end_matches("-----END CERTIFICATE-----");
+        assert!(AttestationBundle::from_pem(unterminated.as_bytes()).is_err());
+    }
+
+    pub fn test_webpki_backend_ecdsa_signature() {
+        let dir = "fixtures/report_signing_chain_ecdsa";
+        let root_ca_cert = read_fixture(&format!("{}/root_ca_cert.der", dir));
+        let signing_cert = read_fixture(&format!("{}/signing_cert.der", dir));
+        let report = read_fixture(&format!("{}/report.json", dir));
+        let signature = read_fixture(&format!("{}/report.sig", dir));
+        let now = SystemTime::now();
+
+        let chain = vec![signing_cert];
+        assert!(WebpkiBackend
+            .verify(&chain, &root_ca_cert, &report, &signature, now)
+            .is_ok());
+        let mut tampered = report.clone();
+        tampered[0] ^= 1;
+        let err = WebpkiBackend
+            .verify(&chain, &root_ca_cert, &tampered, &signature, now)
+            .unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::SignatureInvalid) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+        // The RSA signature of the same report is not accepted
+        let rsa_signature = read_fixture("fixtures/report_signing_chain/report.sig");
+        assert!(WebpkiBackend
+            .verify(&chain, &root_ca_cert, &report, &rsa_signature, now)
+            .is_err());
+    }
+
+    pub fn test_attestation_verifier() {
+        let cert = read_fixture("fixtures/tls_ra_cert_v4.der");
+        let dcap_root_ca_cert = read_fixture("fixtures/dcap_root_ca_cert.der");
+        let mut verifier = AttestationVerifier::new(&dcap_root_ca_cert).unwrap();
+        let unverified = AttestationReport::parse_unverified(&cert).unwrap();
+        let now = unverified.timestamp + Duration::from_secs(30);
+        let report = verifier.verify_at(&cert, now).unwrap();
+        assert_eq!(report.build_identity(), unverified.build_identity());
+
+        let enclave_report = &report.sgx_quote_body.isv_enclave_report;
+        let reference_value = ReferenceValue {
+            mr_enclave: enclave_report.mr_enclave,
+            mr_signer: enclave_report.mr_signer,
+            isv_prod_id: None,
+            min_isv_svn: 0,
+        };
+        verifier.reference_values = Some(vec![reference_value.clone()]);
+        assert!(verifier.verify_at(&cert, now).is_ok());
+        verifier.reference_values = Some(vec![ReferenceValue {
+            mr_enclave: [0; 32],
+            ..reference_value
+        }]);
+        let err = verifier.verify_at(&cert, now).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::ReferenceValueMismatch) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+
+        verifier.reference_values = None;
+        static ECDSA_SIG_ALGS: SignatureAlgorithms =
+            &[&webpki::ECDSA_P256_SHA256, &webpki::ECDSA_P384_SHA384];
+        verifier.sig_algs = ECDSA_SIG_ALGS;
+        let err = verifier.verify_at(&cert, now).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::CertChainInvalid) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+
+        verifier.sig_algs = SUPPORTED_SIG_ALGS;
+        // The report of the fixture was generated in 2022.
+        let err = verifier.verify(&cert).unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::Stale { max_age, .. }) => {
+                assert_eq!(*max_age, DEFAULT_MAX_AGE)
+            }
+            _ => panic!("unexpected error: {:?}", err),
+        }
+        verifier.max_age = Some(Duration::from_secs(60));
+        let report_at = verifier.verify_at(&cert, now).unwrap();
+        assert_eq!(report_at.freshness, Duration::from_secs(30));
+        assert_eq!(report_at.clock_used, ClockSource::Injected(now));
+
+        let ias_root_ca_cert = read_fixture("fixtures/ias_root_ca_cert.der");
+        let err = AttestationVerifier::new(&ias_root_ca_cert)
+            .unwrap()
+            .verify(&cert)
+            .unwrap_err();
+        match err.downcast_ref::<AttestationError>() {
+            Some(AttestationError::CertChainInvalid) => (),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+        assert!(AttestationVerifier::new(b"not a certificate").is_err());
+    }
+
+    pub fn test_channel_binding() {
+        let certs = vec![rustls::Certificate(read_fixture(
+            "fixtures/tls_ra_cert_v4.der",
+        ))];
+        let dcap_root_ca_cert = read_fixture("fixtures/dcap_root_ca_cert.der");
+        let report = AttestationReport::parse_unverified(&certs[0].0).unwrap();
+        let enclave_report = &report.sgx_quote_body.isv_enclave_report;
+        let accepted_enclave_attrs = vec![EnclaveAttr {
+            measurement: teaclave_types::EnclaveMeasurement::new(
+                enclave_report.mr_enclave,
+                enclave_report.mr_signer,
+            ),
+        }];
+        let verifier = AttestationReportVerifier::new(
+            accepted_enclave_attrs,
+            &dcap_root_ca_cert,
+            universal_quote_verifier,
+        );
+
+        // The report of the fixture is older than `DEFAULT_MAX_AGE`.
+        let binding = verifier.channel_binding(&certs).unwrap();
+        assert!(!binding.verified);
+        assert!(binding.verify_peer_cert(&certs[0]).is_err());
+        let binding = verifier
+            .channel_binding_at(&certs, report.timestamp)
+            .unwrap();
+        assert!(binding.verified);
+        assert!(binding.verify_peer_cert(&certs[0]).is_ok());
+        let other_cert = rustls::Certificate(dcap_root_ca_cert.clone());
+        assert!(binding.verify_peer_cert(&other_cert).is_err());
+
+        let verifier =
+            AttestationReportVerifier::new(vec![], &dcap_root_ca_cert, universal_quote_verifier);
+        let binding = verifier.channel_binding(&certs).unwrap();
+        assert!(!binding.verified);
+        assert!(binding.verify_peer_cert(&certs[0]).is_err());
+        // Enclave measures are not checked in test mode.
+        let binding = verifier
+            .channel_binding_at(&certs, report.timestamp)
+            .unwrap();
+        assert_eq!(binding.verified, cfg!(test_mode));
+    }
+
+    pub fn test_reference_value_manifest() {
+        let manifest = read_fixture("fixtures/reference_values.json");
+        let public_keys = vec![read_fixture("fixtures/auditors/godzilla.public.der")];
+        let signatures = vec![read_fixture("fixtures/reference_values.json.sign.sha256")];
+        let manifest =
+            ReferenceValueManifest::verify_and_new(&manifest, &public_keys, &signatures).unwrap();
+
+        let certs = vec![rustls::Certificate(read_fixture(
+            "fixtures/tls_ra_cert_v4.der",
+        ))];
+        let dcap_root_ca_cert = read_fixture("fixtures/dcap_root_ca_cert.der");
+        let now = AttestationReport::parse_unverified(&certs[0].0)
+            .unwrap()
+            .timestamp;
+        let report = AttestationVerifier::new(&dcap_root_ca_cert)
+            .unwrap()
+            .verify_at(&certs[0].0, now)
+            .unwrap();
+        assert!(manifest.verify_report(&report).is_ok());
+
+        let mut manifest = manifest;
+        manifest.reference_values[0].min_isv_svn = 1;
+        assert!(manifest.verify_report(&report).is_err());
+
+        let enclave_report = &report.sgx_quote_body.isv_enclave_report;
+        let mut allowed = ReferenceValue {
+            mr_enclave: enclave_report.mr_enclave,
+            mr_signer: enclave_report.mr_signer,
+            isv_prod_id: Some(enclave_report.isv_prod_id),
+            min_isv_svn: enclave_report.isv_svn,
+        };
+        assert!(report.verify_measurement(&[]).is_err());
+        assert!(report.verify_measurement(&[allowed.clone()]).is_ok());
+        allowed.isv_prod_id = Some(enclave_report.isv_prod_id + 1);
+        assert!(report.verify_measurement(&[allowed.clone()]).is_err());
+        allowed.isv_prod_id = None;
+        allowed.mr_signer = [0u8; 32];
+        let mut other = allowed.clone();
+        other.mr_signer = enclave_report.mr_signer;
+        assert!(report.verify_measurement(&[allowed.clone()]).is_err());
+        assert!(report.verify_measurement(&[allowed, other]).is_ok());
+
+        let mut policy = IsvSvnPolicy::default();
+        assert!(policy.verify(&report).is_err());
+        policy.default_min_isv_svn = Some(0);
+        assert!(policy.verify(&report).is_ok());
+        let mr_enclave = report.sgx_quote_body.isv_enclave_report.mr_enclave;
+        policy.min_isv_svn.insert(mr_enclave, 1);
+        assert!(policy.verify(&report).is_err());
+        policy.min_isv_svn.insert([0u8; 32], 0);
+        policy.min_isv_svn.insert(mr_enclave, 0);
+        policy.default_min_isv_svn = None;
+        assert!(policy.verify(&report).is_ok());
+
+        let tampered = b"{\"reference_values\": []}";
+        assert!(
+            ReferenceValueManifest::verify_and_new(tampered, &public_keys, &signatures).is_err()
+        );
+        let no_signatures: Vec<Vec<u8>> = vec![];
+        assert!(ReferenceValueManifest::verify_and_new(
+            &read_fixture("fixtures/reference_values.json"),
+            &public_keys,
+            &no_signatures
+        )
+        .is_err());
+    }
+}